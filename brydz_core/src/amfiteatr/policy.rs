@@ -1,6 +1,461 @@
 use amfiteatr_core::agent::Policy;
+use amfiteatr_core::error::AmfiteatrError;
+use amfiteatr_core::scheme::Scheme;
+use karty::cards::{Card, Card2SymTrait, STANDARD_DECK};
+use karty::figures::{Jack, F10};
+use karty::register::Register;
+use karty::set::{CardSet, CardSetStd, HandSuitedTrait};
+use karty::suits::{Suit, SUITS};
+use karty::symbol::CardSymbol;
+use crate::cards::trump::TrumpGen;
+use crate::contract::{Contract, ContractMechanics, SmartTrickSolver, TrickSolver};
+use crate::player::side::{Side, SideMap, SIDES};
 use crate::amfiteatr::spec::ContractDP;
+use crate::amfiteatr::state::{ContractAction, ContractAgentInfoSetSimple, ContractInfoSet};
 
 //pub trait ContractPolicy: Policy<ContractDP>{}
 
-//impl<P: Policy<ContractDP>> ContractPolicy for P{}
\ No newline at end of file
+//impl<P: Policy<ContractDP>> ContractPolicy for P{}
+
+/// Cards of suit in set, from the highest.
+fn suit_cards(set: &CardSetStd, suit: Suit) -> Vec<Card>{
+    let mut cards: Vec<Card> = set.suit_iterator(&suit).collect();
+    cards.sort_by(|a, b| b.figure().cmp(&a.figure()));
+    cards
+}
+
+/// All cards of suit in deck, from the highest.
+fn deck_suit(suit: Suit) -> Vec<Card>{
+    let mut cards: Vec<Card> = STANDARD_DECK.iter().filter(|c| c.suit() == suit).copied().collect();
+    cards.sort_by(|a, b| b.figure().cmp(&a.figure()));
+    cards
+}
+
+fn is_honour(card: &Card) -> bool{
+    card.figure() >= Jack
+}
+
+/// Suit cards (from the highest) start with at least two touching cards headed by honour.
+fn starts_sequence(cards: &[Card]) -> bool{
+    cards.len() >= 2 && is_honour(&cards[0]) && cards[1].figure() >= F10
+        && cards[0].figure().usize_index() == cards[1].figure().usize_index() + 1
+}
+
+/// Position as seen by agent choosing card: hands it knows and contract in progress.
+struct PlayView<'a>{
+    contract: &'a Contract,
+    solver: SmartTrickSolver<Card>,
+    trump: Option<Suit>,
+    /// Side whose card is played (dummy, when declarer plays for dummy).
+    player: Side,
+    hand: CardSetStd,
+    /// Hands known to agent: own and dummy's (after it is shown).
+    known: SideMap<Option<CardSetStd>>,
+}
+
+impl<'a> PlayView<'a>{
+    /// Returns `None` when agent is not the one to play card.
+    fn new(info_set: &'a ContractAgentInfoSetSimple) -> Option<Self>{
+        let contract = info_set.contract_data();
+        let player = contract.current_side();
+        let known = SideMap::new_with_fn(|side| match side{
+            s if s == info_set.side() => Some(*info_set.hand()),
+            s if s == contract.dummy() => info_set.dummy_hand().copied(),
+            _ => None
+        });
+        if player != info_set.side() && !(player == contract.dummy() && info_set.side() == contract.declarer()){
+            return None
+        }
+        let hand = known[&player]?;
+        let trump = *contract.contract_spec().bid().trump();
+        Some(Self{
+            contract,
+            solver: SmartTrickSolver::new(trump),
+            trump: match trump{
+                TrumpGen::Colored(suit) => Some(suit),
+                TrumpGen::NoTrump => None
+            },
+            player,
+            hand,
+            known,
+        })
+    }
+
+    fn is_declaring(&self) -> bool{
+        self.player == self.contract.declarer() || self.player == self.contract.dummy()
+    }
+
+    fn is_visible(&self, card: &Card) -> bool{
+        SIDES.iter().any(|side| self.known[side].is_some_and(|hand| hand.contains(card)))
+    }
+
+    /// Checks if hidden hand of side may hold card (it is not played, seen or excluded by shown void).
+    fn may_hold(&self, side: Side, card: &Card) -> bool{
+        self.contract.side_possibly_has_card(side, card) && !self.is_visible(card)
+    }
+
+    /// Checks if side may hold card beating `card`, when it is the best card in trick.
+    /// Hidden hand may be void in suit of side card, so it may beat it with any trump it may hold.
+    fn may_beat(&self, side: Side, card: &Card) -> bool{
+        match self.known[&side]{
+            Some(hand) => hand.suit_iterator(&card.suit()).any(|c| c.figure() > card.figure())
+                || (!hand.contains_in_suit(&card.suit())
+                && self.trump.is_some_and(|t| t != card.suit() && hand.contains_in_suit(&t))),
+            None => STANDARD_DECK.iter()
+                .filter(|c| c.suit() == card.suit() && c.figure() > card.figure())
+                .any(|c| self.may_hold(side, c))
+                || self.trump.is_some_and(|t| t != card.suit() && STANDARD_DECK.iter()
+                    .filter(|c| c.suit() == t)
+                    .any(|c| self.may_hold(side, c)))
+        }
+    }
+
+    /// Card can not be beaten by opponents playing after player in current trick.
+    fn is_safe(&self, card: &Card) -> bool{
+        let remaining = 3 - self.contract.current_trick().count_cards();
+        (1..=remaining).map(|i| self.player.next_i(i))
+            .filter(|side| *side != self.player.partner())
+            .all(|side| !self.may_beat(side, card))
+    }
+
+    /// The lowest card in hand separated from given one only by already played cards.
+    fn lowest_equal(&self, card: Card) -> Card{
+        let mut lowest = card;
+        for c in deck_suit(card.suit()).into_iter().filter(|c| c.figure() < card.figure()){
+            if self.hand.contains(&c){
+                lowest = c;
+            } else if !self.contract.card_used().is_registered(&c){
+                break;
+            }
+        }
+        lowest
+    }
+
+    /// Non-empty side suits of hand (cards from the highest), trumps only when there is nothing else.
+    fn lead_suits(&self, hand: &CardSetStd) -> Vec<Vec<Card>>{
+        let mut suits: Vec<Vec<Card>> = SUITS.iter()
+            .map(|suit| suit_cards(hand, *suit))
+            .filter(|cards| !cards.is_empty())
+            .collect();
+        if suits.iter().any(|cards| Some(cards[0].suit()) != self.trump){
+            suits.retain(|cards| Some(cards[0].suit()) != self.trump);
+        }
+        suits
+    }
+
+    /// Top of touching honours, otherwise fourth best of the longest suit
+    /// (from shorter suit the lowest card, or the highest when it has no honour).
+    fn standard_lead(&self) -> Option<Card>{
+        let suits = self.lead_suits(&self.hand);
+        if let Some(cards) = suits.iter()
+            .filter(|cards| starts_sequence(cards))
+            .max_by_key(|cards| cards[0].figure()){
+            return Some(cards[0])
+        }
+        let longest = suits.iter().max_by_key(|cards| cards.len())?;
+        Some(match longest.len(){
+            n if n >= 4 => longest[3],
+            n if longest.iter().any(is_honour) => longest[n - 1],
+            _ => longest[0]
+        })
+    }
+
+    /// Declarer (also in dummy) first ruffs losers in dummy and draws trumps when it has more of them
+    /// than opponents, then cashes winners and leads towards winners in partner's hand.
+    fn declarer_lead(&self) -> Option<Card>{
+        let partner_hand = match self.known[&self.player.partner()]{
+            Some(hand) => hand,
+            None => return self.standard_lead()
+        };
+        let mut opponents_trumps = 0;
+        if let Some(trump) = self.trump{
+            let own_trumps = self.hand.suit_iterator(&trump).count();
+            let partner_trumps = partner_hand.suit_iterator(&trump).count();
+            if self.player == self.contract.declarer() && partner_trumps > 0 && partner_trumps <= own_trumps{
+                let loser = self.lead_suits(&self.hand).into_iter()
+                    .filter(|cards| cards[0].suit() != trump && !partner_hand.contains_in_suit(&cards[0].suit()))
+                    .filter_map(|cards| cards.last().copied())
+                    .find(|card| !self.is_safe(card));
+                if loser.is_some(){
+                    return loser
+                }
+            }
+            opponents_trumps = deck_suit(trump).iter()
+                .filter(|c| !self.contract.card_used().is_registered(*c) && !self.is_visible(c))
+                .count();
+            if opponents_trumps > 0 && own_trumps > 0 && own_trumps + partner_trumps > opponents_trumps{
+                let trumps = suit_cards(&self.hand, trump);
+                return match self.is_safe(&trumps[0]){
+                    true => Some(trumps[0]),
+                    false => trumps.last().copied()
+                }
+            }
+        }
+        let suits = self.lead_suits(&self.hand);
+        if opponents_trumps == 0{
+            if let Some(winner) = suits.iter().map(|cards| cards[0]).find(|card| self.is_safe(card)){
+                return Some(winner)
+            }
+        }
+        suits.iter()
+            .find(|cards| suit_cards(&partner_hand, cards[0].suit()).first()
+                .is_some_and(|top| self.is_safe(top)))
+            .and_then(|cards| cards.last().copied())
+            .or_else(|| self.standard_lead())
+    }
+
+    /// Second hand low (covering honour with honour), third hand high, fourth hand wins as cheaply
+    /// as possible. Nobody overtakes partner's card that no later opponent can beat or ruff.
+    fn follow(&self, called: Suit) -> Option<Card>{
+        let trick = self.contract.current_trick();
+        let partner_wins = self.solver.leader(trick).is_some_and(|side| side == self.player.partner())
+            && trick[self.player.partner()].as_ref().is_some_and(|card| self.is_safe(card));
+        let in_suit = suit_cards(&self.hand, called);
+        let lowest = match in_suit.last(){
+            Some(card) => *card,
+            None => return self.ruff_or_discard(partner_wins)
+        };
+        if partner_wins{
+            return Some(lowest)
+        }
+        let winners: Vec<Card> = in_suit.into_iter()
+            .filter(|card| self.solver.does_beat_leader(trick, card))
+            .collect();
+        let cheapest_winner = winners.last().copied();
+        Some(match trick.count_cards(){
+            1 => match trick.first_card(){
+                Some(led) if is_honour(led) => cheapest_winner.unwrap_or(lowest),
+                _ => lowest
+            },
+            2 => match winners.first(){
+                Some(top) => {
+                    let equal = self.lowest_equal(*top);
+                    match self.solver.does_beat_leader(trick, &equal){
+                        true => equal,
+                        false => *top
+                    }
+                },
+                None => lowest
+            },
+            _ => cheapest_winner.unwrap_or(lowest)
+        })
+    }
+
+    /// When void in called suit: ruffs as low as possible unless partner wins, otherwise discards
+    /// the lowest card of the longest side suit.
+    fn ruff_or_discard(&self, partner_wins: bool) -> Option<Card>{
+        if let (false, Some(trump)) = (partner_wins, self.trump){
+            let trick = self.contract.current_trick();
+            let ruff = suit_cards(&self.hand, trump).into_iter().rev()
+                .find(|card| self.solver.does_beat_leader(trick, card));
+            if ruff.is_some(){
+                return ruff
+            }
+        }
+        self.lead_suits(&self.hand).into_iter()
+            .max_by_key(|cards| cards.len())
+            .and_then(|cards| cards.last().copied())
+    }
+}
+
+/// Deterministic rule based policy for card play, working on [`ContractAgentInfoSetSimple`].
+/// Dummy shows hand and declarer plays cards also for dummy. Policy follows classic heuristics:
+/// - defenders lead top of touching honours or fourth best from the longest suit;
+/// - declarer ruffs losers in dummy and draws trumps when declaring side has more of them,
+///   then cashes sure winners and leads towards winners in partner's hand;
+/// - second hand plays low, but covers an honour with an honour;
+/// - third hand plays high (the lowest of equal top cards);
+/// - fourth hand wins as cheaply as possible;
+/// - nobody overtakes partner's card that no opponent playing later can beat (hidden opponent
+///   may be void, so side suit card is safe only when that opponent can not hold trumps),
+///   player void in called suit ruffs as low as possible or discards from the longest side suit.
+/// # Example:
+/// ```
+/// use brydz_core::amfiteatr::HeuristicPolicy;
+/// use brydz_core::amfiteatr::re_export::agent::Policy;
+/// use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetSimple};
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+/// use brydz_core::player::side::Side::*;
+/// use karty::card_set;
+/// use karty::cards::*;
+/// let mut contract = Contract::new(
+///     ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 3).unwrap()));
+/// let policy = HeuristicPolicy::new();
+/// let east = ContractAgentInfoSetSimple::new(East,
+///     card_set![KING_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_HEARTS, SEVEN_HEARTS, FOUR_HEARTS, TWO_HEARTS],
+///     contract.clone(), None);
+/// assert_eq!(policy.select_action(&east).unwrap(), ContractAction::PlaceCard(KING_SPADES));
+/// contract.insert_card(East, KING_SPADES).unwrap();
+/// // declarer plays for dummy, honour is covered with honour
+/// let north = ContractAgentInfoSetSimple::new(North,
+///     card_set![TWO_SPADES, THREE_SPADES, ACE_HEARTS], contract.clone(),
+///     Some(card_set![ACE_SPADES, FOUR_SPADES, KING_HEARTS]));
+/// assert_eq!(policy.select_action(&north).unwrap(), ContractAction::PlaceCard(ACE_SPADES));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicPolicy{}
+
+impl HeuristicPolicy{
+    pub fn new() -> Self{
+        Self{}
+    }
+}
+
+impl Policy<ContractDP> for HeuristicPolicy{
+    type InfoSetType = ContractAgentInfoSetSimple;
+
+    fn select_action(&self, state: &Self::InfoSetType) -> Result<<ContractDP as Scheme>::ActionType, AmfiteatrError<ContractDP>> {
+        if state.is_dummy(){
+            return Ok(ContractAction::ShowHand(*state.hand()))
+        }
+        let view = PlayView::new(state).ok_or_else(|| AmfiteatrError::NoActionAvailable {
+            context: format!("Heuristic policy of {:?} is not to play", state.side())
+        })?;
+        let card = match view.contract.current_trick().called_suit(){
+            None => match view.is_declaring(){
+                true => view.declarer_lead(),
+                false => view.standard_lead()
+            },
+            Some(called) => view.follow(called)
+        };
+        card.map(ContractAction::PlaceCard).ok_or_else(|| AmfiteatrError::NoActionAvailable {
+            context: format!("Heuristic policy of {:?} has no card to play", state.side())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use amfiteatr_core::agent::{InformationSet, Policy};
+    use amfiteatr_core::env::SequentialGameState;
+    use karty::card_set;
+    use karty::cards::*;
+    use karty::set::CardSetStd;
+    use karty::suits::Suit::{Hearts, Spades};
+    use crate::amfiteatr::HeuristicPolicy;
+    use super::PlayView;
+    use crate::amfiteatr::state::{ContractAction, ContractAgentInfoSetSimple, ContractEnvStateComplete, ContractState};
+    use crate::bidding::Bid;
+    use crate::cards::trump::TrumpGen;
+    use crate::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use crate::deal::fair_bridge_deal;
+    use crate::player::side::{SideMap, Side::*};
+
+    fn contract_with_trick_to_north(trump: TrumpGen<karty::suits::Suit>) -> Contract{
+        let mut contract = Contract::new(ContractParametersGen::new(North, Bid::init(trump, 4).unwrap()));
+        contract.insert_card(East, TWO_CLUBS).unwrap();
+        contract.insert_card(South, THREE_CLUBS).unwrap();
+        contract.insert_card(West, FOUR_CLUBS).unwrap();
+        contract.insert_card(North, ACE_CLUBS).unwrap();
+        contract
+    }
+
+    #[test]
+    fn hidden_hand_may_ruff_side_suit(){
+        for (trump, may_ruff) in [(TrumpGen::Colored(Spades), true), (TrumpGen::NoTrump, false)]{
+            let mut contract = Contract::new(ContractParametersGen::new(North, Bid::init(trump, 1).unwrap()));
+            contract.insert_card(East, TWO_HEARTS).unwrap();
+            contract.insert_card(South, THREE_HEARTS).unwrap();
+            contract.insert_card(West, KING_HEARTS).unwrap();
+            let info_set = ContractAgentInfoSetSimple::new(North, card_set![ACE_HEARTS, ACE_CLUBS], contract,
+                Some(card_set![FIVE_HEARTS]));
+            let view = PlayView::new(&info_set).unwrap();
+            // nobody may hold higher club than ace, but East has not shown void in clubs
+            assert_eq!(view.may_beat(East, &ACE_CLUBS), may_ruff);
+        }
+    }
+
+    #[test]
+    fn second_hand_low_third_hand_high(){
+        let policy = HeuristicPolicy::new();
+        let mut contract = Contract::new(ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+        contract.insert_card(East, TWO_HEARTS).unwrap();
+        let dummy_hand = card_set![KING_SPADES, FOUR_HEARTS, TEN_HEARTS, ACE_HEARTS];
+        let north = ContractAgentInfoSetSimple::new(North, card_set![ACE_SPADES, THREE_HEARTS], contract.clone(), Some(dummy_hand));
+        assert_eq!(policy.select_action(&north).unwrap(), ContractAction::PlaceCard(FOUR_HEARTS));
+        contract.insert_card(South, FOUR_HEARTS).unwrap();
+        let west = ContractAgentInfoSetSimple::new(West,
+            card_set![KING_HEARTS, QUEEN_HEARTS, FIVE_HEARTS, TWO_SPADES], contract.clone(),
+            Some(card_set![KING_SPADES, TEN_HEARTS, ACE_HEARTS]));
+        assert_eq!(policy.select_action(&west).unwrap(), ContractAction::PlaceCard(QUEEN_HEARTS));
+    }
+
+    #[test]
+    fn partner_winner_is_not_overtaken(){
+        let policy = HeuristicPolicy::new();
+        let mut contract = Contract::new(ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+        contract.insert_card(East, KING_HEARTS).unwrap();
+        contract.insert_card(South, FOUR_HEARTS).unwrap();
+        let west = ContractAgentInfoSetSimple::new(West, card_set![ACE_HEARTS, FIVE_HEARTS], contract.clone(),
+            Some(card_set![KING_SPADES, TEN_HEARTS]));
+        assert_eq!(policy.select_action(&west).unwrap(), ContractAction::PlaceCard(FIVE_HEARTS));
+
+        let mut contract = Contract::new(ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+        contract.insert_card(East, QUEEN_HEARTS).unwrap();
+        contract.insert_card(South, FOUR_HEARTS).unwrap();
+        let west = ContractAgentInfoSetSimple::new(West, card_set![ACE_HEARTS, FIVE_HEARTS], contract.clone(),
+            Some(card_set![KING_SPADES, TEN_HEARTS]));
+        assert_eq!(policy.select_action(&west).unwrap(), ContractAction::PlaceCard(ACE_HEARTS));
+    }
+
+    #[test]
+    fn defender_leads_fourth_best(){
+        let policy = HeuristicPolicy::new();
+        let contract = Contract::new(ContractParametersGen::new(North, Bid::init(TrumpGen::Colored(Spades), 2).unwrap()));
+        let east = ContractAgentInfoSetSimple::new(East,
+            card_set![KING_HEARTS, NINE_HEARTS, SEVEN_HEARTS, FIVE_HEARTS, THREE_HEARTS, EIGHT_CLUBS, FOUR_CLUBS, ACE_SPADES],
+            contract, None);
+        assert_eq!(policy.select_action(&east).unwrap(), ContractAction::PlaceCard(FIVE_HEARTS));
+    }
+
+    #[test]
+    fn declarer_draws_trumps(){
+        let policy = HeuristicPolicy::new();
+        let contract = contract_with_trick_to_north(TrumpGen::Colored(Spades));
+        let north = ContractAgentInfoSetSimple::new(North,
+            card_set![ACE_SPADES, KING_SPADES, QUEEN_SPADES, TEN_SPADES, NINE_SPADES, FIVE_HEARTS],
+            contract, Some(card_set![FOUR_SPADES, THREE_SPADES, TWO_SPADES, SEVEN_HEARTS, SEVEN_DIAMONDS]));
+        assert_eq!(policy.select_action(&north).unwrap(), ContractAction::PlaceCard(ACE_SPADES));
+    }
+
+    #[test]
+    fn declarer_ruffs_loser_in_dummy(){
+        let policy = HeuristicPolicy::new();
+        let mut contract = contract_with_trick_to_north(TrumpGen::Colored(Spades));
+        let north = ContractAgentInfoSetSimple::new(North,
+            card_set![ACE_SPADES, KING_SPADES, QUEEN_SPADES, TEN_SPADES, NINE_SPADES, FIVE_HEARTS],
+            contract.clone(), Some(card_set![FOUR_SPADES, THREE_SPADES, TWO_SPADES, SEVEN_DIAMONDS]));
+        assert_eq!(policy.select_action(&north).unwrap(), ContractAction::PlaceCard(FIVE_HEARTS));
+        contract.insert_card(North, FIVE_HEARTS).unwrap();
+        contract.insert_card(East, JACK_HEARTS).unwrap();
+        let north = ContractAgentInfoSetSimple::new(North,
+            card_set![ACE_SPADES, KING_SPADES, QUEEN_SPADES, TEN_SPADES, NINE_SPADES],
+            contract, Some(card_set![FOUR_SPADES, THREE_SPADES, TWO_SPADES, SEVEN_DIAMONDS]));
+        assert_eq!(policy.select_action(&north).unwrap(), ContractAction::PlaceCard(TWO_SPADES));
+    }
+
+    #[test]
+    fn heuristic_agents_play_legal_games(){
+        let policy = HeuristicPolicy::new();
+        for trump in [TrumpGen::NoTrump, TrumpGen::Colored(Hearts)]{
+            for _ in 0..10{
+                let hands = fair_bridge_deal::<CardSetStd>();
+                let contract = Contract::new(ContractParametersGen::new(South, Bid::init(trump, 3).unwrap()));
+                let mut env = ContractEnvStateComplete::new(contract.clone(),
+                    hands[&South], hands[&West], hands[&North], hands[&East]);
+                let mut info_sets = SideMap::new_with_fn(|side|
+                    ContractAgentInfoSetSimple::new(side, hands[&side], contract.clone(), None));
+                while let Some(player) = env.current_player(){
+                    let action = policy.select_action(&info_sets[&player]).unwrap();
+                    assert!(info_sets[&player].is_action_valid(&action));
+                    for (side, update) in env.forward(player, action).unwrap(){
+                        info_sets[&side].update(update).unwrap();
+                    }
+                }
+                assert_eq!(env.contract_data().count_completed_tricks(), 13);
+            }
+        }
+    }
+}
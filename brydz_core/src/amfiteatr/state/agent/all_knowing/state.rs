@@ -45,6 +45,10 @@ impl ContractAgentInfoSetAllKnowing{
     pub fn initial_deal(&self) -> &SideMap<CardSetStd>{
         &self.initial_deal
     }
+    /// Cards remaining in hands of all players.
+    pub fn deal(&self) -> &SideMap<CardSetStd>{
+        &self.deal
+    }
 
     pub fn is_dummy(&self) -> bool {
         self.side == self.contract.dummy()
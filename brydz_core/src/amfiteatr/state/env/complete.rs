@@ -15,6 +15,7 @@ use crate::amfiteatr::spec::ContractDP;
 use crate::amfiteatr::state::{ContractAction, ContractState, ContractStateUpdate};
 use crate::amfiteatr::state::ContractAction::{PlaceCard, ShowHand};
 use crate::bidding::consts::BID_H3;
+use crate::score::{RewardScheme, Vulnerability};
 use std::str::FromStr;
 
 #[derive(Clone, Debug)]
@@ -25,6 +26,9 @@ pub struct ContractEnvStateComplete{
     offside_hand: CardSetStd,
     contract: Contract,
     dummy_shown: bool,
+    vulnerability: Vulnerability,
+    reward_scheme: RewardScheme,
+    reference_score: Option<i32>,
     //fault_of_side: Option<Side>,
 }
 
@@ -37,6 +41,9 @@ impl Default for ContractEnvStateComplete {
             offside_hand:   CardSetStd::from_str("Q792.95.A76.T763").unwrap(),
             contract: Contract::new(ContractParameters::new(Side::South, BID_H3)),
             dummy_shown: false,
+            vulnerability: Vulnerability::None,
+            reward_scheme: RewardScheme::default(),
+            reference_score: None,
             //fault_of_side: None,
         }
     }
@@ -65,9 +72,41 @@ impl ContractEnvStateComplete{
                dummy_hand: CardSetStd, offside_hand: CardSetStd)
                -> Self{
         Self{contract, declarer_hand, whist_hand, dummy_hand, offside_hand, dummy_shown: false,
+            vulnerability: Vulnerability::None, reward_scheme: RewardScheme::default(), reference_score: None,
             //fault_of_side: None
             }
     }
+
+    pub fn with_reward_scheme(mut self, reward_scheme: RewardScheme) -> Self{
+        self.reward_scheme = reward_scheme;
+        self
+    }
+
+    pub fn with_vulnerability(mut self, vulnerability: Vulnerability) -> Self{
+        self.vulnerability = vulnerability;
+        self
+    }
+
+    pub fn reward_scheme(&self) -> &RewardScheme{
+        &self.reward_scheme
+    }
+
+    pub fn set_reward_scheme(&mut self, reward_scheme: RewardScheme){
+        self.reward_scheme = reward_scheme
+    }
+
+    pub fn vulnerability(&self) -> Vulnerability{
+        self.vulnerability
+    }
+
+    /// Reference score (from the perspective of North-South) used by [`RewardScheme::ImpsVsReference`]
+    pub fn reference_score(&self) -> Option<i32>{
+        self.reference_score
+    }
+
+    pub fn set_reference_score(&mut self, reference_score: Option<i32>){
+        self.reference_score = reference_score
+    }
     fn _index_mut(&mut self, index: Side) -> &mut CardSetStd {
         match index - self.contract.declarer(){
             0 => &mut self.declarer_hand,
@@ -233,7 +272,11 @@ impl GameStateWithPayoffs<ContractDP> for ContractEnvStateComplete{
 
 
     fn state_payoff_of_player(&self, agent: &Side) -> <ContractDP as Scheme>::UniversalReward {
-        self.contract.total_tricks_taken_axis(agent.axis()) as i32
+        self.reward_scheme.payoff(&self.contract, agent.axis(), self.vulnerability, self.reference_score)
+            .unwrap_or_else(|e|{
+                error!("Failed calculating payoff of {agent:} using {:?}: {e}", self.reward_scheme);
+                0
+            })
     }
 
 }
@@ -277,6 +320,8 @@ impl Renew<ContractDP, (&ContractParameters, &DescriptionDeckDeal)> for Contract
         self.dummy_hand = descript.cards[&declarer.next_i(2)];
         self.offside_hand = descript.cards[&declarer.next_i(3)];
         self.dummy_shown = false;
+        self.vulnerability = Vulnerability::None;
+        self.reference_score = None;
         Ok(())
     }
 }
@@ -285,11 +330,14 @@ impl From<&ContractGameDescription> for ContractEnvStateComplete {
     fn from(base: &ContractGameDescription) -> Self {
         let contract = Contract::new(base.parameters().clone());
         let declarer = contract.declarer();
-        Self::new(contract,
+        let mut state = Self::new(contract,
                   base.cards()[&declarer],
                   base.cards()[&declarer.next_i(1)],
                   base.cards()[&declarer.next_i(2)],
                   base.cards()[&declarer.next_i(3)])
+            .with_vulnerability(base.vulnerability());
+        state.set_reference_score(base.reference_score());
+        state
     }
 }
 
@@ -303,6 +351,8 @@ impl Renew<ContractDP, &ContractGameDescription> for ContractEnvStateComplete{
         self.dummy_hand = base.cards()[&declarer.next_i(2)];
         self.offside_hand = base.cards()[&declarer.next_i(3)];
         self.dummy_shown = false;
+        self.vulnerability = base.vulnerability();
+        self.reference_score = base.reference_score();
         Ok(())
     }
 }
@@ -10,6 +10,7 @@ use rand_distr::Distribution as RandDistribution;
 use amfiteatr_core::scheme::Renew;
 use crate::amfiteatr::spec::ContractDP;
 use crate::amfiteatr::state::ContractAgentInfoSetSimple;
+use crate::score::Vulnerability;
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
@@ -24,7 +25,12 @@ pub struct ContractGameDescription {
     parameters: ContractParameters,
     //info_sets: SideMap<DistributionTemplate>
     deal_distribution: DealDistribution,
-    cards: SideMap<CardSetStd>
+    cards: SideMap<CardSetStd>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    vulnerability: Vulnerability,
+    /// Reference score of board (e.g. double dummy par) from the perspective of North-South
+    #[cfg_attr(feature = "serde", serde(default))]
+    reference_score: Option<i32>,
 
 }
 
@@ -32,7 +38,17 @@ impl ContractGameDescription {
     pub fn new(parameters: ContractParameters,
                deal_distribution: DealDistribution,
                cards: SideMap<CardSetStd>) -> Self{
-        Self{parameters, deal_distribution, cards}
+        Self{parameters, deal_distribution, cards, vulnerability: Vulnerability::None, reference_score: None}
+    }
+
+    pub fn with_vulnerability(mut self, vulnerability: Vulnerability) -> Self{
+        self.vulnerability = vulnerability;
+        self
+    }
+
+    pub fn with_reference_score(mut self, reference_score: Option<i32>) -> Self{
+        self.reference_score = reference_score;
+        self
     }
 
     pub fn cards(&self) -> &SideMap<CardSetStd>{
@@ -44,6 +60,15 @@ impl ContractGameDescription {
     pub fn distribution(&self) -> &DealDistribution{
         &self.deal_distribution
     }
+    pub fn vulnerability(&self) -> Vulnerability{
+        self.vulnerability
+    }
+    pub fn reference_score(&self) -> Option<i32>{
+        self.reference_score
+    }
+    pub fn set_reference_score(&mut self, reference_score: Option<i32>){
+        self.reference_score = reference_score
+    }
 
     pub fn new_fair_random(rng: &mut ThreadRng) -> Self{
         let dd = DealDistribution::Fair;
@@ -52,7 +77,9 @@ impl ContractGameDescription {
         Self{
             parameters: params,
             deal_distribution: dd,
-            cards
+            cards,
+            vulnerability: Vulnerability::None,
+            reference_score: None
         }
     }
 }
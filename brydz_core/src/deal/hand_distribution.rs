@@ -45,16 +45,30 @@ pub struct HandDistribution{
 ///
 /// ```
 pub fn fair_bridge_deal<H: CardSet>() -> SideMap<H>{
+    fair_bridge_deal_with_rng(&mut rng())
+}
+
+/// Like [`fair_bridge_deal`], but shuffles cards with given random generator, so deal can be reproduced
+/// from seed.
+/// ```
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+/// use brydz_core::deal::fair_bridge_deal_with_rng;
+/// use karty::set::CardSetStd;
+/// let first = fair_bridge_deal_with_rng::<CardSetStd, _>(&mut StdRng::seed_from_u64(7));
+/// let second = fair_bridge_deal_with_rng::<CardSetStd, _>(&mut StdRng::seed_from_u64(7));
+/// assert_eq!(first, second);
+/// ```
+pub fn fair_bridge_deal_with_rng<H: CardSet, R: Rng + ?Sized>(rng: &mut R) -> SideMap<H>{
     let mut result = SideMap::<H>{
         north: H::empty(),
         east: H::empty(),
         south: H::empty(),
         west: H::empty(),
     };
-    let mut rng = rng();
     let mut v  = Vec::from_iter(H::CardType::iterator()); 
     
-    v.shuffle(&mut rng);
+    v.shuffle(rng);
     let hand_size = v.len()/4;
     /*let north = &v[..hand_size];
     let east = &v[hand_size..2*hand_size];
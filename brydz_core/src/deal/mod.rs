@@ -1,4 +1,5 @@
 mod hand_distribution;
+mod pbn;
 #[cfg(feature = "amfiteatr")]
 mod biased_hand_distribution;
 #[cfg(feature = "amfiteatr")]
@@ -11,6 +12,7 @@ mod deal_distribution;
 //pub mod set;
 
 pub use hand_distribution::*;
+pub use pbn::*;
 #[cfg(feature = "amfiteatr")]
 pub use biased_hand_distribution::*;
 #[cfg(feature = "amfiteatr")]
@@ -0,0 +1,179 @@
+use std::str::FromStr;
+use karty::set::CardSetStd;
+use crate::bidding::{Bid, Doubling};
+use crate::bidding::parser::parse_bid;
+use crate::contract::ContractParameters;
+use crate::error::PbnError;
+use crate::player::side::{Side, SideMap};
+use crate::score::Vulnerability;
+
+/// Board read from Portable Bridge Notation file. Only tags describing deal and final contract
+/// are interpreted, auction and play sections are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PbnBoard{
+    pub hands: SideMap<CardSetStd>,
+    pub dealer: Option<Side>,
+    pub vulnerability: Vulnerability,
+    pub contract: Option<ContractParameters>,
+}
+
+fn bad_value(tag: &str, value: &str) -> PbnError{
+    PbnError::BadTagValue{tag: tag.to_owned(), value: value.to_owned()}
+}
+
+fn parse_side(tag: &str, value: &str) -> Result<Side, PbnError>{
+    match value.trim().to_ascii_uppercase().as_str(){
+        "N" => Ok(Side::North),
+        "E" => Ok(Side::East),
+        "S" => Ok(Side::South),
+        "W" => Ok(Side::West),
+        _ => Err(bad_value(tag, value))
+    }
+}
+
+fn parse_vulnerability(value: &str) -> Result<Vulnerability, PbnError>{
+    match value.trim().to_ascii_uppercase().as_str(){
+        "NONE" | "LOVE" | "-" => Ok(Vulnerability::None),
+        "NS" => Ok(Vulnerability::NorthSouth),
+        "EW" => Ok(Vulnerability::EastWest),
+        "ALL" | "BOTH" => Ok(Vulnerability::Both),
+        _ => Err(bad_value("Vulnerable", value))
+    }
+}
+
+/// Parses value of `Deal` tag, e.g. `N:AKQ.JT9.876.5432 ...` with hands listed clockwise.
+/// ```
+/// use std::str::FromStr;
+/// use brydz_core::deal::parse_pbn_deal;
+/// use brydz_core::karty::set::CardSetStd;
+/// let hands = parse_pbn_deal("E:KQJ.87.832.JT843 932.A42.KQ976.K9 AT86.KJT93.4T.2A 754.Q65.AJ5.Q765").unwrap();
+/// assert_eq!(hands.east, CardSetStd::from_str("KQJ.87.832.JT843").unwrap());
+/// assert_eq!(hands.north, CardSetStd::from_str("754.Q65.AJ5.Q765").unwrap());
+/// assert!(parse_pbn_deal("N:KQJ.87.832.JT843 - - -").is_err());
+/// ```
+pub fn parse_pbn_deal(value: &str) -> Result<SideMap<CardSetStd>, PbnError>{
+    let (first, hands) = value.trim().split_once(':').ok_or_else(|| bad_value("Deal", value))?;
+    let mut side = parse_side("Deal", first)?;
+    let mut result: SideMap<Option<CardSetStd>> = SideMap::default();
+    let mut count = 0;
+    for hand in hands.split_whitespace(){
+        result[&side] = Some(CardSetStd::from_str(hand).map_err(|_| bad_value("Deal", value))?);
+        side = side.next();
+        count += 1;
+    }
+    match (count, result.destruct()){
+        (4, (Some(north), Some(east), Some(south), Some(west))) => Ok(SideMap::new(north, east, south, west)),
+        _ => Err(bad_value("Deal", value))
+    }
+}
+
+fn parse_contract(value: &str) -> Result<Option<(Bid<karty::suits::Suit>, Doubling)>, PbnError>{
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("pass"){
+        return Ok(None)
+    }
+    let (rest, bid) = parse_bid(value).map_err(|_| bad_value("Contract", value))?;
+    let doubling = match rest.trim().to_ascii_uppercase().as_str(){
+        "" => Doubling::None,
+        "X" => Doubling::Double,
+        "XX" => Doubling::Redouble,
+        _ => return Err(bad_value("Contract", value))
+    };
+    Ok(Some((bid, doubling)))
+}
+
+#[derive(Default)]
+struct PendingBoard{
+    hands: Option<SideMap<CardSetStd>>,
+    dealer: Option<Side>,
+    vulnerability: Vulnerability,
+    contract: Option<(Bid<karty::suits::Suit>, Doubling)>,
+    declarer: Option<Side>,
+    started: bool,
+}
+
+impl PendingBoard{
+    fn finish(self, line: usize) -> Result<Option<PbnBoard>, PbnError>{
+        if !self.started{
+            return Ok(None)
+        }
+        let hands = self.hands.ok_or(PbnError::MissingDeal{line})?;
+        let contract = match (self.contract, self.declarer){
+            (Some((bid, doubling)), Some(declarer)) => Some(ContractParameters::new_d(declarer, bid, doubling)),
+            _ => None
+        };
+        Ok(Some(PbnBoard{hands, dealer: self.dealer, vulnerability: self.vulnerability, contract}))
+    }
+}
+
+/// Reads all boards from content of PBN file. Boards are separated by empty lines.
+/// ```
+/// use brydz_core::deal::parse_pbn;
+/// use brydz_core::player::side::Side::{East, South};
+/// use brydz_core::score::Vulnerability;
+/// let pbn = r#"% PBN 2.1
+/// [Event "Example"]
+/// [Dealer "E"]
+/// [Vulnerable "NS"]
+/// [Deal "E:KQJ.87.832.JT843 932.A42.KQ976.K9 AT86.KJT93.4T.2A 754.Q65.AJ5.Q765"]
+/// [Declarer "S"]
+/// [Contract "4HX"]
+///
+/// [Dealer "S"]
+/// [Deal "S:KQJ.87.832.JT843 932.A42.KQ976.K9 AT86.KJT93.4T.2A 754.Q65.AJ5.Q765"]
+/// [Contract "Pass"]
+/// "#;
+/// let boards = parse_pbn(pbn).unwrap();
+/// assert_eq!(boards.len(), 2);
+/// assert_eq!(boards[0].dealer, Some(East));
+/// assert_eq!(boards[0].vulnerability, Vulnerability::NorthSouth);
+/// assert_eq!(boards[0].contract.as_ref().unwrap().declarer(), South);
+/// assert!(boards[1].contract.is_none());
+/// ```
+pub fn parse_pbn(content: &str) -> Result<Vec<PbnBoard>, PbnError>{
+    let mut boards = Vec::new();
+    let mut pending = PendingBoard::default();
+    let mut in_comment = false;
+    for (number, line) in content.lines().enumerate(){
+        let line = line.trim();
+        if in_comment{
+            in_comment = !line.contains('}');
+            continue;
+        }
+        if line.starts_with('{') {
+            in_comment = !line.contains('}');
+            continue;
+        }
+        if line.is_empty(){
+            boards.extend(std::mem::take(&mut pending).finish(number)?);
+            continue;
+        }
+        if !line.starts_with('[') || !line.ends_with(']'){
+            continue;
+        }
+        let inner = &line[1..line.len()-1];
+        let (tag, value) = match inner.split_once(char::is_whitespace){
+            Some((tag, value)) => (tag, value.trim().trim_matches('"')),
+            None => continue
+        };
+        match tag{
+            "Deal" => {
+                if pending.hands.is_some(){
+                    boards.extend(std::mem::take(&mut pending).finish(number)?);
+                }
+                pending.hands = Some(parse_pbn_deal(value)?);
+            },
+            "Dealer" => pending.dealer = Some(parse_side(tag, value)?),
+            "Vulnerable" => pending.vulnerability = parse_vulnerability(value)?,
+            "Contract" => pending.contract = parse_contract(value)?,
+            "Declarer" => pending.declarer = match value.trim(){
+                "" | "?" => None,
+                side => Some(parse_side(tag, side)?)
+            },
+            _ => {}
+        }
+        pending.started = true;
+    }
+    boards.extend(pending.finish(content.lines().count())?);
+    Ok(boards)
+}
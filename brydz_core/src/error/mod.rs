@@ -21,6 +21,8 @@ pub use crate::karty::error::CardSetErrorGen;
 
 mod format;
 mod fuzzy_card_set;
+mod pbn;
 
 pub use format::*;
 pub use fuzzy_card_set::*;
+pub use pbn::*;
@@ -0,0 +1,9 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum PbnError{
+    #[error("Board ending in line {line} has no Deal tag")]
+    MissingDeal{line: usize},
+    #[error("Bad value of tag {tag}: \"{value}\"")]
+    BadTagValue{tag: String, value: String},
+}
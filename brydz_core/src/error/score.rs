@@ -8,7 +8,12 @@ use crate::speedy::{Readable, Writable};
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "speedy", derive(Writable, Readable))]
 pub enum ScoreError{
-    NegativeTrickNumber
+    NegativeTrickNumber,
+    IllegalTrickNumber(u8),
+    /// Score is compared with reference score of board, but none is set.
+    MissingReferenceScore,
+    /// Undertricks are charged with penalty table that has no entries.
+    EmptyPenaltyTable,
 }
 
 impl Display for ScoreError{
@@ -0,0 +1,25 @@
+
+/// Lower bounds of point differences for subsequent IMPs on standard scale.
+pub const IMP_SCALE: [i32; 24] = [
+    20, 50, 90, 130, 170, 220, 270, 320, 370, 430, 500, 600,
+    750, 900, 1100, 1300, 1500, 1750, 2000, 2250, 2500, 3000, 3500, 4000
+];
+
+/// Converts difference of points to International Match Points.
+/// # Examples:
+/// ```
+/// use brydz_core::score::imps;
+/// assert_eq!(imps(0), 0);
+/// assert_eq!(imps(10), 0);
+/// assert_eq!(imps(20), 1);
+/// assert_eq!(imps(-430), -10);
+/// assert_eq!(imps(620 - 170), 10);
+/// assert_eq!(imps(3990), 23);
+/// assert_eq!(imps(7600), 24);
+/// ```
+pub fn imps(points_difference: i32) -> i32{
+    let imps = IMP_SCALE.iter()
+        .take_while(|&&bound| bound <= points_difference.abs())
+        .count() as i32;
+    imps * points_difference.signum()
+}
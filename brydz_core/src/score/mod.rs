@@ -1,12 +1,21 @@
-//pub mod rubber_score;
 //pub mod rewards;
-//pub mod score_table;
 mod calculation;
 pub mod sport;
-pub mod points_table;
 pub mod tables;
+mod score_table_legacy;
+mod rubber_score;
+mod vulnerability;
+mod imps;
+mod reward_scheme;
+mod par;
 
 pub use calculation::{ScoreTracker};
+pub use vulnerability::*;
+pub use imps::*;
+pub use reward_scheme::*;
+pub use par::*;
+pub use score_table_legacy::*;
+pub use rubber_score::*;
 
 
 
@@ -0,0 +1,334 @@
+use std::fmt::{Display, Formatter};
+use karty::suits::Suit;
+use karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+use karty::symbol::CardSymbol;
+use crate::bidding::{Bid, Doubling};
+use crate::cards::trump::{Trump, TrumpGen};
+use crate::cards::trump::TrumpGen::{Colored, NoTrump};
+use crate::contract::ContractParameters;
+use crate::error::ScoreError;
+use crate::meta::{MAX_BID_NUMBER, MIN_BID_NUMBER, TOTAL_TRICKS};
+use crate::player::axis::Axis;
+use crate::player::axis::Axis::{EastWest, NorthSouth};
+use crate::player::side::{Side, SIDES};
+use crate::player::side::Side::{East, North, South, West};
+use crate::score::tables::ScoringTable;
+use crate::score::Vulnerability;
+
+/// Strains in order of bidding.
+const STRAINS: [TrumpGen<Suit>; 5] = [Colored(Clubs), Colored(Diamonds), Colored(Hearts), Colored(Spades), NoTrump];
+
+fn strain_index(strain: &Trump) -> usize{
+    match strain{
+        Colored(s) => s.usize_index(),
+        NoTrump => 4
+    }
+}
+
+fn axis_index(axis: Axis) -> usize{
+    match axis{
+        NorthSouth => 0,
+        EastWest => 1
+    }
+}
+
+fn opposite(axis: Axis) -> Axis{
+    match axis{
+        NorthSouth => EastWest,
+        EastWest => NorthSouth
+    }
+}
+
+/// Selects better of two scores (counted from North-South perspective) for given axis.
+fn better_for(axis: Axis, first: i32, second: i32) -> i32{
+    match axis{
+        NorthSouth => first.max(second),
+        EastWest => first.min(second)
+    }
+}
+
+/// Number of tricks taken by each declarer in each strain, when all hands are visible.
+/// It does not depend on the way it was computed.
+/// ```
+/// use brydz_core::cards::trump::TrumpGen::{Colored, NoTrump};
+/// use brydz_core::player::side::Side::{East, North};
+/// use brydz_core::score::DoubleDummyTable;
+/// use karty::suits::Suit::Spades;
+/// let mut table = DoubleDummyTable::default();
+/// table.set_tricks(North, &Colored(Spades), 10).unwrap();
+/// assert_eq!(table.tricks(North, &Colored(Spades)), 10);
+/// assert_eq!(table.tricks(East, &NoTrump), 0);
+/// assert!(table.set_tricks(North, &NoTrump, 14).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DoubleDummyTable{
+    tricks: [[u8; 5]; 4]
+}
+
+impl DoubleDummyTable{
+    pub fn new_with_fn<F: FnMut(Side, Trump) -> u8>(mut f: F) -> Result<Self, ScoreError>{
+        let mut table = Self::default();
+        for side in SIDES{
+            for strain in STRAINS{
+                table.set_tricks(side, &strain, f(side, strain))?;
+            }
+        }
+        Ok(table)
+    }
+
+    pub fn tricks(&self, declarer: Side, strain: &Trump) -> u8{
+        self.tricks[declarer.index() as usize][strain_index(strain)]
+    }
+
+    pub fn set_tricks(&mut self, declarer: Side, strain: &Trump, tricks: u8) -> Result<(), ScoreError>{
+        if tricks > TOTAL_TRICKS{
+            return Err(ScoreError::IllegalTrickNumber(tricks))
+        }
+        self.tricks[declarer.index() as usize][strain_index(strain)] = tricks;
+        Ok(())
+    }
+
+    /// Returns partner (or both of them) taking the most tricks in strain as declarer.
+    pub fn best_declarers(&self, axis: Axis, strain: &Trump) -> (Side, Option<Side>){
+        let (first, second) = match axis{
+            NorthSouth => (North, South),
+            EastWest => (East, West)
+        };
+        let (tricks_first, tricks_second) = (self.tricks(first, strain), self.tricks(second, strain));
+        match tricks_first.cmp(&tricks_second){
+            std::cmp::Ordering::Less => (second, None),
+            std::cmp::Ordering::Equal => (first, Some(second)),
+            std::cmp::Ordering::Greater => (first, None),
+        }
+    }
+}
+
+impl Display for DoubleDummyTable{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "       C  D  H  S NT")?;
+        for side in SIDES{
+            let name = side.to_string();
+            write!(f, "{name:<6}")?;
+            for strain in STRAINS{
+                write!(f, "{:>3}", self.tricks(side, &strain))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// One of final contracts reaching par score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde_derive", feature = "serde_dedicate"), derive(serde::Serialize, serde::Deserialize))]
+pub struct ParContract{
+    contract: ContractParameters,
+    overtricks: i8
+}
+
+impl ParContract{
+    pub fn contract(&self) -> &ContractParameters{
+        &self.contract
+    }
+    /// Tricks taken over contracted number, negative when contract is a sacrifice.
+    pub fn overtricks(&self) -> i8{
+        self.overtricks
+    }
+    pub fn is_sacrifice(&self) -> bool{
+        self.overtricks < 0
+    }
+}
+
+/// Par score of deal with contracts leading to it. Empty list of contracts means that deal should
+/// be passed out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde_derive", feature = "serde_dedicate"), derive(serde::Serialize, serde::Deserialize))]
+pub struct ParResult{
+    score: i32,
+    contracts: Vec<ParContract>
+}
+
+impl ParResult{
+    /// Par score from North-South perspective.
+    pub fn score(&self) -> i32{
+        self.score
+    }
+    pub fn score_of(&self, axis: Axis) -> i32{
+        match axis{
+            NorthSouth => self.score,
+            EastWest => -self.score
+        }
+    }
+    pub fn contracts(&self) -> &[ParContract]{
+        &self.contracts
+    }
+    pub fn is_passed_out(&self) -> bool{
+        self.contracts.is_empty()
+    }
+}
+
+/// Computes par of deal.
+///
+/// Auction is modelled as a game of two axes, each of them may outbid the last contract or accept it.
+/// Contract that is made is played undoubled, one that fails is doubled. In every strain contract
+/// is played by partner taking more tricks. Axis of dealer is first to make a bid, so it can
+/// preempt when both axes have equally good contracts.
+/// # Examples:
+/// ```
+/// use brydz_core::cards::trump::TrumpGen::Colored;
+/// use brydz_core::player::axis::Axis;
+/// use brydz_core::player::side::Side::North;
+/// use brydz_core::score::{par, DoubleDummyTable, Vulnerability};
+/// use brydz_core::score::tables::SCORING_TABLE_STANDARD;
+/// use karty::suits::Suit::{Diamonds, Spades};
+/// let table = DoubleDummyTable::new_with_fn(|side, strain| {
+///     match (side.axis(), strain){
+///         (Axis::NorthSouth, Colored(Spades)) => 10,
+///         (Axis::NorthSouth, Colored(Diamonds)) => 4,
+///         (Axis::EastWest, Colored(Spades)) => 3,
+///         (Axis::EastWest, Colored(Diamonds)) => 9,
+///         _ => 6
+///     }
+/// }).unwrap();
+/// // East-West sacrifice in 5 diamonds doubled going two down
+/// let result = par(&table, Vulnerability::None, North, &SCORING_TABLE_STANDARD).unwrap();
+/// assert_eq!(result.score(), 300);
+/// assert_eq!(result.contracts().len(), 2);
+/// assert!(result.contracts().iter().all(|c| c.overtricks() == -2 && c.contract().bid().number() == 5));
+/// // Sacrifice costs more than game when East-West are vulnerable
+/// let result = par(&table, Vulnerability::EastWest, North, &SCORING_TABLE_STANDARD).unwrap();
+/// assert_eq!(result.score(), 420);
+/// assert!(result.contracts().iter().all(|c| c.contract().bid().trump() == &Colored(Spades)));
+/// ```
+pub fn par(table: &DoubleDummyTable, vulnerability: Vulnerability, dealer: Side, scoring: &ScoringTable) -> Result<ParResult, ScoreError>{
+    let bids: Vec<Bid<Suit>> = (MIN_BID_NUMBER..=MAX_BID_NUMBER)
+        .flat_map(|level| STRAINS.iter().filter_map(move |strain| Bid::init(*strain, level).ok()))
+        .collect();
+    let axes = [NorthSouth, EastWest];
+
+    // score (North-South perspective) when axis declares bid and it is passed out
+    let mut final_scores = [vec![0i32; bids.len()], vec![0i32; bids.len()]];
+    for axis in axes{
+        for (i, bid) in bids.iter().enumerate(){
+            let (declarer, _) = table.best_declarers(axis, bid.trump());
+            let taken = table.tricks(declarer, bid.trump());
+            let doubling = match taken >= bid.number_normalised(){
+                true => Doubling::None,
+                false => Doubling::Double
+            };
+            let score = scoring.score(&ContractParameters::new_d(declarer, *bid, doubling), taken, vulnerability.is_vulnerable(axis))?;
+            final_scores[axis_index(axis)][i] = match axis{
+                NorthSouth => score,
+                EastWest => -score
+            };
+        }
+    }
+
+    // after_bid[axis][i] - result when axis has just bid i-th bid and both play optimally later
+    // best_bid_from[axis][i] - result of best bid of axis not lower than i-th bid
+    let mut after_bid = [vec![0i32; bids.len()], vec![0i32; bids.len()]];
+    let mut best_bid_from = [vec![0i32; bids.len()], vec![0i32; bids.len()]];
+    for i in (0..bids.len()).rev(){
+        for axis in axes{
+            let opponents = opposite(axis);
+            let accepted = final_scores[axis_index(axis)][i];
+            after_bid[axis_index(axis)][i] = match best_bid_from[axis_index(opponents)].get(i+1){
+                Some(outbid) => better_for(opponents, accepted, *outbid),
+                None => accepted
+            };
+        }
+        for axis in axes{
+            let here = after_bid[axis_index(axis)][i];
+            best_bid_from[axis_index(axis)][i] = match i + 1 < bids.len(){
+                true => better_for(axis, here, best_bid_from[axis_index(axis)][i+1]),
+                false => here
+            };
+        }
+    }
+
+    // Four passes in a row end the deal, dealer's axis acts first and third.
+    let first = dealer.axis();
+    let second = opposite(first);
+    let best_opening = |axis: Axis| best_bid_from[axis_index(axis)][0];
+    let mut score = 0;
+    for axis in [second, first, second, first]{
+        score = better_for(axis, best_opening(axis), score);
+    }
+
+    let mut contracts = Vec::new();
+    for axis in axes{
+        let opponents = opposite(axis);
+        // Sacrifice makes sense only over the highest contract that would be better for opponents than par.
+        let overcalled = (0..bids.len())
+            .filter(|j| better_for(opponents, final_scores[axis_index(opponents)][*j], score) != score)
+            .max();
+        for strain in STRAINS{
+            let (declarer, other) = table.best_declarers(axis, &strain);
+            let taken = table.tricks(declarer, &strain);
+            let lowest = bids.iter().enumerate()
+                .filter(|(_, bid)| bid.trump() == &strain)
+                .find(|(i, bid)| final_scores[axis_index(axis)][*i] == score
+                    && after_bid[axis_index(axis)][*i] == score
+                    && (taken >= bid.number_normalised() || !matches!(overcalled, Some(j) if j >= *i)));
+            if let Some((_, bid)) = lowest{
+                let overtricks = taken as i8 - bid.number_normalised() as i8;
+                let doubling = match overtricks < 0{
+                    true => Doubling::Double,
+                    false => Doubling::None
+                };
+                for side in std::iter::once(declarer).chain(other){
+                    contracts.push(ParContract{contract: ContractParameters::new_d(side, *bid, doubling), overtricks});
+                }
+            }
+        }
+    }
+
+    Ok(ParResult{score, contracts})
+}
+
+#[cfg(test)]
+mod tests{
+    use karty::suits::Suit::Spades;
+    use crate::cards::trump::TrumpGen::{Colored, NoTrump};
+    use crate::player::axis::Axis;
+    use crate::player::side::Side::{East, North, South};
+    use crate::score::{par, DoubleDummyTable, Vulnerability};
+    use crate::score::tables::SCORING_TABLE_STANDARD;
+
+    #[test]
+    fn passed_out_when_nothing_makes(){
+        let table = DoubleDummyTable::new_with_fn(|_, _| 6).unwrap();
+        let result = par(&table, Vulnerability::Both, North, &SCORING_TABLE_STANDARD).unwrap();
+        assert_eq!(result.score(), 0);
+        assert!(result.is_passed_out());
+    }
+
+    #[test]
+    fn dealer_axis_declares_when_both_make_same(){
+        let table = DoubleDummyTable::new_with_fn(|_, strain| match strain{
+            NoTrump => 7,
+            _ => 6
+        }).unwrap();
+        let result = par(&table, Vulnerability::None, North, &SCORING_TABLE_STANDARD).unwrap();
+        assert_eq!(result.score(), 90);
+        assert_eq!(result.contracts().len(), 2);
+        assert!(result.contracts().iter().all(|c| c.contract().declarer().axis() == Axis::NorthSouth));
+        let result = par(&table, Vulnerability::None, East, &SCORING_TABLE_STANDARD).unwrap();
+        assert_eq!(result.score_of(Axis::EastWest), 90);
+    }
+
+    #[test]
+    fn better_declarer_is_chosen(){
+        let table = DoubleDummyTable::new_with_fn(|side, strain| match (side, strain){
+            (North, Colored(Spades)) => 10,
+            (South, Colored(Spades)) => 9,
+            _ => 5
+        }).unwrap();
+        let result = par(&table, Vulnerability::None, East, &SCORING_TABLE_STANDARD).unwrap();
+        assert_eq!(result.score(), 420);
+        assert_eq!(result.contracts().len(), 1);
+        assert_eq!(result.contracts()[0].contract().declarer(), North);
+        assert_eq!(result.contracts()[0].overtricks(), 0);
+    }
+}
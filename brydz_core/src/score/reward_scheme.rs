@@ -0,0 +1,161 @@
+use karty::cards::Card;
+use crate::contract::ContractMechanics;
+use crate::error::ScoreError;
+use crate::player::axis::Axis;
+use crate::score::imps;
+use crate::score::tables::ScoringPreset;
+use crate::score::Vulnerability;
+
+/// Way of rewarding axis for playing contract.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RewardScheme{
+    /// Number of tricks taken by axis, growing with every trick won.
+    #[default]
+    DenseTricks,
+    /// After contract is completed - number of tricks taken by declarer over (or under) contract target.
+    /// Defenders get the negation.
+    TricksOverTarget,
+    /// After contract is completed - duplicate score of axis.
+    DuplicateScore(ScoringPreset),
+    /// After contract is completed - IMPs of duplicate score compared with reference score of board
+    /// (e.g. double dummy par result). Payoff of completed contract fails with
+    /// [`ScoreError::MissingReferenceScore`] when board has no reference score.
+    ImpsVsReference(ScoringPreset),
+}
+
+impl RewardScheme{
+
+    /// Calculates payoff of axis in (possibly not completed) contract.
+    /// `reference_score` is given from the perspective of North-South.
+    /// # Examples:
+    /// ```
+    /// use brydz_core::bidding::Bid;
+    /// use brydz_core::cards::trump::TrumpGen;
+    /// use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    /// use brydz_core::player::axis::Axis::{EastWest, NorthSouth};
+    /// use brydz_core::player::side::Side::{East, North, South, West};
+    /// use brydz_core::score::{RewardScheme, Vulnerability};
+    /// use brydz_core::score::tables::ScoringPreset;
+    /// use brydz_core::fill_deal;
+    /// use karty::cards::*;
+    /// use karty::suits::Suit::Spades;
+    /// let mut contract = Contract::new(ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Spades), 1).unwrap()));
+    /// fill_deal!(contract, [ACE_CLUBS, TWO_CLUBS, THREE_CLUBS, FOUR_CLUBS]);
+    /// assert_eq!(RewardScheme::DenseTricks.payoff(&contract, EastWest, Vulnerability::None, None).unwrap(), 0);
+    /// assert_eq!(RewardScheme::DenseTricks.payoff(&contract, NorthSouth, Vulnerability::None, None).unwrap(), 1);
+    /// assert_eq!(RewardScheme::TricksOverTarget.payoff(&contract, NorthSouth, Vulnerability::None, None).unwrap(), 0);
+    /// let duplicate = RewardScheme::DuplicateScore(ScoringPreset::Standard);
+    /// assert_eq!(duplicate.payoff(&contract, NorthSouth, Vulnerability::None, None).unwrap(), 0);
+    /// ```
+    pub fn payoff<Co: ContractMechanics<Card = Card>>(&self, contract: &Co, axis: Axis, vulnerability: Vulnerability, reference_score: Option<i32>) -> Result<i32, ScoreError>{
+        let declarer_axis = contract.declarer().axis();
+        let taken = contract.total_tricks_taken_axis(declarer_axis) as u8;
+        let declarer_payoff = match self{
+            RewardScheme::DenseTricks => return Ok(contract.total_tricks_taken_axis(axis) as i32),
+            _ if !contract.is_completed() => return Ok(0),
+            RewardScheme::TricksOverTarget => {
+                taken as i32 - contract.contract_spec().bid().number_normalised() as i32
+            },
+            RewardScheme::DuplicateScore(preset) => {
+                preset.table().score(contract.contract_spec(), taken, vulnerability.is_vulnerable(declarer_axis))?
+            },
+            RewardScheme::ImpsVsReference(preset) => {
+                let score = preset.table().score(contract.contract_spec(), taken, vulnerability.is_vulnerable(declarer_axis))?;
+                let reference_score = reference_score.ok_or(ScoreError::MissingReferenceScore)?;
+                let reference = match declarer_axis{
+                    Axis::NorthSouth => reference_score,
+                    Axis::EastWest => -reference_score
+                };
+                imps(score - reference)
+            }
+        };
+        match axis == declarer_axis{
+            true => Ok(declarer_payoff),
+            false => Ok(-declarer_payoff)
+        }
+    }
+
+    /// Returns `true` if payoff is distributed during play, `false` if it is granted only when contract is completed.
+    pub fn is_dense(&self) -> bool{
+        matches!(self, RewardScheme::DenseTricks)
+    }
+
+    /// Scoring rules of reference score required by scheme, `None` if scheme does not compare with
+    /// reference score.
+    /// ```
+    /// use brydz_core::score::RewardScheme;
+    /// use brydz_core::score::tables::ScoringPreset;
+    /// assert_eq!(RewardScheme::ImpsVsReference(ScoringPreset::Standard).reference_scoring(), Some(&ScoringPreset::Standard));
+    /// assert_eq!(RewardScheme::DuplicateScore(ScoringPreset::Standard).reference_scoring(), None);
+    /// ```
+    pub fn reference_scoring(&self) -> Option<&ScoringPreset>{
+        match self{
+            RewardScheme::ImpsVsReference(preset) => Some(preset),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use karty::cards::*;
+    use crate::bidding::Bid;
+    use crate::cards::trump::TrumpGen;
+    use crate::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use crate::error::ScoreError;
+    use crate::fill_deal;
+    use crate::player::axis::Axis::{EastWest, NorthSouth};
+    use crate::player::side::Side::North;
+    use crate::score::{RewardScheme, Vulnerability};
+    use crate::score::tables::ScoringPreset;
+
+    fn completed_1nt_by_north() -> Contract{
+        // South wins every trick.
+        let mut contract = Contract::new(ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+        fill_deal!(contract, [
+            TWO_CLUBS, ACE_CLUBS, THREE_CLUBS, FOUR_CLUBS,
+            KING_CLUBS, FIVE_CLUBS, SIX_CLUBS, SEVEN_CLUBS,
+            QUEEN_CLUBS, EIGHT_CLUBS, NINE_CLUBS, TEN_CLUBS,
+            JACK_CLUBS, TWO_DIAMONDS, THREE_DIAMONDS, FOUR_DIAMONDS,
+            ACE_DIAMONDS, FIVE_DIAMONDS, SIX_DIAMONDS, SEVEN_DIAMONDS,
+            KING_DIAMONDS, EIGHT_DIAMONDS, NINE_DIAMONDS, TEN_DIAMONDS,
+            QUEEN_DIAMONDS, JACK_DIAMONDS, TWO_SPADES, THREE_SPADES,
+            ACE_SPADES, FOUR_SPADES, FIVE_SPADES, SIX_SPADES,
+            KING_SPADES, SEVEN_SPADES, EIGHT_SPADES, NINE_SPADES,
+            QUEEN_SPADES, TEN_SPADES, JACK_SPADES, TWO_HEARTS,
+            ACE_HEARTS, THREE_HEARTS, FOUR_HEARTS, FIVE_HEARTS,
+            KING_HEARTS, SIX_HEARTS, SEVEN_HEARTS, EIGHT_HEARTS,
+            QUEEN_HEARTS, NINE_HEARTS, TEN_HEARTS, JACK_HEARTS
+        ]);
+        contract
+    }
+
+    #[test]
+    fn sparse_schemes_after_completion(){
+        let contract = completed_1nt_by_north();
+        assert!(contract.is_completed());
+        let ns_tricks = contract.total_tricks_taken_axis(NorthSouth) as i32;
+        let target = 7;
+        assert_eq!(RewardScheme::TricksOverTarget.payoff(&contract, NorthSouth, Vulnerability::None, None).unwrap(),
+                   ns_tricks - target);
+        assert_eq!(RewardScheme::TricksOverTarget.payoff(&contract, EastWest, Vulnerability::None, None).unwrap(),
+                   target - ns_tricks);
+        let duplicate = RewardScheme::DuplicateScore(ScoringPreset::Standard);
+        let ns_score = duplicate.payoff(&contract, NorthSouth, Vulnerability::Both, None).unwrap();
+        assert_eq!(ns_score, -duplicate.payoff(&contract, EastWest, Vulnerability::Both, None).unwrap());
+        let imps = RewardScheme::ImpsVsReference(ScoringPreset::Standard);
+        assert_eq!(imps.payoff(&contract, NorthSouth, Vulnerability::Both, Some(ns_score)).unwrap(), 0);
+        assert_eq!(imps.payoff(&contract, EastWest, Vulnerability::Both, Some(ns_score)).unwrap(), 0);
+    }
+
+    #[test]
+    fn imps_require_reference_score(){
+        let contract = completed_1nt_by_north();
+        let imps = RewardScheme::ImpsVsReference(ScoringPreset::Standard);
+        assert_eq!(imps.payoff(&contract, NorthSouth, Vulnerability::None, None), Err(ScoreError::MissingReferenceScore));
+        let mut not_completed = Contract::new(ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+        fill_deal!(not_completed, [TWO_CLUBS, ACE_CLUBS, THREE_CLUBS, FOUR_CLUBS]);
+        assert_eq!(imps.payoff(&not_completed, NorthSouth, Vulnerability::None, None), Ok(0));
+    }
+}
@@ -1,61 +1,169 @@
-use crate::contract::deal::Deal;
-
+use karty::suits::Suit;
+use crate::contract::ContractParametersGen;
+use crate::error::ScoreError;
+use crate::player::axis::Axis;
+use crate::score::ScoreTable;
+use crate::score::tables::{ScoringTable, SCORING_TABLE_STANDARD};
 
 
 pub const GAME_WINNING_POINTS: i32 = 100;
 pub const RUBBER_WINNING_GAMES: i32 = 2;
+/// Premium for winning rubber without losing game.
+pub const RUBBER_PREMIUM_TWO_GAMES: i32 = 700;
+/// Premium for winning rubber two games to one.
+pub const RUBBER_PREMIUM_THREE_GAMES: i32 = 500;
 
 
-/*
-#[derive(Debug, Eq, PartialEq,  Clone)]
-pub struct Game<F: Figure, S: Suit, Um: Register<Card<F,S>>, Se: SuitExhaustRegister<S>>{
-    winning_points: i64,
-    contracts: Vec<Deal<F, S, Um, Se>>,
+/// Rubber bridge score. Axis becomes vulnerable after winning game and rubber ends when
+/// axis wins [`RUBBER_WINNING_GAMES`] games. Contracts are scored with borrowed scoring table.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct RubberScore<'a>{
+    score: ScoreTable<'a>,
+    ns_games: i32,
+    ew_games: i32,
+    ns_game_points: i32,
+    ew_game_points: i32,
+}
 
+impl RubberScore<'static>{
+    pub fn new() -> Self{
+        Self::new_with_table(&SCORING_TABLE_STANDARD)
+    }
 }
-impl<F: Figure, S: Suit, Um: Register<Card<F,S>>, Se: SuitExhaustRegister<S>>Game<F, S, Um, Se>{
-    pub fn new(winning_points: i64) -> Self{
-        Self{winning_points, contracts: Vec::new()}
+
+impl<'a> RubberScore<'a>{
+    pub fn new_with_table(table: &'a ScoringTable) -> Self{
+        Self{score: ScoreTable::new_with_table(table, 0, 0, 0, 0), ns_games: 0, ew_games: 0, ns_game_points: 0, ew_game_points: 0}
     }
 
-}*/
+    pub fn score_table(&self) -> &ScoreTable<'a>{
+        &self.score
+    }
 
-/*
-impl Game{
-    pub fn new() -> Self{
-        Self{north_south_scores: Vec::new(), east_west_scores: Vec::new()}
+    pub fn games(&self, axis: Axis) -> i32{
+        match axis{
+            Axis::NorthSouth => self.ns_games,
+            Axis::EastWest => self.ew_games
+        }
     }
-    pub fn sum(&self, axis: Axis) -> i32{
-        self[axis].iter().sum()
 
+    pub fn is_vulnerable(&self, axis: Axis) -> bool{
+        self.games(axis) > 0
+    }
+
+    pub fn sum(&self, axis: Axis) -> i64{
+        self.score.above(&axis) + self.score.below(&axis)
     }
-    pub fn winner(&self) -> Option<Axis>{
-        match self.sum(NorthSouth).cmp(&self.sum(EastWest)){
-            Ordering::Equal => None,
-            Ordering::Greater => Some(NorthSouth),
-            Ordering::Less => Some(EastWest)
 
+    pub fn winner(&self) -> Option<Axis>{
+        if self.ns_games >= RUBBER_WINNING_GAMES{
+            Some(Axis::NorthSouth)
+        } else if self.ew_games >= RUBBER_WINNING_GAMES{
+            Some(Axis::EastWest)
+        } else {
+            None
         }
     }
+
     pub fn is_finished(&self) -> bool {
-        (self.sum(NorthSouth) >= 100) && (self.sum(EastWest) >= 100)
+        self.winner().is_some()
+    }
+
+    /// Scores played contract (vulnerability of declarer is taken from rubber state),
+    /// closes game when declarer reaches [`GAME_WINNING_POINTS`] below the line
+    /// and writes rubber premium when rubber is won.
+    /// # Example:
+    /// ```
+    /// use brydz_core::bidding::Bid;
+    /// use brydz_core::cards::trump::TrumpGen;
+    /// use brydz_core::contract::ContractParametersGen;
+    /// use brydz_core::player::axis::Axis::NorthSouth;
+    /// use brydz_core::player::side::Side::North;
+    /// use brydz_core::score::RubberScore;
+    /// let mut rubber = RubberScore::new();
+    /// let contract = ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 3).unwrap());
+    /// rubber.add_contract(&contract, 9).unwrap();
+    /// assert!(rubber.is_vulnerable(NorthSouth));
+    /// rubber.add_contract(&contract, 9).unwrap();
+    /// assert_eq!(rubber.winner(), Some(NorthSouth));
+    /// assert_eq!(rubber.sum(NorthSouth), 200 + 700);
+    /// ```
+    pub fn add_contract(&mut self, contract: &ContractParametersGen<Suit>, taken: u8) -> Result<(), ScoreError>{
+        if self.is_finished(){
+            return Ok(())
+        }
+        let declarer_axis = contract.declarer().axis();
+        let below_before = self.score.below(&declarer_axis);
+        self.score.add_contract(contract, taken, self.is_vulnerable(declarer_axis))?;
+        let game_points = (self.score.below(&declarer_axis) - below_before) as i32;
+        let (points, games) = match declarer_axis{
+            Axis::NorthSouth => (&mut self.ns_game_points, &mut self.ns_games),
+            Axis::EastWest => (&mut self.ew_game_points, &mut self.ew_games),
+        };
+        *points += game_points;
+        if *points >= GAME_WINNING_POINTS{
+            *games += 1;
+            self.ns_game_points = 0;
+            self.ew_game_points = 0;
+            if let Some(winner) = self.winner(){
+                let premium = match self.ns_games + self.ew_games{
+                    2 => RUBBER_PREMIUM_TWO_GAMES,
+                    _ => RUBBER_PREMIUM_THREE_GAMES
+                };
+                self.score.add_above(&winner, premium as i64);
+            }
+        }
+        Ok(())
     }
 }
 
-impl Default for Game {
-     fn default() -> Self {
-        Self::new()
-     }
+impl Default for RubberScore<'_>{
+    fn default() -> Self {
+        RubberScore::new()
+    }
 }
 
-impl Index<Axis> for Game{
-    type Output = Vec<i32>;
+#[cfg(test)]
+mod tests{
+    use karty::suits::Suit::{Hearts, Spades};
+    use crate::bidding::Bid;
+    use crate::bidding::Doubling::Double;
+    use crate::cards::trump::TrumpGen;
+    use crate::contract::ContractParametersGen;
+    use crate::player::axis::Axis::{EastWest, NorthSouth};
+    use crate::player::side::Side::{East, North};
+    use crate::score::RubberScore;
+    use crate::score::tables::SCORING_TABLE_PRE_1987;
 
-    fn index(&self, index: Axis) -> &Self::Output {
-        match index{
-            Axis::NorthSouth => &self.north_south_scores,
-            Axis::EastWest => &self.east_west_scores
-        }
+    #[test]
+    fn part_scores_add_up_to_game(){
+        let mut rubber = RubberScore::new();
+        let two_hearts = ContractParametersGen::new(North, Bid::init(TrumpGen::Colored(Hearts), 2).unwrap());
+        rubber.add_contract(&two_hearts, 8).unwrap();
+        assert_eq!(rubber.games(NorthSouth), 0);
+        rubber.add_contract(&two_hearts, 8).unwrap();
+        assert_eq!(rubber.games(NorthSouth), 1);
+        assert!(rubber.is_vulnerable(NorthSouth));
+        assert!(!rubber.is_vulnerable(EastWest));
+        let four_spades = ContractParametersGen::new(East, Bid::init(TrumpGen::Colored(Spades), 4).unwrap());
+        rubber.add_contract(&four_spades, 10).unwrap();
+        assert_eq!(rubber.games(EastWest), 1);
+        assert_eq!(rubber.winner(), None);
+        rubber.add_contract(&two_hearts, 8).unwrap();
+        rubber.add_contract(&two_hearts, 8).unwrap();
+        assert_eq!(rubber.winner(), Some(NorthSouth));
+        assert_eq!(rubber.sum(NorthSouth), 4 * 60 + 500);
+        assert_eq!(rubber.sum(EastWest), 120);
+    }
+
+    #[test]
+    fn penalties_follow_scoring_table(){
+        let four_spades_doubled = ContractParametersGen::new_d(North, Bid::init(TrumpGen::Colored(Spades), 4).unwrap(), Double);
+        let mut rubber = RubberScore::new();
+        rubber.add_contract(&four_spades_doubled, 5).unwrap();
+        assert_eq!(rubber.sum(EastWest), 1100);
+        let mut rubber = RubberScore::new_with_table(&SCORING_TABLE_PRE_1987);
+        rubber.add_contract(&four_spades_doubled, 5).unwrap();
+        assert_eq!(rubber.sum(EastWest), 900);
     }
 }
-*/
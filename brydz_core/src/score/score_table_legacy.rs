@@ -1,19 +1,35 @@
-use serde::{Deserialize, Serialize};
+use karty::suits::Suit;
+use crate::contract::ContractParametersGen;
+use crate::error::ScoreError;
 use crate::player::axis::Axis;
+use crate::score::tables::{ScoringTable, SCORING_TABLE_STANDARD};
 
 
-#[derive(Debug, Eq, PartialEq, Serialize, Deserialize, Clone, Copy)]
-pub struct ScoreTable {
+/// Score sheet with points written above and below the line, counted with borrowed scoring table.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct ScoreTable<'a> {
     score_ns_above_line: i64,
     score_ns_below_line: i64,
     score_ew_above_line: i64,
-    score_ew_below_line: i64
+    score_ew_below_line: i64,
+    table: &'a ScoringTable,
 }
 
-impl ScoreTable {
+impl ScoreTable<'static> {
     pub fn new(ns_above: i64, ns_below:i64, ew_above: i64, ew_below: i64) -> Self{
+        Self::new_with_table(&SCORING_TABLE_STANDARD, ns_above, ns_below, ew_above, ew_below)
+    }
+}
+
+impl<'a> ScoreTable<'a> {
+
+    pub fn new_with_table(table: &'a ScoringTable, ns_above: i64, ns_below:i64, ew_above: i64, ew_below: i64) -> Self{
         Self{score_ns_below_line: ns_below, score_ns_above_line: ns_above,
-        score_ew_above_line: ew_above, score_ew_below_line: ew_below}
+        score_ew_above_line: ew_above, score_ew_below_line: ew_below, table}
+    }
+
+    pub fn table(&self) -> &'a ScoringTable{
+        self.table
     }
 
     pub fn above(&self, axis: &Axis) -> i64{
@@ -43,12 +59,51 @@ impl ScoreTable {
             Axis::EastWest => { self.score_ew_below_line += score}
         }
     }
+
+    /// Writes result of played contract. Contracted tricks of made contract go below the line
+    /// of declarer, overtricks, slam and doubled contract premiums above it. Penalty for defeated
+    /// contract goes above the line of defenders. Game premium is not written, it is granted
+    /// by rubber.
+    /// # Example:
+    /// ```
+    /// use brydz_core::bidding::Bid;
+    /// use brydz_core::cards::trump::TrumpGen;
+    /// use brydz_core::contract::ContractParametersGen;
+    /// use brydz_core::player::axis::Axis::{EastWest, NorthSouth};
+    /// use brydz_core::player::side::Side::North;
+    /// use brydz_core::score::ScoreTable;
+    /// use karty::suits::Suit::Spades;
+    /// let mut table = ScoreTable::default();
+    /// let contract = ContractParametersGen::new(North, Bid::init(TrumpGen::Colored(Spades), 2).unwrap());
+    /// table.add_contract(&contract, 9, false).unwrap();
+    /// assert_eq!(table.below(&NorthSouth), 60);
+    /// assert_eq!(table.above(&NorthSouth), 30);
+    /// table.add_contract(&contract, 6, true).unwrap();
+    /// assert_eq!(table.above(&EastWest), 200);
+    /// ```
+    pub fn add_contract(&mut self, contract: &ContractParametersGen<Suit>, taken: u8, vulnerable: bool) -> Result<(), ScoreError>{
+        let declarer_axis = contract.declarer().axis();
+        if taken >= contract.bid().number_normalised(){
+            let below = self.table.contracted_trick.points(contract, taken);
+            let above = self.table.over_trick.points(contract, taken, vulnerable)
+                + self.table.slam.points(contract, taken, vulnerable)
+                + self.table.premium_contract.points(contract, taken);
+            self.add_below(&declarer_axis, below as i64);
+            self.add_above(&declarer_axis, above as i64);
+        } else {
+            let penalty = self.table.under_trick.penalty_checked(contract, taken, vulnerable)?;
+            let defender_axis = match declarer_axis{
+                Axis::NorthSouth => Axis::EastWest,
+                Axis::EastWest => Axis::NorthSouth
+            };
+            self.add_above(&defender_axis, penalty as i64);
+        }
+        Ok(())
+    }
 }
 
-impl Default for ScoreTable {
+impl Default for ScoreTable<'_> {
     fn default() -> Self {
         ScoreTable::new(0, 0, 0, 0)
     }
 }
-
-
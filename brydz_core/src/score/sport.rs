@@ -4,30 +4,55 @@ use karty::suits::{Suit};
 use crate::contract::{ContractMechanics};
 use crate::error::{BridgeCoreErrorGen, ContractErrorGen};
 use crate::player::axis::Axis;
-use crate::score::calculation::ScoreIngredient;
 use crate::score::ScoreTracker;
-use crate::score::tables::{PENALTY_UNDER_TRICK, POINTS_CONTRACTED_TRICK, POINTS_OVER_TRICK, POINTS_PREMIUM_CONTRACT, POINTS_PREMIUM_SPORT, POINTS_SLAM};
+use crate::score::tables::{ScoringTable, SCORING_TABLE_STANDARD};
 
 
-#[derive(Debug, Copy, Clone, Default)]
-pub struct ScoreTableSport {
+#[derive(Debug, Copy, Clone)]
+pub struct ScoreTableSport<'a> {
     ns_score: i32,
     ew_score: i32,
     ns_vulnerability: bool,
-    ew_vulnerability: bool
+    ew_vulnerability: bool,
+    table: &'a ScoringTable
 
 }
 
-impl ScoreTableSport{
+impl ScoreTableSport<'static>{
     pub fn new(ns_vulnerability: bool, ew_vulnerability: bool) -> Self{
-        Self{ns_score: 0, ew_score: 0, ns_vulnerability, ew_vulnerability}
+        Self::new_with_table(&SCORING_TABLE_STANDARD, ns_vulnerability, ew_vulnerability)
+    }
+}
+
+impl<'a> ScoreTableSport<'a>{
+
+    /// Creates score tracker counting points with provided scoring table.
+    /// # Example:
+    /// ```
+    /// use brydz_core::score::sport::ScoreTableSport;
+    /// use brydz_core::score::tables::{SCORING_TABLE_PRE_1987};
+    /// let score = ScoreTableSport::new_with_table(&SCORING_TABLE_PRE_1987, true, false);
+    /// assert_eq!(score.table(), &SCORING_TABLE_PRE_1987);
+    /// ```
+    pub fn new_with_table(table: &'a ScoringTable, ns_vulnerability: bool, ew_vulnerability: bool) -> Self{
+        Self{ns_score: 0, ew_score: 0, ns_vulnerability, ew_vulnerability, table}
     }
 
+    pub fn table(&self) -> &'a ScoringTable{
+        self.table
+    }
+
+}
+
+impl Default for ScoreTableSport<'_>{
+    fn default() -> Self {
+        ScoreTableSport::new(false, false)
+    }
 }
 
 
 impl<Co: ContractMechanics<Card = Crd>, Crd: Card2SymTrait<Suit =Suit>> ScoreTracker<Co, Crd>
-for ScoreTableSport{
+for ScoreTableSport<'_>{
 
     fn winner_axis(&self) -> Option<Axis> {
         match self.ew_score.cmp(&self.ns_score){
@@ -135,25 +160,14 @@ for ScoreTableSport{
                 Axis::EastWest => self.ew_vulnerability,
                 Axis::NorthSouth => self.ns_vulnerability
             };
-            let defender_vulnerability = match axis{
-                Axis::EastWest => self.ns_vulnerability,
-                Axis::NorthSouth => self.ew_vulnerability
-            };
             let taken = deal.total_tricks_taken_axis(axis) as u8;
-            let contracted_points = POINTS_CONTRACTED_TRICK.calculate(deal.contract_spec(), taken, false);
-            let overtrick_bonus = POINTS_OVER_TRICK.points(deal.contract_spec(), taken, vulnerability);
-            let slam_bonus = POINTS_SLAM.points(deal.contract_spec(), taken, vulnerability);
-            let premium_game_points = POINTS_PREMIUM_SPORT.points(contracted_points, vulnerability);
-            let premium_contract_points = POINTS_PREMIUM_CONTRACT.points(deal.contract_spec(), taken);
-            let penalty_undertricks = match PENALTY_UNDER_TRICK.penalty_checked(deal.contract_spec(), taken, defender_vulnerability){
-                Ok(points) => points,
-                Err(e) => return Err(BridgeCoreErrorGen::Score(e))
-            };
-            println!("contracted: {}, overtrick: {}", &contracted_points, &overtrick_bonus);
+            let score = self.table.score(deal.contract_spec(), taken, vulnerability)
+                .map_err(BridgeCoreErrorGen::Score)?;
 
-            let declarer_axis_score = contracted_points+ overtrick_bonus + slam_bonus
-                + premium_game_points + premium_contract_points;
-            let defender_axis_score = penalty_undertricks;
+            let (declarer_axis_score, defender_axis_score) = match score >= 0{
+                true => (score, 0),
+                false => (0, -score)
+            };
 
             match axis{
                 Axis::NorthSouth => {
@@ -6,6 +6,8 @@ use crate::cards::trump::TrumpGen;
 use crate::meta::HALF_TRICKS;
 use crate::score::calculation::ScoreIngredient;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointsContractedTrick{
     pub clubs: i32,
     pub diamonds: i32,
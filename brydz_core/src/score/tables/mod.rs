@@ -3,9 +3,11 @@ mod overtricks;
 mod slam;
 mod premium;
 mod penalties;
+mod scoring;
 
 pub use contracted::*;
 pub use overtricks::*;
 pub use slam::*;
 pub use premium::*;
-pub use penalties::*;
\ No newline at end of file
+pub use penalties::*;
+pub use scoring::*;
\ No newline at end of file
@@ -4,6 +4,8 @@ use crate::contract::ContractParametersGen;
 use crate::cards::trump::TrumpGen;
 use crate::score::calculation::ScoreIngredient;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointsOverTrick{
     pub not_doubled_clubs: i32,
     pub not_doubled_diamonds: i32,
@@ -6,7 +6,8 @@ use crate::error::{ScoreError};
 use crate::meta::{SIZE_GREATER_HALF_TRICKS};
 
 
-#[derive(Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PenaltyTable{
     pub not_vulnerable: i32,
     pub vulnerable: i32,
@@ -59,9 +60,13 @@ pub const LEVEL_3_TRICK_PENALTY: PenaltyTable = PenaltyTable{
     vulnerable_redoubled: 600
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PenaltyUnderTrick<SU: SuitTrait, const L: usize, >{
 
+    #[cfg_attr(feature = "serde", serde(with = "serde_big_array::BigArray"))]
     pub penalty_tables: [PenaltyTable;L],
+    #[cfg_attr(feature = "serde", serde(skip))]
     _phantom: PhantomData<SU>
 
     /*pub first_undertrick: PenaltyTable,
@@ -71,14 +76,40 @@ pub struct PenaltyUnderTrick<SU: SuitTrait, const L: usize, >{
 
 impl<SU: SuitTrait, const L: usize> PenaltyUnderTrick<SU, L>{
 
+    pub const fn new(penalty_tables: [PenaltyTable;L]) -> Self{
+        Self{penalty_tables, _phantom: PhantomData}
+    }
+
+    /// Calculates penalty for undertricks. Undertricks beyond the length of table are charged
+    /// as the last one in table, empty table can not charge any undertrick.
+    /// # Examples:
+    /// ```
+    /// use brydz_core::contract::ContractParametersGen;
+    /// use brydz_core::player::side::Side::North;
+    /// use brydz_core::bidding::Bid;
+    /// use brydz_core::bidding::Doubling::Double;
+    /// use brydz_core::cards::trump::TrumpGen::NoTrump;
+    /// use brydz_core::score::tables::PENALTY_UNDER_TRICK;
+    /// let contract = ContractParametersGen::new(North, Bid::init(NoTrump, 7).unwrap());
+    /// assert_eq!(PENALTY_UNDER_TRICK.penalty_checked(&contract, 12, false).unwrap(), 50);
+    /// let contract = ContractParametersGen::new_d(North, Bid::init(NoTrump, 7).unwrap(), Double);
+    /// assert_eq!(PENALTY_UNDER_TRICK.penalty_checked(&contract, 3, false).unwrap(), 2600);
+    ///
+    /// use brydz_core::error::ScoreError;
+    /// use brydz_core::score::tables::PenaltyUnderTrick;
+    /// use karty::suits::Suit;
+    /// let empty = PenaltyUnderTrick::<Suit, 0>::new([]);
+    /// assert_eq!(empty.penalty_checked(&contract, 13, false).unwrap(), 0);
+    /// assert_eq!(empty.penalty_checked(&contract, 12, false), Err(ScoreError::EmptyPenaltyTable));
+    /// ```
     pub fn penalty_checked(&self, contract: &ContractParametersGen<SU>, taken: u8, vulnerability: bool) -> Result<i32, ScoreError>{
         let number_of_undertricks = contract.bid().number_normalised().saturating_sub(taken);
-        if (number_of_undertricks as usize) > self.penalty_tables.len(){
-            return Err(ScoreError::NegativeTrickNumber)
+        if number_of_undertricks > 0 && self.penalty_tables.is_empty(){
+            return Err(ScoreError::EmptyPenaltyTable)
         }
         let mut penalty = 0;
         for i in 0..number_of_undertricks as usize{
-            penalty += self.penalty_tables[i].get_points(contract.doubling(), vulnerability);
+            penalty += self.penalty_tables[i.min(L-1)].get_points(contract.doubling(), vulnerability);
         }
         Ok(penalty)
 
@@ -97,4 +128,33 @@ pub const PENALTY_UNDER_TRICK: PenaltyUnderTrick<Suit, SIZE_GREATER_HALF_TRICKS>
         LEVEL_3_TRICK_PENALTY
     ],
     _phantom: PhantomData
+};
+
+/// Undertrick penalties before 1987 laws, when fourth and subsequent not vulnerable doubled undertricks
+/// were charged the same as second and third.
+/// # Examples:
+/// ```
+/// use brydz_core::contract::ContractParametersGen;
+/// use brydz_core::player::side::Side::North;
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::bidding::Doubling::Double;
+/// use brydz_core::cards::trump::TrumpGen::NoTrump;
+/// use brydz_core::score::tables::{PENALTY_UNDER_TRICK, PENALTY_UNDER_TRICK_PRE_1987};
+/// let contract = ContractParametersGen::new_d(North, Bid::init(NoTrump, 4).unwrap(), Double);
+/// assert_eq!(PENALTY_UNDER_TRICK.penalty_checked(&contract, 6, false).unwrap(), 800);
+/// assert_eq!(PENALTY_UNDER_TRICK_PRE_1987.penalty_checked(&contract, 6, false).unwrap(), 700);
+/// assert_eq!(PENALTY_UNDER_TRICK.penalty_checked(&contract, 6, true).unwrap(), 1100);
+/// assert_eq!(PENALTY_UNDER_TRICK_PRE_1987.penalty_checked(&contract, 6, true).unwrap(), 1100);
+/// ```
+pub const PENALTY_UNDER_TRICK_PRE_1987: PenaltyUnderTrick<Suit, SIZE_GREATER_HALF_TRICKS> = PenaltyUnderTrick{
+    penalty_tables: [
+        FIRST_TRICK_PENALTY,
+        LEVEL_2_TRICK_PENALTY,
+        LEVEL_2_TRICK_PENALTY,
+        LEVEL_2_TRICK_PENALTY,
+        LEVEL_2_TRICK_PENALTY,
+        LEVEL_2_TRICK_PENALTY,
+        LEVEL_2_TRICK_PENALTY
+    ],
+    _phantom: PhantomData
 };
\ No newline at end of file
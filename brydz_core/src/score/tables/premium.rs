@@ -3,6 +3,8 @@ use karty::suits::{SuitTrait, Suit};
 use crate::bidding::Doubling;
 use crate::contract::ContractParametersGen;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointsPremiumSport{
     pub game_vulnerable: i32,
     pub game_not_vulnerable: i32,
@@ -33,15 +35,22 @@ impl PointsPremiumSport{
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointsPremiumContract<SU: SuitTrait>{
     pub on_doubled: i32,
     pub on_redoubled: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _phantom: PhantomData<SU>
 }
 pub type PointsPremiumContractStd = PointsPremiumContract<Suit>;
 
 impl<SU: SuitTrait> PointsPremiumContract<SU>{
 
+    pub const fn new(on_doubled: i32, on_redoubled: i32) -> Self{
+        Self{on_doubled, on_redoubled, _phantom: PhantomData}
+    }
+
     pub fn points(&self, contract: &ContractParametersGen<SU>, taken: u8) -> i32{
         if taken >= contract.bid().number_normalised(){
             return match contract.doubling(){
@@ -57,4 +66,9 @@ impl<SU: SuitTrait> PointsPremiumContract<SU>{
 
 pub const POINTS_PREMIUM_CONTRACT: PointsPremiumContractStd = PointsPremiumContractStd {
     on_doubled: 50,
-    on_redoubled: 100, _phantom: PhantomData };
\ No newline at end of file
+    on_redoubled: 100, _phantom: PhantomData };
+
+/// Premium for making doubled or redoubled contract before 2007 laws, when redoubled contract was rewarded with the same 50 points as doubled one.
+pub const POINTS_PREMIUM_CONTRACT_PRE_2007: PointsPremiumContractStd = PointsPremiumContractStd {
+    on_doubled: 50,
+    on_redoubled: 50, _phantom: PhantomData };
\ No newline at end of file
@@ -0,0 +1,149 @@
+use karty::suits::Suit;
+use crate::contract::ContractParametersGen;
+use crate::error::ScoreError;
+use crate::meta::SIZE_GREATER_HALF_TRICKS;
+use crate::score::tables::{PenaltyUnderTrick, PointsContractedTrick, PointsOverTrick, PointsPremiumContractStd, PointsPremiumSport, PointsSlam, PENALTY_UNDER_TRICK, PENALTY_UNDER_TRICK_PRE_1987, POINTS_CONTRACTED_TRICK, POINTS_OVER_TRICK, POINTS_PREMIUM_CONTRACT, POINTS_PREMIUM_CONTRACT_PRE_2007, POINTS_PREMIUM_SPORT, POINTS_SLAM};
+
+/// Complete set of tables used to score single contract.
+/// Default is the current duplicate scoring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoringTable{
+    pub contracted_trick: PointsContractedTrick,
+    pub over_trick: PointsOverTrick,
+    pub slam: PointsSlam,
+    pub premium_sport: PointsPremiumSport,
+    pub premium_contract: PointsPremiumContractStd,
+    pub under_trick: PenaltyUnderTrick<Suit, SIZE_GREATER_HALF_TRICKS>,
+}
+
+impl ScoringTable{
+
+    /// Calculates score of declaring axis. Positive when contract is made, negative when it is defeated.
+    /// # Examples:
+    /// ```
+    /// use brydz_core::contract::ContractParametersGen;
+    /// use brydz_core::player::side::Side::North;
+    /// use brydz_core::bidding::Bid;
+    /// use brydz_core::bidding::Doubling::{Double, Redouble};
+    /// use brydz_core::cards::trump::TrumpGen;
+    /// use brydz_core::cards::trump::TrumpGen::NoTrump;
+    /// use brydz_core::score::tables::{SCORING_TABLE_PRE_1987, SCORING_TABLE_STANDARD};
+    /// use karty::suits::Suit::{Hearts, Spades};
+    /// let table = SCORING_TABLE_STANDARD;
+    /// let contract = ContractParametersGen::new(North, Bid::init(NoTrump, 3).unwrap());
+    /// assert_eq!(table.score(&contract, 9, false).unwrap(), 400);
+    /// assert_eq!(table.score(&contract, 10, true).unwrap(), 630);
+    /// assert_eq!(table.score(&contract, 7, true).unwrap(), -200);
+    /// let contract = ContractParametersGen::new(North, Bid::init(TrumpGen::Colored(Hearts), 2).unwrap());
+    /// assert_eq!(table.score(&contract, 8, false).unwrap(), 110);
+    /// let contract = ContractParametersGen::new_d(North, Bid::init(TrumpGen::Colored(Hearts), 2).unwrap(), Double);
+    /// assert_eq!(table.score(&contract, 8, false).unwrap(), 470);
+    /// let contract = ContractParametersGen::new(North, Bid::init(TrumpGen::Colored(Spades), 6).unwrap());
+    /// assert_eq!(table.score(&contract, 12, true).unwrap(), 1430);
+    /// let contract = ContractParametersGen::new_d(North, Bid::init(TrumpGen::Colored(Spades), 4).unwrap(), Double);
+    /// assert_eq!(table.score(&contract, 5, false).unwrap(), -1100);
+    /// assert_eq!(SCORING_TABLE_PRE_1987.score(&contract, 5, false).unwrap(), -900);
+    /// let contract = ContractParametersGen::new_d(North, Bid::init(TrumpGen::Colored(Spades), 1).unwrap(), Redouble);
+    /// assert_eq!(table.score(&contract, 7, false).unwrap(), 520);
+    /// assert_eq!(SCORING_TABLE_PRE_1987.score(&contract, 7, false).unwrap(), 470);
+    /// ```
+    pub fn score(&self, contract: &ContractParametersGen<Suit>, taken: u8, vulnerable: bool) -> Result<i32, ScoreError>{
+        if taken >= contract.bid().number_normalised(){
+            let contracted_points = self.contracted_trick.points(contract, taken);
+            Ok(contracted_points
+                + self.over_trick.points(contract, taken, vulnerable)
+                + self.slam.points(contract, taken, vulnerable)
+                + self.premium_sport.points(contracted_points, vulnerable)
+                + self.premium_contract.points(contract, taken))
+        } else {
+            self.under_trick.penalty_checked(contract, taken, vulnerable).map(|p| -p)
+        }
+    }
+}
+
+impl Default for ScoringTable{
+    fn default() -> Self {
+        SCORING_TABLE_STANDARD
+    }
+}
+
+/// Current duplicate scoring.
+pub const SCORING_TABLE_STANDARD: ScoringTable = ScoringTable{
+    contracted_trick: POINTS_CONTRACTED_TRICK,
+    over_trick: POINTS_OVER_TRICK,
+    slam: POINTS_SLAM,
+    premium_sport: POINTS_PREMIUM_SPORT,
+    premium_contract: POINTS_PREMIUM_CONTRACT,
+    under_trick: PENALTY_UNDER_TRICK,
+};
+
+/// Duplicate scoring of 1987 laws, used until 2007 - making redoubled contract gives premium of 50.
+pub const SCORING_TABLE_PRE_2007: ScoringTable = ScoringTable{
+    contracted_trick: POINTS_CONTRACTED_TRICK,
+    over_trick: POINTS_OVER_TRICK,
+    slam: POINTS_SLAM,
+    premium_sport: POINTS_PREMIUM_SPORT,
+    premium_contract: POINTS_PREMIUM_CONTRACT_PRE_2007,
+    under_trick: PENALTY_UNDER_TRICK,
+};
+
+/// Duplicate scoring used before 1987 - fourth and further not vulnerable doubled undertricks
+/// cost 200 (400 redoubled) and making redoubled contract gives premium of 50.
+pub const SCORING_TABLE_PRE_1987: ScoringTable = ScoringTable{
+    contracted_trick: POINTS_CONTRACTED_TRICK,
+    over_trick: POINTS_OVER_TRICK,
+    slam: POINTS_SLAM,
+    premium_sport: POINTS_PREMIUM_SPORT,
+    premium_contract: POINTS_PREMIUM_CONTRACT_PRE_2007,
+    under_trick: PENALTY_UNDER_TRICK_PRE_1987,
+};
+
+/// Named sets of scoring rules, to be selected in configuration instead of listing whole table.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScoringPreset{
+    #[default]
+    Standard,
+    Pre2007,
+    Pre1987,
+    Custom(Box<ScoringTable>)
+}
+
+impl ScoringPreset{
+    pub fn table(&self) -> &ScoringTable{
+        match self{
+            ScoringPreset::Standard => &SCORING_TABLE_STANDARD,
+            ScoringPreset::Pre2007 => &SCORING_TABLE_PRE_2007,
+            ScoringPreset::Pre1987 => &SCORING_TABLE_PRE_1987,
+            ScoringPreset::Custom(table) => table
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::score::tables::{ScoringPreset, ScoringTable};
+
+    #[test]
+    #[cfg(feature = "serde_ron")]
+    fn scoring_table_ron_round_trip(){
+        use crate::score::tables::SCORING_TABLE_PRE_1987;
+        let serialized = ron::to_string(&SCORING_TABLE_PRE_1987).unwrap();
+        let deserialized: ScoringTable = ron::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, SCORING_TABLE_PRE_1987);
+    }
+
+    #[test]
+    #[cfg(feature = "serde_ron")]
+    fn scoring_preset_deserialize(){
+        use crate::score::tables::SCORING_TABLE_PRE_1987;
+        let preset: ScoringPreset = ron::from_str("Pre1987").unwrap();
+        assert_eq!(preset.table(), &SCORING_TABLE_PRE_1987);
+    }
+
+    #[test]
+    fn default_preset_is_default_table(){
+        assert_eq!(ScoringPreset::default().table(), &ScoringTable::default());
+    }
+}
@@ -3,11 +3,13 @@ use crate::contract::ContractParametersGen;
 use crate::meta::QUARTER_SIZE;
 use crate::score::calculation::ScoreIngredient;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointsSlam{
-    vulnerable_great_slam: i32,
-    vulnerable_small_slam: i32,
-    not_vulnerable_great_slam: i32,
-    not_vulnerable_small_slam: i32,
+    pub vulnerable_great_slam: i32,
+    pub vulnerable_small_slam: i32,
+    pub not_vulnerable_great_slam: i32,
+    pub not_vulnerable_small_slam: i32,
 }
 
 pub const POINTS_SLAM: PointsSlam = PointsSlam{
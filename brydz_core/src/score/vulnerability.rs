@@ -0,0 +1,44 @@
+use crate::player::axis::Axis;
+
+#[cfg(feature="speedy")]
+use crate::speedy::{Readable, Writable};
+
+/// Vulnerability of axes on board.
+/// ```
+/// use brydz_core::player::axis::Axis::{EastWest, NorthSouth};
+/// use brydz_core::score::Vulnerability;
+/// let vulnerability = Vulnerability::new(true, false);
+/// assert_eq!(vulnerability, Vulnerability::NorthSouth);
+/// assert!(vulnerability.is_vulnerable(NorthSouth));
+/// assert!(!vulnerability.is_vulnerable(EastWest));
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "speedy", derive(Writable, Readable))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Vulnerability{
+    #[default]
+    None,
+    NorthSouth,
+    EastWest,
+    Both
+}
+
+impl Vulnerability{
+    pub fn new(north_south: bool, east_west: bool) -> Self{
+        match (north_south, east_west){
+            (false, false) => Self::None,
+            (true, false) => Self::NorthSouth,
+            (false, true) => Self::EastWest,
+            (true, true) => Self::Both
+        }
+    }
+
+    pub fn is_vulnerable(&self, axis: Axis) -> bool{
+        match (self, axis){
+            (Self::Both, _) => true,
+            (Self::NorthSouth, Axis::NorthSouth) => true,
+            (Self::EastWest, Axis::EastWest) => true,
+            _ => false
+        }
+    }
+}
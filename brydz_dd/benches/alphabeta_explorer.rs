@@ -12,7 +12,7 @@ use brydz_core::player::side::Side::{East, North};
 use criterion::{ criterion_group, criterion_main, Criterion, BenchmarkId};
 use brydz_dd::explore::{ Explorer};
 use brydz_dd::node::TrickNode;
-use brydz_dd::actions::DistinctCardGrouper;
+use brydz_dd::actions::{ActionOptimiser, DistinctCardGrouper, HeuristicCardOrderer, NeighbourCardGrouper};
 
 pub fn prepare_explorer(card_supply: Vec<Card>, first_side: Side) -> Explorer<DistinctCardGrouper, DummyNodeStore>{
     let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply.clone(), first_side);
@@ -199,8 +199,39 @@ pub fn bench_alpha_beta_unoptimised(c: &mut Criterion){
 
 }
 
+pub fn prepare_explorer_with<G: ActionOptimiser>(card_supply: Vec<Card>, first_side: Side) -> Explorer<G, DummyNodeStore>{
+    let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, first_side);
+    let contract = Contract::new(
+        ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
+    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+    Explorer::<G, DummyNodeStore>::new_checked(contract, node).unwrap()
+}
+
+pub fn bench_alpha_beta_ordering(c: &mut Criterion){
+    let figures = vec![Ace, King, Queen, Jack, F10, F9, F8, F7, F6, F5, F4, F3, F2];
+    let mut group = c.benchmark_group("AlphaBeta_move_ordering_8_to_24");
+    for fig_num in [2,3,4,5,6]{
+        let card_supply: Vec<Card> = Card::card_subset(figures[..fig_num].to_vec(), SUITS).collect();
+        let parameter_string = format!("{} cards", fig_num*4);
+        group.bench_function(BenchmarkId::new("neighbour_grouper ", &parameter_string), |b|{
+            b.iter_batched(||{
+                prepare_explorer_with::<NeighbourCardGrouper>(card_supply.clone(), North)
+            }, | mut e |{
+                e.hint().unwrap()
+            }, criterion::BatchSize::SmallInput)
+        });
+        group.bench_function(BenchmarkId::new("heuristic_orderer ", &parameter_string), |b|{
+            b.iter_batched(||{
+                prepare_explorer_with::<HeuristicCardOrderer>(card_supply.clone(), North)
+            }, | mut e |{
+                e.hint().unwrap()
+            }, criterion::BatchSize::SmallInput)
+        });
+    }
+}
 
 //criterion_group!(benches, hint_4_cards_benchmark, hint_5_cards_benchmark, hint_6_cards_benchmark);
 criterion_group!(unoptimised, bench_alpha_beta_unoptimised);
 criterion_group!(range,  hint_range_2_6);
-criterion_main!(unoptimised);
\ No newline at end of file
+criterion_group!(ordering, bench_alpha_beta_ordering);
+criterion_main!(unoptimised, ordering);
\ No newline at end of file
@@ -0,0 +1,50 @@
+use brydz_core::bidding::Bid;
+use brydz_core::cards::trump::Trump;
+use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+use brydz_core::deal::fair_bridge_partial_deal;
+use brydz_core::karty::cards::{Card, Card2SymTrait};
+use brydz_core::karty::figures::*;
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::karty::suits::{Suit::*, SUITS};
+use brydz_core::player::side::Side::{North, West};
+use brydz_core::player::side::SideMap;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use brydz_dd::actions::HeuristicCardOrderer;
+use brydz_dd::explore::LazySmp;
+use brydz_dd::hash::SharedNodeStore;
+use brydz_dd::hash::hash24::Hash24;
+use brydz_dd::node::TrickNode;
+
+type Store = SharedNodeStore<Hash24<3>, 0x10000>;
+
+const THREADS: [usize; 4] = [1, 2, 4, 8];
+
+/// Every thread count solves the same deals, each search starts with empty node store.
+pub fn bench_lazy_smp_threads(c: &mut Criterion){
+    let figures = vec![Ace, King, Queen, Jack, F10, F9, F8, F7, F6, F5, F4, F3, F2];
+    for fig_num in [7, 8]{
+        let card_supply: Vec<Card> = Card::card_subset(figures[..fig_num].to_vec(), SUITS).collect();
+        let deals: Vec<SideMap<CardSetStd>> = (0..4)
+            .map(|_| fair_bridge_partial_deal::<CardSetStd>(card_supply.clone(), North))
+            .collect();
+        let contract = Contract::new(
+            ContractParametersGen::new(West, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
+        let mut group = c.benchmark_group(format!("Lazy SMP using {} cards", fig_num * 4));
+        group.sample_size(10);
+        for threads in THREADS{
+            group.bench_function(BenchmarkId::new("solve", format!("{threads} threads")), |b|{
+                b.iter_batched(|| LazySmp::<HeuristicCardOrderer, Store>::new(threads),
+                |solver| {
+                    for hands in &deals{
+                        let node = TrickNode::new_checked(*hands, contract.current_side()).unwrap();
+                        solver.solve(&contract, node).unwrap();
+                    }
+                }, criterion::BatchSize::LargeInput)
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(lazy_smp_threads, bench_lazy_smp_threads);
+criterion_main!(lazy_smp_threads);
@@ -0,0 +1,56 @@
+use clap::Parser;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use brydz_core::bidding::Bid;
+use brydz_core::cards::trump::TRUMPS;
+use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+use brydz_core::deal::fair_bridge_deal_with_rng;
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::player::side::Side::North;
+use brydz_dd::hash::{HashArrayNodeStore, NodeHasher, VerifiedNodeStore};
+use brydz_dd::hash::hash24::Hash24;
+use brydz_dd::hash::ranker::MoreCardsRanker;
+use brydz_dd::hash::zobrist::Zobrist64;
+use brydz_dd::node::TrickNode;
+use brydz_dd::solver::{mtdf, DefaultOptimiser};
+
+/// Measures collision rate of node hashers: random full deals are solved in every strain
+/// with every hit of hash array store verified against full nodes.
+#[derive(Parser)]
+struct CollisionOptions{
+    /// Number of deals to solve
+    #[arg(short = 'd', long = "deals", default_value_t = 10)]
+    deals: usize,
+    /// Seed of deal generator
+    #[arg(short = 's', long = "seed", default_value_t = 0)]
+    seed: u64,
+}
+
+type Store<H> = HashArrayNodeStore<H, MoreCardsRanker, 0x10000, 4>;
+
+fn measure<H: NodeHasher>(name: &str, options: &CollisionOptions){
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let (mut hits, mut false_hits) = (0, 0);
+    for _ in 0..options.deals{
+        let hands = fair_bridge_deal_with_rng::<CardSetStd, _>(&mut rng);
+        for strain in TRUMPS{
+            let contract = Contract::new(ContractParametersGen::new(North, Bid::init(strain, 1).unwrap()));
+            let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+            let (_, store) = mtdf::<DefaultOptimiser, VerifiedNodeStore<Store<H>>>(contract, node, VerifiedNodeStore::default()).unwrap();
+            hits += store.verified_hits() + store.false_hits();
+            false_hits += store.false_hits();
+        }
+    }
+    let rate = match hits{
+        0 => 0.0,
+        hits => false_hits as f64 / hits as f64
+    };
+    println!("{name}:\thits: {hits}\tfalse hits: {false_hits}\tcollision rate: {rate:.3e}");
+}
+
+fn main(){
+    let options = CollisionOptions::parse();
+    println!("Solving {} deals in every strain (seed {}).", options.deals, options.seed);
+    measure::<Hash24<3>>("Hash24<3>", &options);
+    measure::<Zobrist64>("Zobrist64", &options);
+}
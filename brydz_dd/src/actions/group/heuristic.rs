@@ -0,0 +1,236 @@
+use smallvec::SmallVec;
+use brydz_core::cards::trump::TrumpGen;
+use brydz_core::contract::ContractMechanics;
+use brydz_core::error::BridgeCoreError;
+use brydz_core::karty::cards::{Card, Card2SymTrait, DECK_SIZE};
+use brydz_core::karty::set::CardSet;
+use brydz_core::karty::suits::{Suit, SuitMap};
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::meta::CONTRACT_ACTION_ESTIMATED_SUIT_MAP_BOUND;
+use brydz_core::player::side::SideMap;
+use crate::actions::{ActionOptimiser, CardPack, NeighbourCardGrouper, VCardPack};
+use crate::explore::ExplorerGameState;
+
+const KILLER_SLOTS: usize = 2;
+
+/// Static priority of card pack, higher is explored earlier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MoveClass{
+    /// Overtaking partner who already wins the trick.
+    OvertakePartner,
+    /// Card that can not win the trick.
+    Discard,
+    /// Lead not supported by top cards.
+    PlainLead,
+    /// Card following partner who is winning the trick.
+    FollowPartner,
+    /// Lead from sequence of at least two cards.
+    SequenceLead,
+    /// Ruff of trick led in other suit.
+    Ruff,
+    /// Card winning trick in suit.
+    Win,
+    /// Lead with the highest remaining card of suit.
+    TopLead,
+}
+
+/// Groups cards like [`NeighbourCardGrouper`] and orders groups before exploring.
+/// Groups are ordered by killer moves (cards that caused cut-off in sibling nodes at the same depth),
+/// then by bridge heuristics (cheap wins, ruffs, leads from sequences and top cards,
+/// low cards when partner already wins) and then by history of cut-offs.
+/// Killer and history tables are learned during search and kept for the life of explorer.
+#[derive(Debug, Clone)]
+pub struct HeuristicCardOrderer{
+    groups: NeighbourCardGrouper,
+    killers: [[Option<Card>; KILLER_SLOTS]; DECK_SIZE],
+    history: SideMap<[u32; DECK_SIZE]>,
+}
+
+impl Default for HeuristicCardOrderer{
+    fn default() -> Self {
+        Self{
+            groups: NeighbourCardGrouper::default(),
+            killers: [[None; KILLER_SLOTS]; DECK_SIZE],
+            history: SideMap::new_symmetric([0; DECK_SIZE]),
+        }
+    }
+}
+
+/// Number of cards already played in contract, used as depth for killer moves.
+fn ply<G: ActionOptimiser>(state: &ExplorerGameState<G>) -> usize{
+    state.contract().count_completed_tricks() * 4 + state.contract().current_trick().count_cards() as usize
+}
+
+fn highest_card(pack: &CardPack) -> Card{
+    Card::from_figure_and_suit(*pack.figures().iter().max().unwrap(), pack.suit())
+}
+
+fn beats(card: &Card, best: &Card, trump: &TrumpGen<Suit>) -> bool{
+    match card.suit() == best.suit(){
+        true => card.figure() > best.figure(),
+        false => matches!(trump, TrumpGen::Colored(t) if *t == card.suit())
+    }
+}
+
+impl HeuristicCardOrderer{
+    fn classify<G: ActionOptimiser>(state: &ExplorerGameState<G>, pack: &CardPack) -> MoveClass{
+        let side = state.current_side();
+        let trick = state.contract().current_trick();
+        let trump = state.contract().trump();
+        match trick.called_suit(){
+            None => {
+                let top = highest_card(pack);
+                match state.actual_node().suit_leader(&pack.suit()){
+                    Some((leader, card)) if leader == side && card == top => MoveClass::TopLead,
+                    _ if pack.figures().len() > 1 => MoveClass::SequenceLead,
+                    _ => MoveClass::PlainLead
+                }
+            },
+            Some(called) => {
+                let trump_leader = match trump{
+                    TrumpGen::Colored(t) if *t != called => trick.leader_in_suit_with_card(t),
+                    _ => None
+                };
+                let (winner, best) = match trump_leader.or_else(|| trick.leader_in_called_suit_with_card()){
+                    Some((winner, best)) => (winner, *best),
+                    None => return MoveClass::PlainLead
+                };
+                let card = pack.lowest_card();
+                let wins = beats(&highest_card(pack), &best, trump);
+                if winner == side.partner(){
+                    return match wins{
+                        true if beats(&card, &best, trump) => MoveClass::OvertakePartner,
+                        _ => MoveClass::FollowPartner
+                    }
+                }
+                match wins{
+                    true if card.suit() != called => MoveClass::Ruff,
+                    true => MoveClass::Win,
+                    false => MoveClass::Discard
+                }
+            }
+        }
+    }
+
+    fn history_index(card: &Card) -> usize{
+        card.position()
+    }
+}
+
+impl ActionOptimiser for HeuristicCardOrderer{
+    fn cache_on_trick_new(&mut self, state: &ExplorerGameState<Self>) -> Result<(), BridgeCoreError> {
+        self.groups.push_trick_new(state);
+        Ok(())
+    }
+
+    fn cache_on_trick_drop(&mut self, _state: &ExplorerGameState<Self>) -> Result<(), BridgeCoreError> {
+        self.groups.pop_trick();
+        Ok(())
+    }
+
+    fn cache_on_partial_trick(&mut self, state: &ExplorerGameState<Self>) -> Result<(), BridgeCoreError> {
+        self.groups.push_partial_trick(state)
+    }
+
+    fn prepare_vec(&self, state: &ExplorerGameState<Self>) -> SuitMap<SmallVec<[CardPack; CONTRACT_ACTION_ESTIMATED_SUIT_MAP_BOUND]>> {
+        self.groups.grouped_actions(state)
+    }
+
+    fn order_actions(&self, state: &ExplorerGameState<Self>, actions: &mut VCardPack) {
+        let side = state.current_side();
+        let killers = self.killers.get(ply(state)).copied().unwrap_or_default();
+        let history = &self.history[&side];
+        // cheaper card of the same class first, so winning and following is done as low as possible
+        actions.sort_by_cached_key(|pack|{
+            let card = pack.lowest_card();
+            let killer = pack.figures().iter()
+                .any(|f| killers.contains(&Some(Card::from_figure_and_suit(*f, pack.suit()))));
+            let class = Self::classify(state, pack);
+            std::cmp::Reverse((killer, class, history[Self::history_index(&card)], std::cmp::Reverse(card.figure())))
+        });
+    }
+
+    fn record_cutoff(&mut self, state: &ExplorerGameState<Self>, card: &Card) {
+        if let Some(slots) = self.killers.get_mut(ply(state)){
+            if slots[0] != Some(*card){
+                slots[1] = slots[0];
+                slots[0] = Some(*card);
+            }
+        }
+        // cut-offs close to root save more nodes
+        let remaining = state.current_hand().len() as u32 + 1;
+        let entry = &mut self.history[&state.current_side()][Self::history_index(card)];
+        *entry = entry.saturating_add(remaining * remaining);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+    use brydz_core::player::side::{SideMap, Side::*};
+    use crate::actions::{ActionOptimiser, HeuristicCardOrderer, NeighbourCardGrouper};
+    use crate::explore::{ExploreOutput, Explorer, ExplorerGameState, ExplorerStateUpdate};
+    use crate::hash::DummyNodeStore;
+    use crate::node::TrickNode;
+
+    fn hands() -> SideMap<brydz_core::karty::set::CardSetStd>{
+        SideMap::new(
+            card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+            card_set![ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS],
+            card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+            card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS])
+    }
+
+    #[test]
+    fn heuristic_order_of_following_cards(){
+        let contract = Contract::new(
+            ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+        let node = TrickNode::new_checked(hands(), contract.current_side()).unwrap();
+        let mut state = ExplorerGameState::<HeuristicCardOrderer>::new_checked(contract, node).unwrap();
+        // North leads with top spade
+        assert_eq!(state.ordered_actions().first().map(|p| p.lowest_card()), Some(ACE_SPADES));
+        state.update(ExplorerStateUpdate::PlaceCard(QUEEN_SPADES)).unwrap();
+        // East is void in spades, ruffs as cheap as possible
+        assert_eq!(state.ordered_actions().first().map(|p| p.lowest_card()), Some(JACK_DIAMONDS));
+        state.update(ExplorerStateUpdate::PlaceCard(KING_DIAMONDS)).unwrap();
+        // South overruffs, then discards from low cards
+        let south: Vec<Card> = state.ordered_actions().into_iter().map(|p| p.lowest_card()).collect();
+        assert_eq!(south.len(), 4);
+        assert_eq!(south[0], ACE_DIAMONDS);
+        assert_eq!(south[3], ACE_CLUBS);
+        state.update(ExplorerStateUpdate::PlaceCard(QUEEN_HEARTS)).unwrap();
+        // West follows low as partner wins
+        assert_eq!(state.ordered_actions().first().map(|p| p.lowest_card()), Some(JACK_SPADES));
+    }
+
+    #[test]
+    fn heuristic_ordering_keeps_results(){
+        for declarer in [North, East, South, West]{
+            for trump in [TrumpGen::Colored(Diamonds), TrumpGen::NoTrump]{
+                let contract = Contract::new(
+                    ContractParametersGen::new(declarer, Bid::init(trump, 1).unwrap()));
+                let node = TrickNode::new_checked(hands(), contract.current_side()).unwrap();
+                let mut plain = Explorer::<NeighbourCardGrouper, DummyNodeStore>::new_checked(contract.clone(), node).unwrap();
+                let mut ordered = Explorer::<HeuristicCardOrderer, DummyNodeStore>::new_checked(contract, node).unwrap();
+                assert_eq!(ordered.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity),
+                    plain.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity));
+            }
+        }
+    }
+
+    #[test]
+    fn cutoffs_are_recorded(){
+        let contract = Contract::new(
+            ContractParametersGen::new(West, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+        let node = TrickNode::new_checked(hands(), contract.current_side()).unwrap();
+        let state = ExplorerGameState::<HeuristicCardOrderer>::new_checked(contract, node).unwrap();
+        let mut orderer = HeuristicCardOrderer::default();
+        orderer.record_cutoff(&state, &JACK_CLUBS);
+        let mut actions = state.ordered_actions();
+        orderer.order_actions(&state, &mut actions);
+        assert_eq!(actions.first().map(|p| p.lowest_card()), Some(JACK_CLUBS));
+    }
+}
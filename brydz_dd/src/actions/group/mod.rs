@@ -1,5 +1,6 @@
 mod distinct;
 mod neigbour;
+mod heuristic;
 
 use std::cmp::Ordering;
 use std::fmt::Debug;
@@ -11,7 +12,8 @@ use brydz_core::karty::suits::SuitMap;
 use brydz_core::meta::{CONTRACT_ACTION_ESTIMATED_SUIT_MAP_BOUND, };
 pub use distinct::*;
 pub use neigbour::*;
-use crate::actions::card_pack::CardPack;
+pub use heuristic::*;
+use crate::actions::card_pack::{CardPack, VCardPack};
 use crate::explore::ExplorerGameState;
 
 
@@ -29,6 +31,13 @@ pub trait ActionOptimiser: Debug  + Clone + Default{
 
     fn prepare_vec(&self, state: &ExplorerGameState<Self>) -> SuitMap<SmallVec<[CardPack; CONTRACT_ACTION_ESTIMATED_SUIT_MAP_BOUND]>>;
 
+    /// Sorts actions prepared with [`prepare_vec`](ActionOptimiser::prepare_vec) in order they should be explored.
+    /// By default actions are left in order of suits.
+    fn order_actions(&self, _state: &ExplorerGameState<Self>, _actions: &mut VCardPack){}
+
+    /// Notifies optimiser that placing `card` in `state` caused cut-off in search.
+    fn record_cutoff(&mut self, _state: &ExplorerGameState<Self>, _card: &Card){}
+
 
 
 }
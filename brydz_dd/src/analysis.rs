@@ -0,0 +1,344 @@
+use std::fmt::{Display, Formatter};
+use brydz_core::contract::{Contract, ContractMechanics};
+use brydz_core::error::BridgeCoreError;
+use brydz_core::karty::cards::{Card, Card2SymTrait};
+use brydz_core::karty::error::CardSetErrorGen;
+use brydz_core::karty::set::{CardSet, CardSetStd};
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use crate::actions::ActionOptimiser;
+use crate::cache::{cache_north_south, cached_north_south};
+use crate::error::DoubleDummyError;
+use crate::explore::ExplorerGameState;
+use crate::hash::NodeStoreTrait;
+use crate::node::TrickNode;
+use crate::solver::{mtdf, DefaultNodeStore, DefaultOptimiser};
+
+/// Double dummy assessment of single card played in game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardAnalysis{
+    /// Index of trick (counted from 0).
+    pub trick: usize,
+    /// Hand from which card was played.
+    pub side: Side,
+    /// Player deciding about card, declarer for cards of dummy.
+    pub player: Side,
+    pub card: Card,
+    /// Tricks of North-South with optimal play from position before card.
+    pub north_south_best: u8,
+    /// Tricks of North-South with optimal play after card was played.
+    pub north_south_after: u8,
+    /// Tricks lost by side of player.
+    pub tricks_lost: u8,
+    /// Cards that would keep optimal value, empty when play was forced.
+    pub optimal_cards: Vec<Card>,
+}
+
+impl CardAnalysis{
+    pub fn is_mistake(&self) -> bool{
+        self.tricks_lost > 0
+    }
+}
+
+/// Report of double dummy analysis of game, card by card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameAnalysis{
+    pub declarer: Side,
+    /// Tricks of declarer with optimal play of everyone from the beginning.
+    pub declarer_double_dummy: u8,
+    /// Tricks taken by declarer in game.
+    pub declarer_taken: u8,
+    pub cards: Vec<CardAnalysis>,
+}
+
+impl GameAnalysis{
+    /// Cards that cost tricks.
+    pub fn mistakes(&self) -> impl Iterator<Item=&CardAnalysis>{
+        self.cards.iter().filter(|card| card.is_mistake())
+    }
+
+    /// Sum of tricks lost by decisions of player (declarer decides for dummy).
+    pub fn tricks_lost_by(&self, player: Side) -> u32{
+        self.mistakes().filter(|card| card.player == player).map(|card| card.tricks_lost as u32).sum()
+    }
+
+    /// Number of cards (decisions) that cost tricks made by player.
+    pub fn mistakes_by(&self, player: Side) -> usize{
+        self.mistakes().filter(|card| card.player == player).count()
+    }
+}
+
+impl Display for GameAnalysis{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Declarer: {}, double dummy tricks: {}, tricks taken: {}",
+            self.declarer, self.declarer_double_dummy, self.declarer_taken)?;
+        for mistake in self.mistakes(){
+            write!(f, "Trick {}: {} played {:#}", mistake.trick + 1, mistake.side, mistake.card)?;
+            if mistake.player != mistake.side{
+                write!(f, " (by {})", mistake.player)?;
+            }
+            write!(f, ", lost {} trick(s); optimal:", mistake.tricks_lost)?;
+            for card in &mistake.optimal_cards{
+                write!(f, " {card:#}")?;
+            }
+            writeln!(f)?;
+        }
+        for player in SIDES{
+            writeln!(f, "{player}: {} mistake(s), {} trick(s) lost", self.mistakes_by(player), self.tricks_lost_by(player))?;
+        }
+        Ok(())
+    }
+}
+
+/// Cards played in contract in order: completed tricks and then current one.
+fn played_cards(contract: &Contract) -> Vec<(usize, Side, Card)>{
+    let mut tricks = contract.completed_tricks();
+    tricks.push(contract.current_trick().clone());
+    let mut cards = Vec::new();
+    for (index, trick) in tricks.iter().enumerate(){
+        let first = trick.first_player_side();
+        for i in 0..4{
+            let side = first.next_i(i);
+            if let Some(card) = trick[side]{
+                cards.push((index, side, card));
+            }
+        }
+    }
+    cards
+}
+
+/// Tricks of North-South with optimal play from position, reusing node store of deal.
+/// Positions opening trick are looked up in global cache first, when it is installed.
+pub(crate) fn position_value<G: ActionOptimiser, A: NodeStoreTrait>(contract: &Contract, hands: &SideMap<CardSetStd>, node_store: A) -> Result<(u8, A), DoubleDummyError>{
+    if SIDES.iter().all(|side| hands[side].is_empty()){
+        return Ok((contract.total_tricks_taken_axis(Axis::NorthSouth) as u8, node_store))
+    }
+    if let Some(north_south) = cached_north_south(contract, hands){
+        return Ok((north_south, node_store))
+    }
+    let node = TrickNode::new_in_contract(*hands, contract).map_err(BridgeCoreError::from)?;
+    let (north_south, node_store) = mtdf::<G, A>(contract.clone(), node, node_store)?;
+    cache_north_south(contract, hands, north_south);
+    Ok((north_south, node_store))
+}
+
+/// Values every legal card of side on move in position given by `contract` and cards not played
+/// yet (position may be in the middle of trick). Value of card is number of tricks North-South
+/// take in the whole deal when card is played and everyone plays optimally later.
+/// Cards of one group have the same value. One node store is used for all cards.
+pub fn card_values_with<G: ActionOptimiser, A: NodeStoreTrait>(contract: &Contract, hands: &SideMap<CardSetStd>) -> Result<Vec<(Card, u8)>, DoubleDummyError>{
+    card_values_in_store::<G, A>(contract, hands, A::default()).map(|(values, _)| values)
+}
+
+/// Like [`card_values_with`], but uses given node store and returns it to be reused for next
+/// positions of the same deal. Store must not be shared by different layouts of the same cards,
+/// unless it tells owners of cards apart ([`Hash24`](crate::hash::hash24::Hash24) based stores,
+/// like [`DefaultNodeStore`], do not).
+pub fn card_values_in_store<G: ActionOptimiser, A: NodeStoreTrait>(contract: &Contract, hands: &SideMap<CardSetStd>, node_store: A) -> Result<(Vec<(Card, u8)>, A), DoubleDummyError>{
+    let side = contract.current_side();
+    let node = TrickNode::new_in_contract(*hands, contract).map_err(BridgeCoreError::from)?;
+    let state = ExplorerGameState::<G>::new_checked(contract.clone(), node)?;
+    let mut node_store = node_store;
+    let mut values = Vec::new();
+    for pack in state.ordered_actions(){
+        let lead = pack.lowest_card();
+        let mut next = contract.clone();
+        next.insert_card(side, lead).map_err(BridgeCoreError::from)?;
+        let mut next_hands = *hands;
+        next_hands[&side].remove_card(&lead).map_err(BridgeCoreError::from)?;
+        let (north_south, store) = position_value::<G, A>(&next, &next_hands, node_store)?;
+        node_store = store;
+        values.extend(pack.figures().iter()
+            .map(|figure| (Card::from_figure_and_suit(*figure, pack.suit()), north_south)));
+    }
+    Ok((values, node_store))
+}
+
+/// Values of legal cards with default optimiser and node store, see [`card_values_with`].
+/// ```
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::karty::set::CardSet;
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::analysis::card_values;
+/// let mut contract = Contract::new(
+///     ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+/// let mut hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// contract.insert_card(North, QUEEN_SPADES).unwrap();
+/// hands.north.remove_card(&QUEEN_SPADES).unwrap();
+/// let values = card_values(&contract, &hands).unwrap();
+/// // East has no spades, so every card is legal
+/// assert_eq!(values.len(), 4);
+/// assert!(values.iter().all(|(card, tricks)| hands.east.contains(card) && *tricks <= 4));
+/// ```
+pub fn card_values(contract: &Contract, hands: &SideMap<CardSetStd>) -> Result<Vec<(Card, u8)>, DoubleDummyError>{
+    card_values_with::<DefaultOptimiser, DefaultNodeStore>(contract, hands)
+}
+
+/// Replays finished (or partially played) `contract` from initial `deal` and compares every
+/// played card with double dummy optimal play. Every legal card group is solved, so analysis
+/// of full deal takes a while.
+pub fn analyse_game_with<G: ActionOptimiser, A: NodeStoreTrait>(contract: &Contract, deal: &SideMap<CardSetStd>) -> Result<GameAnalysis, DoubleDummyError>{
+    let declarer = contract.declarer();
+    let dummy = contract.dummy();
+    let mut replay = Contract::new(contract.contract_spec().clone());
+    let mut hands = *deal;
+    let (start_value, mut node_store) = position_value::<G, A>(&replay, &hands, A::default())?;
+    let mut current_value = start_value;
+    let mut cards = Vec::new();
+    for (trick, side, card) in played_cards(contract){
+        let node = TrickNode::new_in_contract(hands, &replay).map_err(BridgeCoreError::from)?;
+        let state = ExplorerGameState::<G>::new_checked(replay.clone(), node)?;
+        let packs = state.ordered_actions();
+        let maximising = side.axis() == Axis::NorthSouth;
+
+        let (best, after, optimal_cards) = match packs.len(){
+            0 | 1 => (current_value, current_value, Vec::new()),
+            _ => {
+                let mut values = Vec::with_capacity(packs.len());
+                for pack in &packs{
+                    let mut next = replay.clone();
+                    let lead = pack.lowest_card();
+                    next.insert_card(side, lead).map_err(BridgeCoreError::from)?;
+                    let mut next_hands = hands;
+                    next_hands[&side].remove_card(&lead).map_err(BridgeCoreError::from)?;
+                    let (value, store) = position_value::<G, A>(&next, &next_hands, node_store)?;
+                    node_store = store;
+                    values.push(value);
+                }
+                let best = match maximising{
+                    true => values.iter().max(),
+                    false => values.iter().min()
+                }.copied().unwrap_or(current_value);
+                let played = packs.iter().zip(values.iter())
+                    .find(|(pack, _)| pack.suit() == card.suit() && pack.figures().contains(&card.figure()))
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| BridgeCoreError::from(CardSetErrorGen::CardNotInSet(card)))?;
+                let optimal = packs.iter().zip(values.iter())
+                    .filter(|(_, value)| **value == best)
+                    .flat_map(|(pack, _)| pack.figures().iter()
+                        .map(|figure| Card::from_figure_and_suit(*figure, pack.suit())))
+                    .collect();
+                (best, played, optimal)
+            }
+        };
+        let tricks_lost = match maximising{
+            true => best.saturating_sub(after),
+            false => after.saturating_sub(best)
+        };
+        cards.push(CardAnalysis{
+            trick, side,
+            player: if side == dummy {declarer} else {side},
+            card,
+            north_south_best: best,
+            north_south_after: after,
+            tricks_lost,
+            optimal_cards,
+        });
+        replay.insert_card(side, card).map_err(BridgeCoreError::from)?;
+        hands[&side].remove_card(&card).map_err(BridgeCoreError::from)?;
+        current_value = after;
+    }
+    let tricks_in_deal = SIDES.iter().map(|side| deal[side].len()).max().unwrap_or(0) as u8;
+    let declarer_value = |north_south: u8| match declarer.axis(){
+        Axis::NorthSouth => north_south,
+        Axis::EastWest => tricks_in_deal - north_south
+    };
+    Ok(GameAnalysis{
+        declarer,
+        declarer_double_dummy: declarer_value(start_value),
+        declarer_taken: contract.total_tricks_taken_axis(declarer.axis()) as u8,
+        cards,
+    })
+}
+
+/// Double dummy analysis of game with default optimiser and node store, see [`analyse_game_with`].
+pub fn analyse_game(contract: &Contract, deal: &SideMap<CardSetStd>) -> Result<GameAnalysis, DoubleDummyError>{
+    analyse_game_with::<DefaultOptimiser, DefaultNodeStore>(contract, deal)
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+    use brydz_core::karty::set::{CardSet, CardSetStd, HandSuitedTrait};
+    use brydz_core::player::side::{SideMap, Side::*};
+    use crate::actions::DistinctCardGrouper;
+    use crate::analysis::analyse_game;
+    use crate::explore::Explorer;
+    use crate::hash::DummyNodeStore;
+    use crate::node::TrickNode;
+
+    fn deal() -> SideMap<CardSetStd>{
+        SideMap::new(
+            card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+            card_set![ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS],
+            card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+            card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS])
+    }
+
+    fn new_contract() -> Contract{
+        Contract::new(ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()))
+    }
+
+    /// Plays deal to the end choosing cards with `choose`.
+    fn play(choose: impl Fn(&Contract, &SideMap<CardSetStd>) -> Card) -> Contract{
+        let mut contract = new_contract();
+        let mut hands = deal();
+        for _ in 0..16{
+            let side = contract.current_side();
+            let card = choose(&contract, &hands);
+            contract.insert_card(side, card).unwrap();
+            hands[&side].remove_card(&card).unwrap();
+        }
+        contract
+    }
+
+    #[test]
+    fn optimal_play_has_no_mistakes(){
+        let contract = play(|contract, hands|{
+            let node = TrickNode::new_in_contract(*hands, contract).unwrap();
+            let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract.clone(), node).unwrap();
+            *explorer.hint().unwrap().hint().unwrap()
+        });
+        let analysis = analyse_game(&contract, &deal()).unwrap();
+        assert_eq!(analysis.cards.len(), 16);
+        assert_eq!(analysis.mistakes().count(), 0);
+        assert_eq!(analysis.declarer_double_dummy, 3);
+        assert_eq!(analysis.declarer_taken, 3);
+    }
+
+    #[test]
+    fn lost_tricks_explain_result(){
+        // everyone plays the lowest legal card
+        let contract = play(|contract, hands|{
+            let hand = &hands[&contract.current_side()];
+            contract.current_trick().called_suit()
+                .filter(|suit| hand.contains_in_suit(suit))
+                .and_then(|suit| hand.suit_iterator(&suit).next())
+                .or_else(|| hand.into_iter().next())
+                .unwrap()
+        });
+        let analysis = analyse_game(&contract, &deal()).unwrap();
+        let declarer_lost = (analysis.tricks_lost_by(West) + analysis.tricks_lost_by(East)) as i32;
+        let defenders_lost = (analysis.tricks_lost_by(North) + analysis.tricks_lost_by(South)) as i32;
+        assert_eq!(analysis.declarer_taken as i32,
+            analysis.declarer_double_dummy as i32 - declarer_lost + defenders_lost);
+        assert_eq!(analysis.tricks_lost_by(East), 0);
+        for mistake in analysis.mistakes(){
+            assert!(!mistake.optimal_cards.contains(&mistake.card));
+            assert!(!mistake.optimal_cards.is_empty());
+        }
+        let text = analysis.to_string();
+        assert!(text.starts_with("Declarer: W"));
+        assert_eq!(text.lines().count(), 1 + analysis.mistakes().count() + 4);
+    }
+}
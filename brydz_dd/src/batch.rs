@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use clap::ValueEnum;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use brydz_core::contract::ContractParameters;
+use brydz_core::deal::{ContractGameDescription, PbnBoard};
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::player::side::SideMap;
+use crate::actions::ActionOptimiser;
+use crate::cache::{solve_contract_cached, solve_table_cached, DoubleDummyCache};
+use crate::hash::NodeStoreTrait;
+use crate::table::{solve_contract, solve_table, DoubleDummyTable};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat{
+    Ron,
+    Yaml,
+    Pbn
+}
+
+impl InputFormat{
+    /// Guesses format from file extension.
+    pub fn from_path(path: &Path) -> Option<Self>{
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str(){
+            "ron" => Some(Self::Ron),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "pbn" => Some(Self::Pbn),
+            _ => None
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BatchMode{
+    /// Solve every declarer in every strain
+    #[default]
+    Table,
+    /// Solve only contract given with deal
+    Contract
+}
+
+/// Deal to be solved in batch, with contract if input provided it.
+#[derive(Debug, Clone)]
+pub struct BatchTask{
+    pub hands: SideMap<CardSetStd>,
+    pub contract: Option<ContractParameters>,
+}
+
+impl From<&ContractGameDescription> for BatchTask{
+    fn from(description: &ContractGameDescription) -> Self {
+        Self{hands: *description.cards(), contract: Some(description.parameters().clone())}
+    }
+}
+
+impl From<&PbnBoard> for BatchTask{
+    fn from(board: &PbnBoard) -> Self {
+        Self{hands: board.hands, contract: board.contract.clone()}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatchOutcome{
+    Table(DoubleDummyTable),
+    Contract{
+        contract: ContractParameters,
+        declarer_tricks: u8
+    },
+    Error(String)
+}
+
+/// Single line of batch output. Index refers to position of deal in input file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRecord{
+    pub index: usize,
+    pub outcome: BatchOutcome
+}
+
+/// Solves task, consulting `cache` first when it is given.
+pub fn solve_task<G, A>(task: &BatchTask, mode: BatchMode, cache: Option<&DoubleDummyCache>) -> BatchOutcome
+where G: ActionOptimiser + Send, A: NodeStoreTrait + Send{
+    match mode{
+        BatchMode::Table => match cache{
+            Some(cache) => solve_table_cached::<G, A>(cache, &task.hands),
+            None => solve_table::<G, A>(&task.hands)
+        }{
+            Ok(table) => BatchOutcome::Table(table),
+            Err(e) => BatchOutcome::Error(e.to_string())
+        },
+        BatchMode::Contract => match &task.contract{
+            None => BatchOutcome::Error("Deal has no contract to solve".to_owned()),
+            Some(contract) => match cache{
+                Some(cache) => solve_contract_cached::<G, A>(cache, &task.hands, contract.clone()),
+                None => solve_contract::<G, A>(&task.hands, contract.clone())
+            }{
+                Ok(declarer_tricks) => BatchOutcome::Contract{contract: contract.clone(), declarer_tricks},
+                Err(e) => BatchOutcome::Error(e.to_string())
+            }
+        }
+    }
+}
+
+/// Reads indices of deals already solved in output of interrupted run.
+/// Lines that can not be parsed (e.g. last one written partially) and error records are ignored,
+/// so failed deals are tried again.
+pub fn completed_indices(previous_output: &str) -> HashSet<usize>{
+    previous_output.lines()
+        .filter_map(|line| serde_json::from_str::<BatchRecord>(line).ok())
+        .filter(|record| !matches!(record.outcome, BatchOutcome::Error(_)))
+        .map(|record| record.index)
+        .collect()
+}
+
+/// Solves tasks in parallel, skipping indices in `completed`. Every record is written as one
+/// JSON line and flushed immediately, so output of interrupted run can be resumed.
+/// `progress` is called with number of deals solved in this run and number of deals to solve.
+/// Results found in `cache` are not solved again and new results are added to it.
+pub fn run_batch<G, A, W, P>(tasks: &[BatchTask], mode: BatchMode, cache: Option<&DoubleDummyCache>, completed: &HashSet<usize>, writer: W, progress: P) -> std::io::Result<usize>
+where G: ActionOptimiser + Send, A: NodeStoreTrait + Send, W: Write + Send, P: Fn(usize, usize) + Sync{
+    let pending: Vec<usize> = (0..tasks.len()).filter(|i| !completed.contains(i)).collect();
+    let total = pending.len();
+    let done = AtomicUsize::new(0);
+    let writer = Mutex::new(writer);
+    pending.par_iter().try_for_each(|index|{
+        let record = BatchRecord{index: *index, outcome: solve_task::<G, A>(&tasks[*index], mode, cache)};
+        let line = serde_json::to_string(&record).map_err(std::io::Error::other)?;
+        {
+            let mut guard = writer.lock().map_err(|e| std::io::Error::other(e.to_string()))?;
+            writeln!(guard, "{line}")?;
+            guard.flush()?;
+        }
+        progress(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+        Ok(())
+    })?;
+    Ok(done.into_inner())
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::ContractParameters;
+    use brydz_core::deal::fair_bridge_partial_deal;
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, King};
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::side::Side::North;
+    use crate::actions::DistinctCardGrouper;
+    use crate::batch::{completed_indices, run_batch, BatchMode, BatchOutcome, BatchRecord, BatchTask};
+    use crate::cache::DoubleDummyCache;
+    use crate::hash::DummyNodeStore;
+
+    #[test]
+    fn batch_resumes_after_completed(){
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        let contract = ContractParameters::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap());
+        let tasks = vec![BatchTask{hands, contract: Some(contract)}; 3];
+        let mut output = Vec::new();
+        let solved = run_batch::<DistinctCardGrouper, DummyNodeStore, _, _>(
+            &tasks[..2], BatchMode::Contract, None, &Default::default(), &mut output, |_,_|{}).unwrap();
+        assert_eq!(solved, 2);
+        let completed = completed_indices(std::str::from_utf8(&output).unwrap());
+        assert_eq!(completed.len(), 2);
+        let solved = run_batch::<DistinctCardGrouper, DummyNodeStore, _, _>(
+            &tasks, BatchMode::Contract, None, &completed, &mut output, |_,_|{}).unwrap();
+        assert_eq!(solved, 1);
+        assert_eq!(completed_indices(std::str::from_utf8(&output).unwrap()).len(), 3);
+    }
+
+    #[test]
+    fn failed_deals_are_not_completed(){
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        let contract = ContractParameters::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap());
+        // Second deal has no contract, so it fails in contract mode.
+        let tasks = vec![BatchTask{hands, contract: Some(contract)}, BatchTask{hands, contract: None}];
+        let mut output = Vec::new();
+        run_batch::<DistinctCardGrouper, DummyNodeStore, _, _>(
+            &tasks, BatchMode::Contract, None, &Default::default(), &mut output, |_,_|{}).unwrap();
+        let completed = completed_indices(std::str::from_utf8(&output).unwrap());
+        assert_eq!(completed, [0].into_iter().collect());
+    }
+
+    #[test]
+    fn batch_uses_cache(){
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        let tasks = vec![BatchTask{hands, contract: None}; 2];
+        let cache = DoubleDummyCache::in_memory();
+        let mut first = Vec::new();
+        run_batch::<DistinctCardGrouper, DummyNodeStore, _, _>(
+            &tasks[..1], BatchMode::Table, Some(&cache), &Default::default(), &mut first, |_,_|{}).unwrap();
+        assert_eq!((cache.hits(), cache.misses(), cache.tables_len()), (0, 1, 1));
+        let mut second = Vec::new();
+        run_batch::<DistinctCardGrouper, DummyNodeStore, _, _>(
+            &tasks, BatchMode::Table, Some(&cache), &Default::default(), &mut second, |_,_|{}).unwrap();
+        assert_eq!(cache.hits(), 2);
+        let outcomes: Vec<BatchOutcome> = std::str::from_utf8(&first).unwrap().lines()
+            .chain(std::str::from_utf8(&second).unwrap().lines())
+            .map(|line| serde_json::from_str::<BatchRecord>(line).unwrap().outcome)
+            .collect();
+        assert_eq!(outcomes.len(), 3);
+        assert!(outcomes.iter().all(|outcome| outcome == &outcomes[0]));
+    }
+}
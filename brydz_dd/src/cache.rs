@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use log::warn;
+use smallvec::SmallVec;
+use brydz_core::cards::trump::{Trump, TRUMPS};
+use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+use brydz_core::karty::cards::STANDARD_DECK;
+use brydz_core::karty::set::{CardSet, CardSetStd};
+use brydz_core::karty::suits::Suit;
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use crate::actions::ActionOptimiser;
+use crate::error::DoubleDummyError;
+use crate::hash::NodeStoreTrait;
+use crate::table::{solve_contract_uncached, solve_table_uncached, DoubleDummyTable};
+
+const CACHE_HEADER: &[u8; 8] = b"BRYDZDD\x02";
+const TABLE_TAG: u8 = 1;
+const CONTRACT_TAG: u8 = 2;
+const PARTIAL_TABLE_TAG: u8 = 3;
+const PARTIAL_CONTRACT_TAG: u8 = 4;
+const KEY_SIZE: usize = 13;
+const PRESENCE_SIZE: usize = 7;
+const TABLE_SIZE: usize = 20;
+const CONTRACT_SIZE: usize = 3;
+const COMPLETE_DECK: u64 = (1 << 52) - 1;
+
+/// Canonical identifier of deal: side holding every card of standard deck written on 2 bits,
+/// which makes 13 bytes. Partial deals (with fewer cards) carry also mask of cards in play,
+/// so they have their own identifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DealKey{
+    owners: [u8; KEY_SIZE],
+    present: u64,
+}
+
+impl DealKey{
+    /// Owners of cards, 2 bits per card of [`STANDARD_DECK`] (index of side).
+    pub fn owners(&self) -> &[u8; KEY_SIZE]{
+        &self.owners
+    }
+
+    /// Every card of deck is dealt.
+    pub fn is_complete(&self) -> bool{
+        self.present == COMPLETE_DECK
+    }
+
+    /// Complete deals take [`KEY_SIZE`] bytes, partial ones also mask of present cards.
+    fn to_bytes(self) -> SmallVec<[u8; KEY_SIZE + PRESENCE_SIZE]>{
+        let mut bytes = SmallVec::from_slice(&self.owners);
+        if !self.is_complete(){
+            bytes.extend_from_slice(&self.present.to_le_bytes()[..PRESENCE_SIZE]);
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self{
+        let mut owners = [0; KEY_SIZE];
+        owners.copy_from_slice(&bytes[..KEY_SIZE]);
+        let present = match bytes.get(KEY_SIZE..KEY_SIZE + PRESENCE_SIZE){
+            None => COMPLETE_DECK,
+            Some(mask) => {
+                let mut present = [0; 8];
+                present[..PRESENCE_SIZE].copy_from_slice(mask);
+                u64::from_le_bytes(present)
+            }
+        };
+        Self{owners, present}
+    }
+}
+
+impl From<&SideMap<CardSetStd>> for DealKey{
+    fn from(hands: &SideMap<CardSetStd>) -> Self {
+        let mut key = Self{owners: [0; KEY_SIZE], present: 0};
+        for (i, card) in STANDARD_DECK.iter().enumerate(){
+            if let Some(side) = SIDES.iter().find(|side| hands[*side].contains(card)){
+                key.owners[i / 4] |= side.index() << (2 * (i % 4));
+                key.present |= 1 << i;
+            }
+        }
+        key
+    }
+}
+
+fn strain_index(strain: &Trump) -> u8{
+    TRUMPS.iter().position(|t| t == strain).unwrap() as u8
+}
+
+#[derive(Debug, Default)]
+struct CacheState{
+    tables: HashMap<DealKey, DoubleDummyTable>,
+    contracts: HashMap<(DealKey, u8, u8), u8>,
+    file: Option<File>,
+}
+
+impl CacheState{
+    /// Reads one record from the beginning of `bytes`, returning its length.
+    /// Returns `None` if record is incomplete or malformed.
+    fn read_record(&mut self, bytes: &[u8]) -> Option<usize>{
+        let (key_size, value_size) = match *bytes.first()?{
+            TABLE_TAG => (KEY_SIZE, TABLE_SIZE),
+            CONTRACT_TAG => (KEY_SIZE, CONTRACT_SIZE),
+            PARTIAL_TABLE_TAG => (KEY_SIZE + PRESENCE_SIZE, TABLE_SIZE),
+            PARTIAL_CONTRACT_TAG => (KEY_SIZE + PRESENCE_SIZE, CONTRACT_SIZE),
+            _ => return None
+        };
+        let record = bytes.get(..1 + key_size + value_size)?;
+        let key = DealKey::from_bytes(&record[1..1 + key_size]);
+        let value = &record[1 + key_size..];
+        match value_size{
+            TABLE_SIZE => {
+                let table = DoubleDummyTable::new_with_fn(|side, strain|
+                    value[side.index() as usize * 5 + strain_index(&strain) as usize]).ok()?;
+                self.tables.insert(key, table);
+            },
+            _ => {
+                let [declarer, strain, tricks] = value.try_into().unwrap();
+                if declarer as usize >= SIDES.len() || strain as usize >= TRUMPS.len() || tricks > 13{
+                    return None
+                }
+                self.contracts.insert((key, declarer, strain), tricks);
+            }
+        }
+        Some(record.len())
+    }
+
+    fn append(&mut self, record: &[u8]) -> std::io::Result<()>{
+        match &mut self.file{
+            None => Ok(()),
+            Some(file) => file.write_all(record)
+        }
+    }
+}
+
+/// Persistent cache of double dummy results, keyed by [`DealKey`].
+/// It keeps complete tables and results of single contracts (they depend only on declarer and
+/// strain). Results are loaded into memory when cache is opened and every new result is appended
+/// to the file as one binary record, so results solved before interruption are not lost.
+/// Incomplete record at the end of file (left by interrupted write) is cut off on opening.
+/// Cache can be shared between threads.
+/// # Example:
+/// ```
+/// use brydz_core::cards::trump::TrumpGen::Colored;
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::actions::DistinctCardGrouper;
+/// use brydz_dd::cache::{solve_table_cached, DoubleDummyCache};
+/// use brydz_dd::hash::DummyNodeStore;
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// let cache = DoubleDummyCache::in_memory();
+/// let table = solve_table_cached::<DistinctCardGrouper, DummyNodeStore>(&cache, &hands).unwrap();
+/// assert_eq!(cache.table(&hands), Some(table));
+/// assert_eq!(cache.contract_tricks(&hands, West, &Colored(Diamonds)), Some(3));
+/// assert_eq!((cache.hits(), cache.misses()), (2, 1));
+/// ```
+#[derive(Debug, Default)]
+pub struct DoubleDummyCache{
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl DoubleDummyCache{
+    /// Cache not backed by file.
+    pub fn in_memory() -> Self{
+        Self::default()
+    }
+
+    /// Opens cache file, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self>{
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path.as_ref())?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        if bytes.is_empty(){
+            file.write_all(CACHE_HEADER)?;
+            bytes.extend_from_slice(CACHE_HEADER);
+        }
+        if !bytes.starts_with(CACHE_HEADER){
+            return Err(std::io::Error::new(ErrorKind::InvalidData,
+                format!("{:?} is not double dummy cache file", path.as_ref())))
+        }
+        let mut state = CacheState::default();
+        let mut position = CACHE_HEADER.len();
+        while position < bytes.len(){
+            match state.read_record(&bytes[position..]){
+                Some(length) => position += length,
+                None => {
+                    warn!("Dropping {} bytes of invalid record at the end of cache {:?}",
+                        bytes.len() - position, path.as_ref());
+                    file.set_len(position as u64)?;
+                    break;
+                }
+            }
+        }
+        state.file = Some(file);
+        Ok(Self{state: Mutex::new(state), ..Default::default()})
+    }
+
+    pub fn tables_len(&self) -> usize{
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).tables.len()
+    }
+
+    pub fn contracts_len(&self) -> usize{
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).contracts.len()
+    }
+
+    /// Number of lookups answered from cache.
+    pub fn hits(&self) -> u64{
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups not found in cache.
+    pub fn misses(&self) -> u64{
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    fn count<T>(&self, found: Option<T>) -> Option<T>{
+        match found{
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed)
+        };
+        found
+    }
+
+    pub fn table(&self, hands: &SideMap<CardSetStd>) -> Option<DoubleDummyTable>{
+        let found = self.state.lock().unwrap_or_else(PoisonError::into_inner)
+            .tables.get(&DealKey::from(hands)).copied();
+        self.count(found)
+    }
+
+    /// Tricks of declarer in strain, taken from single contract result or from complete table.
+    pub fn contract_tricks(&self, hands: &SideMap<CardSetStd>, declarer: Side, strain: &Trump) -> Option<u8>{
+        let key = DealKey::from(hands);
+        let found = {
+            let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.contracts.get(&(key, declarer.index(), strain_index(strain))).copied()
+                .or_else(|| state.tables.get(&key).map(|table| table.tricks(declarer, strain)))
+        };
+        self.count(found)
+    }
+
+    pub fn insert_table(&self, hands: &SideMap<CardSetStd>, table: DoubleDummyTable) -> std::io::Result<()>{
+        let key = DealKey::from(hands);
+        let mut record = Vec::with_capacity(1 + KEY_SIZE + PRESENCE_SIZE + TABLE_SIZE);
+        record.push(match key.is_complete(){
+            true => TABLE_TAG,
+            false => PARTIAL_TABLE_TAG
+        });
+        record.extend_from_slice(&key.to_bytes());
+        for side in SIDES{
+            for strain in TRUMPS{
+                record.push(table.tricks(side, &strain));
+            }
+        }
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.tables.insert(key, table) == Some(table){
+            return Ok(())
+        }
+        state.append(&record)
+    }
+
+    pub fn insert_contract_tricks(&self, hands: &SideMap<CardSetStd>, declarer: Side, strain: &Trump, tricks: u8) -> std::io::Result<()>{
+        let key = DealKey::from(hands);
+        let (declarer, strain) = (declarer.index(), strain_index(strain));
+        let mut record = Vec::with_capacity(1 + KEY_SIZE + PRESENCE_SIZE + CONTRACT_SIZE);
+        record.push(match key.is_complete(){
+            true => CONTRACT_TAG,
+            false => PARTIAL_CONTRACT_TAG
+        });
+        record.extend_from_slice(&key.to_bytes());
+        record.extend_from_slice(&[declarer, strain, tricks]);
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.contracts.insert((key, declarer, strain), tricks) == Some(tricks){
+            return Ok(())
+        }
+        state.append(&record)
+    }
+}
+
+static GLOBAL_CACHE: OnceLock<DoubleDummyCache> = OnceLock::new();
+
+/// Installs cache consulted by [`solve`](crate::solve), [`solve_table`](crate::table::solve_table),
+/// [`solve_contract`](crate::table::solve_contract) and double dummy analysis for the rest of
+/// the process. Cache can be installed once, otherwise it is given back.
+/// ```
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::cache::{global_cache, install_global_cache, DoubleDummyCache};
+/// install_global_cache(DoubleDummyCache::in_memory()).unwrap();
+/// let contract = Contract::new(
+///     ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// assert_eq!(brydz_dd::solve(&contract, &hands), Ok(3));
+/// assert_eq!(brydz_dd::solve(&contract, &hands), Ok(3));
+/// let cache = global_cache().unwrap();
+/// assert_eq!(cache.contract_tricks(&hands, West, &TrumpGen::Colored(Diamonds)), Some(3));
+/// assert!(cache.hits() >= 2);
+/// ```
+pub fn install_global_cache(cache: DoubleDummyCache) -> Result<(), DoubleDummyCache>{
+    GLOBAL_CACHE.set(cache)
+}
+
+pub fn global_cache() -> Option<&'static DoubleDummyCache>{
+    GLOBAL_CACHE.get()
+}
+
+/// Position opening trick is like start of contract of player on right of leader, limited to cards
+/// not played yet, so its result is stored as contract result of partial deal.
+/// Returns declarer of such contract and number of tricks left.
+fn opening_position(contract: &Contract, hands: &SideMap<CardSetStd>) -> Option<(Side, u8)>{
+    match contract.current_trick().is_empty(){
+        true => Some((contract.current_side().prev(), SIDES.iter().map(|side| hands[side].len()).max().unwrap_or(0) as u8)),
+        false => None
+    }
+}
+
+/// Converts tricks of declarer to tricks of North-South and back.
+fn swap_axis(declarer: Side, tricks: u8, tricks_left: u8) -> u8{
+    match declarer.axis(){
+        Axis::NorthSouth => tricks,
+        Axis::EastWest => tricks_left - tricks
+    }
+}
+
+/// Tricks North-South take (counting already completed tricks) from position opening trick
+/// (`hands` are cards not played yet), looked up in global cache.
+pub(crate) fn cached_north_south(contract: &Contract, hands: &SideMap<CardSetStd>) -> Option<u8>{
+    let cache = global_cache()?;
+    let (declarer, tricks_left) = opening_position(contract, hands)?;
+    let tricks = cache.contract_tricks(hands, declarer, contract.trump())?;
+    Some(contract.total_tricks_taken_axis(Axis::NorthSouth) as u8 + swap_axis(declarer, tricks, tricks_left))
+}
+
+/// Stores result of position opening trick in global cache, see [`cached_north_south`].
+pub(crate) fn cache_north_south(contract: &Contract, hands: &SideMap<CardSetStd>, north_south: u8){
+    let (cache, (declarer, tricks_left)) = match (global_cache(), opening_position(contract, hands)){
+        (Some(cache), Some(position)) => (cache, position),
+        _ => return
+    };
+    let left_north_south = north_south - contract.total_tricks_taken_axis(Axis::NorthSouth) as u8;
+    if let Err(e) = cache.insert_contract_tricks(hands, declarer, contract.trump(), swap_axis(declarer, left_north_south, tricks_left)){
+        warn!("Failed writing position result to double dummy cache: {e}");
+    }
+}
+
+/// Like [`solve_table`](crate::table::solve_table), but looks for table in cache first and stores newly solved one.
+/// Failure of writing to cache file is logged and does not fail solving.
+pub fn solve_table_cached<G, A>(cache: &DoubleDummyCache, hands: &SideMap<CardSetStd>) -> Result<DoubleDummyTable, DoubleDummyError>
+where G: ActionOptimiser + Send, A: NodeStoreTrait + Send{
+    if let Some(table) = cache.table(hands){
+        return Ok(table)
+    }
+    let table = solve_table_uncached::<G, A>(hands)?;
+    if let Err(e) = cache.insert_table(hands, table){
+        warn!("Failed writing table to double dummy cache: {e}");
+    }
+    Ok(table)
+}
+
+/// Like [`solve_contract`](crate::table::solve_contract), but looks for result in cache first and stores newly solved one.
+/// Failure of writing to cache file is logged and does not fail solving.
+pub fn solve_contract_cached<G: ActionOptimiser, A: NodeStoreTrait>(cache: &DoubleDummyCache, hands: &SideMap<CardSetStd>, parameters: ContractParametersGen<Suit>) -> Result<u8, DoubleDummyError>{
+    let (declarer, strain) = (parameters.declarer(), *parameters.bid().trump());
+    if let Some(tricks) = cache.contract_tricks(hands, declarer, &strain){
+        return Ok(tricks)
+    }
+    let tricks = solve_contract_uncached::<G, A>(hands, parameters)?;
+    if let Err(e) = cache.insert_contract_tricks(hands, declarer, &strain, tricks){
+        warn!("Failed writing contract result to double dummy cache: {e}");
+    }
+    Ok(tricks)
+}
+
+#[cfg(test)]
+mod tests{
+    use std::io::Write;
+    use std::fs::OpenOptions;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::{TrumpGen, TRUMPS};
+    use brydz_core::contract::ContractParametersGen;
+    use brydz_core::deal::{fair_bridge_deal_with_rng, fair_bridge_partial_deal};
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, King, Queen};
+    use brydz_core::karty::set::{CardSet, CardSetStd};
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::side::Side::{East, North, South};
+    use brydz_core::player::side::SIDES;
+    use crate::actions::DistinctCardGrouper;
+    use crate::cache::{solve_contract_cached, solve_table_cached, DealKey, DoubleDummyCache};
+    use crate::hash::DummyNodeStore;
+    use crate::table::{solve_table, DoubleDummyTable};
+
+    #[test]
+    fn complete_deal_key_takes_13_bytes(){
+        let path = std::env::temp_dir().join(format!("brydz_dd_cache_key_test_{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let hands = fair_bridge_deal_with_rng::<CardSetStd, _>(&mut StdRng::seed_from_u64(1));
+        let key = DealKey::from(&hands);
+        assert!(key.is_complete());
+        assert_eq!(key.to_bytes().len(), 13);
+        assert_eq!(DealKey::from_bytes(&key.to_bytes()), key);
+        let mut other_hands = hands;
+        let card = other_hands.north.into_iter().next().unwrap();
+        other_hands.north.remove_card(&card).unwrap();
+        other_hands.south.insert_card(card).unwrap();
+        assert_ne!(DealKey::from(&other_hands), key);
+
+        let table = DoubleDummyTable::new_with_fn(|side, _| 6 + side.index()).unwrap();
+        {
+            let cache = DoubleDummyCache::open(&path).unwrap();
+            cache.insert_table(&hands, table).unwrap();
+            cache.insert_contract_tricks(&other_hands, East, &TrumpGen::NoTrump, 8).unwrap();
+        }
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 8 + 34 + 17);
+        let cache = DoubleDummyCache::open(&path).unwrap();
+        assert_eq!(cache.table(&hands), Some(table));
+        assert_eq!(cache.contract_tricks(&other_hands, East, &TrumpGen::NoTrump), Some(8));
+        assert_eq!(cache.contract_tricks(&other_hands, South, &TrumpGen::NoTrump), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cache_survives_reopening(){
+        let path = std::env::temp_dir().join(format!("brydz_dd_cache_test_{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let card_supply: Vec<Card> = Card::card_subset(vec![Ace, King, Queen], vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply.clone(), North);
+        let other_hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, East);
+        let table = solve_table::<DistinctCardGrouper, DummyNodeStore>(&hands).unwrap();
+        let contract = ContractParametersGen::new(South, Bid::init(TrumpGen::NoTrump, 1).unwrap());
+        {
+            let cache = DoubleDummyCache::open(&path).unwrap();
+            assert_eq!(solve_table_cached::<DistinctCardGrouper, DummyNodeStore>(&cache, &hands), Ok(table));
+            let tricks = solve_contract_cached::<DistinctCardGrouper, DummyNodeStore>(&cache, &other_hands, contract.clone()).unwrap();
+            assert_eq!(tricks, solve_table::<DistinctCardGrouper, DummyNodeStore>(&other_hands).unwrap()
+                .tricks(South, &TrumpGen::NoTrump));
+            assert_eq!((cache.hits(), cache.misses()), (0, 2));
+        }
+        // record interrupted while writing
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(&[1, 2, 3]).unwrap();
+
+        let cache = DoubleDummyCache::open(&path).unwrap();
+        assert_eq!((cache.tables_len(), cache.contracts_len()), (1, 1));
+        assert_eq!(cache.table(&hands), Some(table));
+        assert_eq!(cache.table(&other_hands), None);
+        for declarer in SIDES{
+            for strain in TRUMPS{
+                assert_eq!(cache.contract_tricks(&hands, declarer, &strain), Some(table.tricks(declarer, &strain)));
+            }
+        }
+        assert_eq!(solve_contract_cached::<DistinctCardGrouper, DummyNodeStore>(&cache, &other_hands, contract).ok(),
+            cache.contract_tricks(&other_hands, South, &TrumpGen::NoTrump));
+        assert_eq!(cache.misses(), 1);
+
+        cache.insert_table(&hands, table).unwrap();
+        drop(cache);
+        let expected_size = 8 + 41 + 24;
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), expected_size);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,754 @@
+//! Counterfactual regret minimisation in small endgames.
+//!
+//! From position in the middle of play complete imperfect information game tree is built: chance
+//! selects layout of cards not played yet from [`EndgamePrior`], then declarer (playing also for
+//! dummy) and both defenders play cards knowing their own hand, dummy and cards played.
+//! Information set of agent is therefore identified by its hand and sequence of cards played.
+//! Tree is solved with CFR or CFR+ as zero sum game of declaring and defending axis, resulting
+//! average strategies ([`EndgameStrategy`]) are usable as [`EndgamePolicy`] and as reference to
+//! measure how much other policies lose in endgames.
+//!
+//! Declarer (with dummy) is single player with perfect recall, but defenders are two players with
+//! separate information, so the game is not two player zero sum game and regret minimisation has
+//! no guarantee to converge to equilibrium. Quality of solution should be checked with
+//! [`EndgameStrategy::exploitability`].
+//!
+//! Size of tree grows very fast: prior built with [`EndgamePrior::from_public`] for 3 tricks left
+//! has up to 1680 layouts (9 hidden cards in three hands), every one with up to `6^4` card
+//! sequences, and every iteration traverses it twice. Hence [`MAX_ENDGAME_TRICKS`].
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use brydz_core::amfiteatr::re_export::agent::Policy;
+use brydz_core::amfiteatr::re_export::error::AmfiteatrError;
+use brydz_core::amfiteatr::re_export::scheme::Scheme;
+use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetSimple, ContractInfoSet, CreatedContractInfoSet};
+use brydz_core::contract::{Contract, ContractMechanics};
+use brydz_core::deal::BiasedHandDistribution;
+use brydz_core::error::BridgeCoreError;
+use brydz_core::karty::cards::{Card, Card2SymTrait, STANDARD_DECK};
+use brydz_core::karty::set::{CardSet, CardSetStd};
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use crate::error::DoubleDummyError;
+use crate::ismcts::rollout_information_set;
+use crate::play::{legal_cards, play_card};
+use crate::single_dummy::{played_cards, shown_voids};
+
+/// Maximal number of tricks left in endgame accepted by [`EndgameSolver`]. With 4 tricks public
+/// prior would have 34650 layouts, which is too many to traverse hundreds of times.
+pub const MAX_ENDGAME_TRICKS: usize = 3;
+
+/// Cards played since root of endgame, in order of play.
+type History = SmallVec<[u8; 16]>;
+
+/// Mixed strategy in information set.
+type CardDistribution = SmallVec<[(Card, f64); 13]>;
+
+/// Side deciding about card on move: declarer for dummy.
+fn deciding_agent(contract: &Contract) -> Side{
+    match contract.current_side() == contract.dummy(){
+        true => contract.declarer(),
+        false => contract.current_side()
+    }
+}
+
+/// Number of cards not played yet by side, when `tricks` are left (including current one).
+fn cards_left(contract: &Contract, side: Side, tricks: usize) -> usize{
+    match contract.current_trick()[side]{
+        Some(_) => tricks.saturating_sub(1),
+        None => tricks
+    }
+}
+
+/// Indexes of all cards played in contract, in order of play.
+fn play_order(contract: &Contract) -> Vec<u8>{
+    let mut tricks = contract.completed_tricks();
+    tricks.push(contract.current_trick().clone());
+    let mut order = Vec::with_capacity(tricks.len() * 4);
+    for trick in tricks{
+        let first = trick.first_player_side();
+        for i in 0..trick.count_cards(){
+            if let Some(card) = trick[first.next_i(i)]{
+                order.push(card.usize_index() as u8);
+            }
+        }
+    }
+    order
+}
+
+/// Variant of regret minimisation.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CfrVariant{
+    /// Regret matching with uniform averaging of strategies.
+    Vanilla,
+    /// Regrets floored at zero and averaging weighted by iteration, converges much faster.
+    #[default]
+    Plus,
+}
+
+/// Probability distribution over layouts of cards not played yet.
+#[derive(Debug, Clone)]
+pub struct EndgamePrior{
+    layouts: Vec<(SideMap<CardSetStd>, f64)>,
+}
+
+impl EndgamePrior{
+    /// Layouts with weights, normalised to probabilities. Layouts without positive weight are dropped.
+    pub fn new(layouts: Vec<(SideMap<CardSetStd>, f64)>) -> Result<Self, DoubleDummyError>{
+        let total: f64 = layouts.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total <= 0.0{
+            return Err(DoubleDummyError::NoConsistentLayout)
+        }
+        Ok(Self{
+            layouts: layouts.into_iter()
+                .filter(|(_, weight)| *weight > 0.0)
+                .map(|(layout, weight)| (layout, weight / total))
+                .collect()
+        })
+    }
+
+    /// Every layout of cards not played yet that is consistent with public information: dummy hand
+    /// and voids shown in play (whole deck is assumed to be dealt). Weight of layout is product of
+    /// probabilities of its hidden cards in distribution, so fair distribution gives uniform prior.
+    pub fn from_public(contract: &Contract, dummy_hand: CardSetStd, distribution: &BiasedHandDistribution) -> Result<Self, DoubleDummyError>{
+        let dummy = contract.dummy();
+        let tricks = match contract.current_trick()[dummy]{
+            Some(_) => dummy_hand.len() + 1,
+            None => dummy_hand.len()
+        };
+        if tricks > MAX_ENDGAME_TRICKS{
+            return Err(DoubleDummyError::EndgameTooLarge{tricks, limit: MAX_ENDGAME_TRICKS})
+        }
+        let played = played_cards(contract)?;
+        let voids = shown_voids(contract);
+        let hidden: Vec<Card> = STANDARD_DECK.into_iter()
+            .filter(|card| !played.contains(card) && !dummy_hand.contains(card))
+            .collect();
+        let mut free = SideMap::new_with_fn(|side| match side == dummy{
+            true => 0,
+            false => cards_left(contract, side, tricks)
+        });
+        if hidden.len() != SIDES.iter().map(|side| free[side]).sum::<usize>(){
+            return Err(DoubleDummyError::NoConsistentLayout)
+        }
+        let probability = |card: &Card, side: Side| match voids[&side][card.suit()]{
+            true => 0.0,
+            false => f64::from(f32::from(distribution.card_probabilities(card)[&side]))
+        };
+        let mut layout = SideMap::new_symmetric(CardSetStd::empty());
+        layout[&dummy] = dummy_hand;
+        let mut layouts = Vec::new();
+        Self::assign(&hidden, &mut layout, &mut free, 1.0, &probability, &mut layouts)?;
+        Self::new(layouts)
+    }
+
+    fn assign<P: Fn(&Card, Side) -> f64>(cards: &[Card], layout: &mut SideMap<CardSetStd>, free: &mut SideMap<usize>,
+        weight: f64, probability: &P, layouts: &mut Vec<(SideMap<CardSetStd>, f64)>) -> Result<(), DoubleDummyError>{
+        let Some((card, rest)) = cards.split_first() else {
+            layouts.push((layout.clone(), weight));
+            return Ok(())
+        };
+        for side in SIDES{
+            let p = probability(card, side);
+            if free[&side] > 0 && p > 0.0{
+                layout[&side].insert_card(*card).map_err(BridgeCoreError::from)?;
+                free[&side] -= 1;
+                Self::assign(rest, layout, free, weight * p, probability, layouts)?;
+                layout[&side].remove_card(card).map_err(BridgeCoreError::from)?;
+                free[&side] += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Layouts with their probabilities.
+    pub fn layouts(&self) -> &[(SideMap<CardSetStd>, f64)]{
+        &self.layouts
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct InfoSetKey{
+    /// Index of deciding agent.
+    agent: u8,
+    /// Cards of agent not played yet.
+    hand: u64,
+    history: History,
+}
+
+struct InfoSetNode{
+    cards: SmallVec<[Card; 13]>,
+    regrets: SmallVec<[f64; 13]>,
+    strategy_sum: SmallVec<[f64; 13]>,
+    /// Increments of regrets collected over all layouts, current strategy must not change before
+    /// every layout is traversed.
+    regret_updates: SmallVec<[f64; 13]>,
+    strategy_updates: SmallVec<[f64; 13]>,
+}
+
+impl InfoSetNode{
+    fn new(cards: SmallVec<[Card; 13]>) -> Self{
+        let zeros: SmallVec<[f64; 13]> = SmallVec::from_elem(0.0, cards.len());
+        Self{cards, regrets: zeros.clone(), strategy_sum: zeros.clone(), regret_updates: zeros.clone(), strategy_updates: zeros}
+    }
+
+    /// Adds increments collected in iteration, regrets are floored at zero in CFR+.
+    fn apply_updates(&mut self, variant: CfrVariant){
+        for (regret, update) in self.regrets.iter_mut().zip(self.regret_updates.iter_mut()){
+            *regret += std::mem::take(update);
+            if variant == CfrVariant::Plus{
+                *regret = regret.max(0.0);
+            }
+        }
+        for (sum, update) in self.strategy_sum.iter_mut().zip(self.strategy_updates.iter_mut()){
+            *sum += std::mem::take(update);
+        }
+    }
+
+    fn normalised(values: &[f64]) -> SmallVec<[f64; 13]>{
+        let total: f64 = values.iter().map(|v| v.max(0.0)).sum();
+        match total > 0.0{
+            true => values.iter().map(|v| v.max(0.0) / total).collect(),
+            false => SmallVec::from_elem(1.0 / values.len() as f64, values.len())
+        }
+    }
+
+    /// Regret matching.
+    fn current_strategy(&self) -> SmallVec<[f64; 13]>{
+        Self::normalised(&self.regrets)
+    }
+
+    fn average_strategy(&self) -> CardDistribution{
+        self.cards.iter().copied().zip(Self::normalised(&self.strategy_sum)).collect()
+    }
+}
+
+/// Layout of cards not played yet with probability of reaching current history by chance and by
+/// agents that do not best respond.
+#[derive(Clone)]
+struct World{
+    hands: SideMap<CardSetStd>,
+    reach: f64,
+}
+
+/// Reach probabilities of history: contributions of agents and of chance.
+#[derive(Clone)]
+struct Reach{
+    agents: SideMap<f64>,
+    chance: f64,
+}
+
+/// Builds and solves endgame tree from position (contract) and prior over layouts.
+/// # Example:
+/// ```
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::cfr::{EndgamePrior, EndgameSolver};
+/// let contract = Contract::new(
+///     ContractParametersGen::new(South, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, KING_SPADES],
+///     card_set![QUEEN_SPADES, JACK_SPADES],
+///     card_set![ACE_HEARTS, KING_HEARTS],
+///     card_set![QUEEN_HEARTS, JACK_HEARTS]);
+/// let prior = EndgamePrior::new(vec![(hands, 1.0)]).unwrap();
+/// let strategy = EndgameSolver::new(contract, prior).unwrap().solve(10).unwrap();
+/// assert!((strategy.expected_tricks().unwrap() - 2.0).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EndgameSolver{
+    contract: Contract,
+    prior: EndgamePrior,
+    variant: CfrVariant,
+}
+
+impl EndgameSolver{
+    /// Checks that every layout of prior holds exactly cards not played yet, with the same dummy hand,
+    /// and that endgame is small enough.
+    pub fn new(contract: Contract, prior: EndgamePrior) -> Result<Self, DoubleDummyError>{
+        let dummy = contract.dummy();
+        let (first, _) = prior.layouts.first().ok_or(DoubleDummyError::NoConsistentLayout)?;
+        let tricks = SIDES.iter().map(|side| first[side].len()).max().unwrap_or(0);
+        if tricks > MAX_ENDGAME_TRICKS{
+            return Err(DoubleDummyError::EndgameTooLarge{tricks, limit: MAX_ENDGAME_TRICKS})
+        }
+        let played = u64::from(played_cards(&contract)?);
+        let dummy_hand = u64::from(first[&dummy]);
+        for (layout, _) in &prior.layouts{
+            let mut cards = played;
+            for side in SIDES{
+                let hand = u64::from(layout[&side]);
+                if hand & cards != 0 || layout[&side].len() != cards_left(&contract, side, tricks)
+                    || (side == dummy && hand != dummy_hand){
+                    return Err(DoubleDummyError::NoConsistentLayout)
+                }
+                cards |= hand;
+            }
+        }
+        Ok(Self{contract, prior, variant: CfrVariant::default()})
+    }
+
+    pub fn with_variant(mut self, variant: CfrVariant) -> Self{
+        self.variant = variant;
+        self
+    }
+
+    /// Runs `iterations` of regret minimisation, every iteration updates declaring and then defending
+    /// axis traversing whole tree for every layout of prior. Updates are applied after all layouts
+    /// are traversed.
+    pub fn solve(&self, iterations: usize) -> Result<EndgameStrategy, DoubleDummyError>{
+        let mut nodes = HashMap::new();
+        let declaring = self.contract.declarer().axis();
+        let defending = self.contract.declarer().next().axis();
+        for iteration in 1..=iterations{
+            let weight = match self.variant{
+                CfrVariant::Vanilla => 1.0,
+                CfrVariant::Plus => iteration as f64
+            };
+            for updating in [declaring, defending]{
+                for (layout, probability) in &self.prior.layouts{
+                    let reach = Reach{agents: SideMap::new_symmetric(1.0), chance: *probability};
+                    self.traverse(&mut nodes, &self.contract, layout, &mut History::new(), &reach, (updating, weight))?;
+                }
+                for node in nodes.values_mut(){
+                    node.apply_updates(self.variant);
+                }
+            }
+        }
+        Ok(EndgameStrategy{
+            contract: self.contract.clone(),
+            prior: self.prior.clone(),
+            root_order: play_order(&self.contract),
+            strategies: nodes.into_iter().map(|(key, node)| (key, node.average_strategy())).collect(),
+        })
+    }
+
+    /// Returns tricks of declaring axis (in whole contract) expected under current strategies,
+    /// collecting updates of regrets and strategy sums of agents of `updating` axis.
+    fn traverse(&self, nodes: &mut HashMap<InfoSetKey, InfoSetNode>, contract: &Contract, hands: &SideMap<CardSetStd>,
+        history: &mut History, reach: &Reach, (updating, weight): (Axis, f64)) -> Result<f64, DoubleDummyError>{
+        let side = contract.current_side();
+        if hands[&side].is_empty(){
+            return Ok(contract.total_tricks_taken_axis(contract.declarer().axis()) as f64)
+        }
+        let agent = deciding_agent(contract);
+        let key = InfoSetKey{agent: agent.index(), hand: u64::from(hands[&agent]), history: history.clone()};
+        let node = nodes.entry(key.clone()).or_insert_with(|| InfoSetNode::new(legal_cards(contract, &hands[&side])));
+        let strategy = node.current_strategy();
+        let cards = node.cards.clone();
+
+        let mut values: SmallVec<[f64; 13]> = SmallVec::new();
+        let mut value = 0.0;
+        for (card, probability) in cards.iter().zip(strategy.iter()){
+            let mut next_contract = contract.clone();
+            let mut next_hands = hands.clone();
+            play_card(&mut next_contract, &mut next_hands, side, *card)?;
+            let mut next_reach = reach.clone();
+            next_reach.agents[&agent] *= probability;
+            history.push(card.usize_index() as u8);
+            let card_value = self.traverse(nodes, &next_contract, &next_hands, history, &next_reach, (updating, weight))?;
+            history.pop();
+            values.push(card_value);
+            value += probability * card_value;
+        }
+
+        if agent.axis() == updating{
+            let counterfactual_reach = reach.chance * SIDES.iter()
+                .filter(|s| **s != agent && **s != contract.dummy())
+                .map(|s| reach.agents[s])
+                .product::<f64>();
+            let sign = match agent.axis() == contract.declarer().axis(){
+                true => 1.0,
+                false => -1.0
+            };
+            if let Some(node) = nodes.get_mut(&key){
+                for ((regret, sum), (card_value, probability)) in node.regret_updates.iter_mut()
+                    .zip(node.strategy_updates.iter_mut())
+                    .zip(values.iter().zip(strategy.iter())){
+                    *regret += counterfactual_reach * sign * (card_value - value);
+                    *sum += reach.agents[&agent] * probability * weight;
+                }
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Average strategies of solved endgame.
+#[derive(Debug, Clone)]
+pub struct EndgameStrategy{
+    contract: Contract,
+    prior: EndgamePrior,
+    root_order: Vec<u8>,
+    strategies: HashMap<InfoSetKey, CardDistribution>,
+}
+
+impl EndgameStrategy{
+    /// Root position of endgame.
+    pub fn contract(&self) -> &Contract{
+        &self.contract
+    }
+
+    pub fn prior(&self) -> &EndgamePrior{
+        &self.prior
+    }
+
+    /// Number of information sets in tree.
+    pub fn information_sets(&self) -> usize{
+        self.strategies.len()
+    }
+
+    /// Strategy of agent (declarer also when dummy is on move) with `hand` of cards not played yet,
+    /// in position reached from root. `None` when position is not in solved tree.
+    pub fn strategy(&self, contract: &Contract, agent: Side, hand: &CardSetStd) -> Option<&[(Card, f64)]>{
+        let order = play_order(contract);
+        if !order.starts_with(&self.root_order){
+            return None
+        }
+        let key = InfoSetKey{
+            agent: agent.index(),
+            hand: u64::from(*hand),
+            history: order[self.root_order.len()..].iter().copied().collect()
+        };
+        self.strategies.get(&key).map(|strategy| strategy.as_slice())
+    }
+
+    /// Expected number of tricks taken by declaring axis since root, when both axes play
+    /// equilibrium strategies.
+    pub fn expected_tricks(&self) -> Result<f64, DoubleDummyError>{
+        self.expected_value(self.contract.declarer().axis(), |contract, hands, history| self.equilibrium(contract, hands, history))
+    }
+
+    /// Expected number of tricks taken by `axis` since root, when it plays with `policy` (one
+    /// action is asked for every decision) and opponents play average strategies of solution.
+    /// Difference to [`expected_tricks`](Self::expected_tricks) is loss of policy against these
+    /// strategies, not its exploitability (see [`exploit_policy`](Self::exploit_policy)).
+    pub fn evaluate_policy<P>(&self, axis: Axis, policy: &P) -> Result<f64, AmfiteatrError<ContractDP>>
+    where P: Policy<ContractDP>, P::InfoSetType: CreatedContractInfoSet{
+        self.expected_value(axis, |contract, hands, history| {
+            if deciding_agent(contract).axis() != axis{
+                return self.equilibrium(contract, hands, history).map_err(AmfiteatrError::from)
+            }
+            Self::policy_card(policy, contract, hands)
+        })
+    }
+
+    /// Expected number of tricks taken by `axis` since root, when it plays with `policy` and
+    /// opponents best respond to it (see [`best_response`](Self::best_response)). Difference to
+    /// value of solution measures how much policy can be exploited in endgame.
+    pub fn exploit_policy<P>(&self, axis: Axis, policy: &P) -> Result<f64, AmfiteatrError<ContractDP>>
+    where P: Policy<ContractDP>, P::InfoSetType: CreatedContractInfoSet{
+        let opponents = match axis == self.contract.declarer().axis(){
+            true => self.contract.declarer().next().axis(),
+            false => self.contract.declarer().axis()
+        };
+        let responding = self.best_response_value(opponents, |contract, hands, _| Self::policy_card(policy, contract, hands))?;
+        Ok(self.tricks_left() as f64 - responding)
+    }
+
+    /// Expected number of tricks taken by `axis` since root, when it best responds to average
+    /// strategies of the other axis. Every agent of `axis` selects one card in every of its
+    /// information sets. Response of declarer is exact, but defenders respond one after another
+    /// bottom-up, which gives lower bound of response of coordinated defenders.
+    pub fn best_response(&self, axis: Axis) -> Result<f64, DoubleDummyError>{
+        self.best_response_value(axis, |contract, hands, history| self.equilibrium(contract, hands, history))
+    }
+
+    /// Mean gain (in tricks) of both axes from best responding to average strategy of the other
+    /// one, it is zero for exact equilibrium.
+    pub fn exploitability(&self) -> Result<f64, DoubleDummyError>{
+        let declaring = self.best_response(self.contract.declarer().axis())?;
+        let defending = self.best_response(self.contract.declarer().next().axis())?;
+        Ok((declaring + defending - self.tricks_left() as f64) / 2.0)
+    }
+
+    /// Number of tricks left since root, including current one.
+    fn tricks_left(&self) -> usize{
+        self.prior.layouts.first()
+            .map(|(layout, _)| SIDES.iter().map(|side| layout[side].len()).max().unwrap_or(0))
+            .unwrap_or(0)
+    }
+
+    fn policy_card<P>(policy: &P, contract: &Contract, hands: &SideMap<CardSetStd>) -> Result<CardDistribution, AmfiteatrError<ContractDP>>
+    where P: Policy<ContractDP>, P::InfoSetType: CreatedContractInfoSet{
+        let info_set: P::InfoSetType = rollout_information_set(contract, hands);
+        match policy.select_action(&info_set)?{
+            ContractAction::PlaceCard(card) => Ok(SmallVec::from_elem((card, 1.0), 1)),
+            ContractAction::ShowHand(_) => Err(AmfiteatrError::NoActionAvailable {
+                context: "Policy showed hand instead of playing card in endgame".into()
+            })
+        }
+    }
+
+    fn equilibrium(&self, contract: &Contract, hands: &SideMap<CardSetStd>, history: &History) -> Result<CardDistribution, DoubleDummyError>{
+        let agent = deciding_agent(contract);
+        let key = InfoSetKey{agent: agent.index(), hand: u64::from(hands[&agent]), history: history.clone()};
+        self.strategies.get(&key).cloned().ok_or(DoubleDummyError::NoConsistentLayout)
+    }
+
+    fn expected_value<E, F>(&self, axis: Axis, choose: F) -> Result<f64, E>
+    where E: From<DoubleDummyError>, F: Fn(&Contract, &SideMap<CardSetStd>, &History) -> Result<CardDistribution, E>{
+        let base = self.contract.total_tricks_taken_axis(axis) as f64;
+        let mut total = 0.0;
+        for (layout, probability) in &self.prior.layouts{
+            let value = Self::walk(&self.contract, layout, &mut History::new(), axis, &choose)?;
+            total += probability * (value - base);
+        }
+        Ok(total)
+    }
+
+    fn best_response_value<E, F>(&self, axis: Axis, choose: F) -> Result<f64, E>
+    where E: From<DoubleDummyError>, F: Fn(&Contract, &SideMap<CardSetStd>, &History) -> Result<CardDistribution, E>{
+        let base = self.contract.total_tricks_taken_axis(axis) as f64;
+        let worlds: Vec<World> = self.prior.layouts.iter()
+            .map(|(hands, probability)| World{hands: hands.clone(), reach: *probability})
+            .collect();
+        let values = Self::respond(&self.contract, &worlds, &mut History::new(), axis, &choose)?;
+        Ok(worlds.iter().zip(values).map(|(world, value)| world.reach * (value - base)).sum())
+    }
+
+    /// Tricks of `axis` (in whole contract) in every world, when `axis` best responds to `choose`.
+    /// Worlds are grouped by hand of deciding agent, so response is the same in information set.
+    fn respond<E, F>(contract: &Contract, worlds: &[World], history: &mut History, axis: Axis, choose: &F) -> Result<Vec<f64>, E>
+    where E: From<DoubleDummyError>, F: Fn(&Contract, &SideMap<CardSetStd>, &History) -> Result<CardDistribution, E>{
+        let side = contract.current_side();
+        let agent = deciding_agent(contract);
+        let mut values = vec![0.0; worlds.len()];
+        if worlds.first().is_none_or(|world| world.hands[&side].is_empty()){
+            values.fill(contract.total_tricks_taken_axis(axis) as f64);
+            return Ok(values)
+        }
+        let mut groups: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, world) in worlds.iter().enumerate(){
+            groups.entry(u64::from(world.hands[&agent])).or_default().push(index);
+        }
+        for group in groups.values(){
+            let hands = &worlds[group[0]].hands;
+            if agent.axis() == axis{
+                let mut best: Option<(f64, Vec<f64>)> = None;
+                for card in legal_cards(contract, &hands[&side]){
+                    let (next_contract, next_worlds) = Self::after_card(contract, worlds, group, card, 1.0)?;
+                    history.push(card.usize_index() as u8);
+                    let card_values = Self::respond(&next_contract, &next_worlds, history, axis, choose)?;
+                    history.pop();
+                    let total: f64 = next_worlds.iter().zip(card_values.iter()).map(|(world, value)| world.reach * value).sum();
+                    if best.as_ref().is_none_or(|(best_total, _)| total > *best_total){
+                        best = Some((total, card_values));
+                    }
+                }
+                let (_, best_values) = best.ok_or(DoubleDummyError::NoConsistentLayout)?;
+                for (index, value) in group.iter().zip(best_values){
+                    values[*index] = value;
+                }
+            } else {
+                for (card, probability) in choose(contract, hands, history)?{
+                    if probability <= 0.0{
+                        continue;
+                    }
+                    let (next_contract, next_worlds) = Self::after_card(contract, worlds, group, card, probability)?;
+                    history.push(card.usize_index() as u8);
+                    let card_values = Self::respond(&next_contract, &next_worlds, history, axis, choose)?;
+                    history.pop();
+                    for (index, value) in group.iter().zip(card_values){
+                        values[*index] += probability * value;
+                    }
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// Position after side on move plays `card` in selected worlds.
+    fn after_card(contract: &Contract, worlds: &[World], selected: &[usize], card: Card, probability: f64) -> Result<(Contract, Vec<World>), DoubleDummyError>{
+        let side = contract.current_side();
+        let mut next_contract = contract.clone();
+        next_contract.insert_card(side, card).map_err(BridgeCoreError::from)?;
+        let mut next_worlds = Vec::with_capacity(selected.len());
+        for index in selected{
+            let mut world = worlds[*index].clone();
+            world.hands[&side].remove_card(&card).map_err(BridgeCoreError::from)?;
+            world.reach *= probability;
+            next_worlds.push(world);
+        }
+        Ok((next_contract, next_worlds))
+    }
+
+    fn walk<E, F>(contract: &Contract, hands: &SideMap<CardSetStd>, history: &mut History, axis: Axis, choose: &F) -> Result<f64, E>
+    where E: From<DoubleDummyError>, F: Fn(&Contract, &SideMap<CardSetStd>, &History) -> Result<CardDistribution, E>{
+        let side = contract.current_side();
+        if hands[&side].is_empty(){
+            return Ok(contract.total_tricks_taken_axis(axis) as f64)
+        }
+        let mut value = 0.0;
+        for (card, probability) in choose(contract, hands, history)?{
+            if probability <= 0.0{
+                continue;
+            }
+            let mut next_contract = contract.clone();
+            let mut next_hands = hands.clone();
+            play_card(&mut next_contract, &mut next_hands, side, card)?;
+            history.push(card.usize_index() as u8);
+            value += probability * Self::walk(&next_contract, &next_hands, history, axis, choose)?;
+            history.pop();
+        }
+        Ok(value)
+    }
+}
+
+/// Plays solved endgame: card is sampled from equilibrium strategy of information set, with
+/// randomness seeded from policy seed and position (so it is repeatable).
+/// Positions outside of solved tree are reported as [`AmfiteatrError::NoActionAvailable`].
+#[derive(Debug, Clone)]
+pub struct EndgamePolicy{
+    strategy: Arc<EndgameStrategy>,
+    seed: u64,
+}
+
+impl EndgamePolicy{
+    pub fn new(strategy: Arc<EndgameStrategy>) -> Self{
+        Self{strategy, seed: 0}
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self{
+        self.seed = seed;
+        self
+    }
+
+    pub fn strategy(&self) -> &EndgameStrategy{
+        &self.strategy
+    }
+}
+
+impl Policy<ContractDP> for EndgamePolicy{
+    type InfoSetType = ContractAgentInfoSetSimple;
+
+    fn select_action(&self, state: &Self::InfoSetType) -> Result<<ContractDP as Scheme>::ActionType, AmfiteatrError<ContractDP>> {
+        if state.side() == state.dummy_side(){
+            return Ok(ContractAction::ShowHand(*state.hand()))
+        }
+        let contract = state.contract_data();
+        let strategy = self.strategy.strategy(contract, state.side(), state.hand())
+            .filter(|strategy| !strategy.is_empty())
+            .ok_or_else(|| AmfiteatrError::NoActionAvailable {
+                context: format!("Position of {:?} is not in solved endgame", state.side())
+            })?;
+        let mut hasher = DefaultHasher::new();
+        (self.seed, u64::from(*state.hand()), play_order(contract)).hash(&mut hasher);
+        let mut sample = StdRng::seed_from_u64(hasher.finish()).random_range(0.0..1.0);
+        let card = strategy.iter()
+            .find(|(_, probability)| {
+                sample -= probability;
+                sample < 0.0
+            })
+            .unwrap_or(&strategy[strategy.len() - 1]).0;
+        Ok(ContractAction::PlaceCard(card))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::sync::Arc;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use brydz_core::amfiteatr::HeuristicPolicy;
+    use brydz_core::amfiteatr::re_export::agent::Policy;
+    use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetSimple};
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::deal::BiasedHandDistribution;
+    use brydz_core::deal::fair_bridge_deal_with_rng;
+    use brydz_core::karty::{card_set, cards::*};
+    use brydz_core::karty::set::{CardSet, CardSetStd};
+    use brydz_core::karty::suits::Suit::Hearts;
+    use brydz_core::player::side::{SideMap, Side::*, SIDES};
+    use crate::cfr::{CfrVariant, EndgamePolicy, EndgamePrior, EndgameSolver};
+    use crate::error::DoubleDummyError;
+    use crate::test_util::two_tricks_left;
+
+    /// South won first trick with ace of clubs and leads spade towards dummy's ace and queen:
+    /// West holds king or not with equal probability.
+    fn finesse() -> (Contract, EndgamePrior){
+        let mut contract = Contract::new(ContractParametersGen::new(South, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+        contract.insert_card(West, TWO_CLUBS).unwrap();
+        contract.insert_card(North, THREE_CLUBS).unwrap();
+        contract.insert_card(East, FOUR_CLUBS).unwrap();
+        contract.insert_card(South, ACE_CLUBS).unwrap();
+        let king_west = SideMap::new(
+            card_set![ACE_SPADES, QUEEN_SPADES],
+            card_set![SIX_SPADES, FIVE_SPADES],
+            card_set![THREE_SPADES, TWO_SPADES],
+            card_set![KING_SPADES, FOUR_SPADES]);
+        let king_east = SideMap::new(
+            card_set![ACE_SPADES, QUEEN_SPADES],
+            card_set![KING_SPADES, SIX_SPADES],
+            card_set![THREE_SPADES, TWO_SPADES],
+            card_set![FIVE_SPADES, FOUR_SPADES]);
+        (contract, EndgamePrior::new(vec![(king_west, 1.0), (king_east, 1.0)]).unwrap())
+    }
+
+    #[test]
+    fn finesse_is_taken_in_equilibrium(){
+        let (contract, prior) = finesse();
+        for variant in [CfrVariant::Vanilla, CfrVariant::Plus]{
+            let strategy = EndgameSolver::new(contract.clone(), prior.clone()).unwrap()
+                .with_variant(variant)
+                .solve(300).unwrap();
+            assert!((strategy.expected_tricks().unwrap() - 1.5).abs() < 0.05);
+        }
+        let strategy = Arc::new(EndgameSolver::new(contract.clone(), prior).unwrap().solve(300).unwrap());
+        let mut position = contract.clone();
+        position.insert_card(South, TWO_SPADES).unwrap();
+        position.insert_card(West, FOUR_SPADES).unwrap();
+        let south = ContractAgentInfoSetSimple::new(South, card_set![THREE_SPADES], position,
+            Some(card_set![ACE_SPADES, QUEEN_SPADES]));
+        let policy = EndgamePolicy::new(strategy.clone()).with_seed(3);
+        assert_eq!(policy.select_action(&south).unwrap(), ContractAction::PlaceCard(QUEEN_SPADES));
+
+        // West does not play king second hand, so finessing is the best declarer can do
+        assert!((strategy.best_response(South.axis()).unwrap() - 1.5).abs() < 0.05);
+        assert!(strategy.exploitability().unwrap() < 0.05);
+
+        let heuristic = strategy.evaluate_policy(South.axis(), &HeuristicPolicy::new()).unwrap();
+        assert!(heuristic <= 1.5 + 0.1);
+        let exploited = strategy.exploit_policy(South.axis(), &HeuristicPolicy::new()).unwrap();
+        assert!(exploited <= heuristic + 1e-9);
+    }
+
+    #[test]
+    fn public_prior_enumerates_hidden_layouts(){
+        let hands = fair_bridge_deal_with_rng::<CardSetStd, _>(&mut StdRng::seed_from_u64(4));
+        let contract = Contract::new(ContractParametersGen::new(South, Bid::init(TrumpGen::Colored(Hearts), 2).unwrap()));
+        let too_large = EndgamePrior::from_public(&contract, hands[&North], &BiasedHandDistribution::default());
+        assert!(matches!(too_large, Err(DoubleDummyError::EndgameTooLarge{tricks: 13, ..})));
+
+        let contract = two_tricks_left();
+        let dummy_hand = card_set![ACE_SPADES, KING_SPADES];
+        let prior = EndgamePrior::from_public(&contract, dummy_hand, &BiasedHandDistribution::default()).unwrap();
+        // six hidden cards in three hands of two
+        assert_eq!(prior.layouts().len(), 90);
+        for (layout, probability) in prior.layouts(){
+            assert!((probability - 1.0 / 90.0).abs() < 1e-12);
+            assert_eq!(layout[&North], dummy_hand);
+            assert!(SIDES.iter().all(|side| layout[side].len() == 2));
+        }
+
+        // dummy cashes both top spades in every layout
+        let strategy = EndgameSolver::new(contract, prior).unwrap().solve(20).unwrap();
+        assert!((strategy.expected_tricks().unwrap() - 2.0).abs() < 1e-9);
+        assert!((strategy.best_response(South.axis()).unwrap() - 2.0).abs() < 1e-9);
+        assert!(strategy.best_response(East.axis()).unwrap().abs() < 1e-9);
+        assert!(strategy.exploitability().unwrap().abs() < 1e-9);
+    }
+}
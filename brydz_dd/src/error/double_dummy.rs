@@ -1,13 +1,24 @@
 use std::fmt::{Display, Formatter};
-use brydz_core::error::BridgeCoreError;
+use brydz_core::error::{BridgeCoreError, ScoreError};
 use crate::actions::CardPack;
 use crate::error::OptimiserError;
+use crate::explore::ExploreOutput;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DoubleDummyError{
     Core(BridgeCoreError),
     EmptyPack(CardPack),
-    Optimiser(OptimiserError)
+    Optimiser(OptimiserError),
+    UnboundedValue(ExploreOutput),
+    /// Exploration was stopped by other thread.
+    Interrupted,
+    /// No layout of hidden cards consistent with player's knowledge was found.
+    NoConsistentLayout,
+    /// Endgame has too many tricks left to be solved with full game tree.
+    EndgameTooLarge{
+        tricks: usize,
+        limit: usize
+    }
 }
 
 impl Display for DoubleDummyError{
@@ -20,4 +31,10 @@ impl From<BridgeCoreError> for DoubleDummyError{
     fn from(e: BridgeCoreError) -> Self {
         Self::Core(e)
     }
-}
\ No newline at end of file
+}
+
+impl From<ScoreError> for DoubleDummyError{
+    fn from(e: ScoreError) -> Self {
+        Self::Core(e.into())
+    }
+}
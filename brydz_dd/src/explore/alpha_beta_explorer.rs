@@ -1,7 +1,8 @@
 use std::cmp::{max, min};
 
 use brydz_core::contract::{Contract, ContractMechanics};
-use brydz_core::error::{BridgeCoreErrorGen};
+use brydz_core::amfiteatr::state::{ContractEnvStateComplete, ContractState};
+use brydz_core::error::{BridgeCoreError, BridgeCoreErrorGen, ContractErrorGen};
 use brydz_core::karty::cards::Card;
 use brydz_core::player::axis::Axis;
 use brydz_core::player::side::Side;
@@ -10,15 +11,17 @@ use crate::error::{DoubleDummyError};
 use crate::node::TrickNode;
 use std::fmt::{Debug};
 use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use smallvec::SmallVec;
-use brydz_core::karty::set::CardSetStd;
+use brydz_core::karty::set::{CardSet, CardSetStd};
 use brydz_core::player::axis::Axis::NorthSouth;
 use crate::actions::ActionOptimiser;
-use crate::explore::{ExploreOutput, ExplorerGameState, ExplorerStateUpdate};
+use crate::explore::{quick_bounds, ExploreOutput, ExplorerGameState, ExplorerStateUpdate};
 use crate::explore::track::{CardPackResult, GameTrack, TrackStep};
 use crate::actions::CardPackVec;
-use crate::hash::{NodeStoreTrait};
+use crate::hash::{NodeStoreTrait, TrickBounds};
 
 
 #[derive(Debug, Clone)]
@@ -28,7 +31,8 @@ pub struct Explorer<G: ActionOptimiser, A: NodeStoreTrait > {
     //north_south_min: ExploreOutput,
     //initial_north_south_max: ExploreOutput,
     //initial_north_south_min: ExploreOutput,
-    node_store: A
+    node_store: A,
+    stop: Option<Arc<AtomicBool>>
 
 
 
@@ -48,10 +52,41 @@ impl<G: ActionOptimiser, A: NodeStoreTrait > Explorer<G, A>{
 
         //explorer.check_valid().map(|()| explorer)
         Ok(Self{game_state: ExplorerGameState::new_checked(contract, initial_node)?, 
-            node_store: A::default(),
+            node_store: A::default(), stop: None,
         })
     }
 
+    /// Creates explorer reusing node store filled during previous exploration with the same trump.
+    pub fn new_with_node_store(contract: Contract, initial_node: TrickNode, node_store: A) -> Result<Self, DoubleDummyError>{
+        Ok(Self{game_state: ExplorerGameState::new_checked(contract, initial_node)?,
+            node_store, stop: None,
+        })
+    }
+
+    /// Creates explorer of position in game run by environment, which may be in the middle of trick.
+    pub fn new_from_env_state(state: &ContractEnvStateComplete) -> Result<Self, DoubleDummyError>{
+        let node = TrickNode::from_env_state(state).map_err(BridgeCoreError::from)?;
+        Self::new_checked(state.contract_data().clone(), node)
+    }
+
+    /// Sets flag checked at every trick opening. When it is raised exploration ends
+    /// with [`DoubleDummyError::Interrupted`].
+    pub fn set_stop_flag(&mut self, stop: Arc<AtomicBool>){
+        self.stop = Some(stop)
+    }
+
+    pub fn node_store(&self) -> &A{
+        &self.node_store
+    }
+
+    pub fn into_node_store(self) -> A{
+        self.node_store
+    }
+
+    pub(crate) fn node_store_mut(&mut self) -> &mut A{
+        &mut self.node_store
+    }
+
     /*fn update_state(&mut self, update: ExplorerStateUpdate) -> Result<(), BridgeCoreError>{
         self.game_state.update(update)
     }*/
@@ -110,10 +145,27 @@ impl<G: ActionOptimiser, A: NodeStoreTrait > Explorer<G, A>{
             return Ok((self.state().contract().total_tricks_taken_axis(Axis::NorthSouth) as u8).into())
         }
 
-        if self.game_state.contract().current_trick().is_empty(){
-            if let Some(hit_node) = self.node_store.get_value(self.game_state.actual_node()){
-                return Ok(ExploreOutput::Number(hit_node + self.state().contract().total_tricks_taken_axis(NorthSouth) as u8))
+        let trick_opening = self.game_state.contract().current_trick().is_empty();
+        if trick_opening{
+            if self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed)){
+                return Err(DoubleDummyError::Interrupted)
+            }
+            let quick = quick_bounds(self.game_state.actual_node(), self.game_state.contract().trump());
+            let bounds = match self.node_store.get_bounds(self.game_state.actual_node()){
+                Some(stored) => stored.intersect(&quick),
+                None => quick
+            };
+            let taken = self.state().contract().total_tricks_taken_axis(NorthSouth) as u8;
+            let lower = ExploreOutput::Number(bounds.lower() + taken);
+            let upper = ExploreOutput::Number(bounds.upper() + taken);
+            if lower == upper || lower > north_south_max{
+                return Ok(lower)
+            }
+            if upper < north_south_min{
+                return Ok(upper)
             }
+            north_south_min = max(north_south_min, lower);
+            north_south_max = min(north_south_max, upper);
 
             if let ExploreOutput::Number(min) = north_south_min{
                 let potential = <CardSetStd as Into<u64>>::into(self.game_state.actual_node().hands()[&self.current_side()]).count_ones() as u8
@@ -133,25 +185,25 @@ impl<G: ActionOptimiser, A: NodeStoreTrait > Explorer<G, A>{
             }
         }
 
+        let (window_min, window_max) = (north_south_min, north_south_max);
+        let ordered_actions = self.game_state.order_actions(next_actions);
         match self.current_side().axis(){
             Axis::NorthSouth => {
                 //maximising
                 let mut value = ExploreOutput::MinusInfinity;
-                for card_pack in next_actions{
+                for card_pack in ordered_actions{
                     //debug!("{:?} placing card {:#}", self.current_side(),card_pack.lowest_card() );
                     self.update(ExplorerStateUpdate::PlaceCard(card_pack.lowest_card()))?;
                     value = max(value, self.explore_actions(north_south_min, north_south_max)?);
                     self.update(ExplorerStateUpdate::Undo)?;
                     if value > north_south_max{
+                        self.game_state.record_cutoff(&card_pack.lowest_card());
                         break;
                     }
                     north_south_min = max(north_south_min, value)
                 }
-                if self.game_state.contract().current_trick().is_empty(){
-                    if let ExploreOutput::Number(v) = value{
-                        self.node_store.store_value(self.game_state.actual_node(),
-                    v - self.game_state.contract().total_tricks_taken_axis(NorthSouth) as u8);
-                    }
+                if trick_opening{
+                    self.store_result(value, window_min, window_max);
                 }
                 Ok(value)
 
@@ -159,20 +211,18 @@ impl<G: ActionOptimiser, A: NodeStoreTrait > Explorer<G, A>{
             Axis::EastWest => {
                 //minimising
                 let mut value = ExploreOutput::Infinity;
-                for card_pack in next_actions{
+                for card_pack in ordered_actions{
                     self.update(ExplorerStateUpdate::PlaceCard(card_pack.lowest_card()))?;
                     value = min(value, self.explore_actions(north_south_min, north_south_max)?);
                     self.update(ExplorerStateUpdate::Undo)?;
                     if value < north_south_min{
+                        self.game_state.record_cutoff(&card_pack.lowest_card());
                         break;
                     }
                     north_south_max = min(north_south_max, value)
                 }
-                if self.game_state.contract().current_trick().is_empty(){
-                    if let ExploreOutput::Number(v) = value{
-                        self.node_store.store_value(self.game_state.actual_node(),
-                    v - self.game_state.contract().total_tricks_taken_axis(NorthSouth) as u8);
-                    }
+                if trick_opening{
+                    self.store_result(value, window_min, window_max);
                 }
                 Ok(value)
 
@@ -180,6 +230,24 @@ impl<G: ActionOptimiser, A: NodeStoreTrait > Explorer<G, A>{
         }
     }
 
+    /// Stores value found from node opening trick. Values outside of search window are only
+    /// bounds: below it upper bound, above it lower bound.
+    fn store_result(&mut self, value: ExploreOutput, window_min: ExploreOutput, window_max: ExploreOutput){
+        if let ExploreOutput::Number(v) = value{
+            let taken = self.game_state.contract().total_tricks_taken_axis(NorthSouth) as u8;
+            let remaining = self.game_state.actual_node().hands()[&self.current_side()].len() as u8;
+            let relative = v - taken;
+            let bounds = if value < window_min{
+                TrickBounds::new(0, relative)
+            } else if value > window_max{
+                TrickBounds::new(relative, remaining)
+            } else {
+                TrickBounds::exact(relative)
+            };
+            self.node_store.store_bounds(self.game_state.actual_node(), bounds);
+        }
+    }
+
     pub fn hint(&mut self) -> Result<TrackStep, DoubleDummyError>{
         //self.reset_alpha_beta();
         let next_actions = self.state().available_actions();
@@ -188,8 +256,7 @@ impl<G: ActionOptimiser, A: NodeStoreTrait > Explorer<G, A>{
             self.state().contract().current_trick(), next_actions);
         if next_actions.and(SmallVec::is_empty){
             debug!("No next actions: current side: {:?}, tricks completed: {:?}, cards in current trick: {:?}", self.current_side(), self.state().contract().count_completed_tricks(), self.state().contract().current_trick().count_cards());
-            //return Ok((self.state().contract().total_tricks_taken_axis(Axis::NorthSouth) as u8).into())
-            todo!()
+            return Err(BridgeCoreError::from(ContractErrorGen::ContractFull).into())
         }
         let mut track_step = TrackStep::new(self.current_side());
         
@@ -467,16 +534,8 @@ impl<G: ActionOptimiser, A: NodeStoreTrait > Explorer<G, A>{
         &self.game_state
     }
 
-}
-/*
-impl<G: ActionOptimiser, A: NodeStoreTrait >  StatefulAgent<ContractProtocolSpec> for Explorer<G, A>{
-    type State = ExplorerGameState<G>;
-
-
 }
 
-*/
-
 
 
 #[cfg(test)]
@@ -489,15 +548,18 @@ mod tests{
     use brydz_core::error::ContractErrorGen::CurrentSidePresume;
     use brydz_core::karty::cards::{Card, Card2SymTrait};
     use brydz_core::karty::error::CardSetError;
-    use brydz_core::karty::figures::{Ace, King, Queen};
+    use brydz_core::karty::figures::{Ace, Jack, King, Queen};
     use brydz_core::karty::set::{CardSet, CardSetStd};
     use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
     use brydz_core::player::side::Side::{East, North, South, West};
     use crate::actions::DistinctCardGrouper;
-    use crate::explore::{Explorer};
+    use crate::explore::{BinaryExplorer, ExploreOutput, Explorer};
     use crate::explore::ExplorerStateUpdate::PlaceCard;
-    use crate::hash::DummyNodeStore;
+    use crate::hash::{DummyNodeStore, HashArrayNodeStore};
+    use crate::hash::hash24::Hash24;
+    use crate::hash::ranker::MoreCardsRanker;
     use crate::node::TrickNode;
+    use crate::table::solve_table;
 
     #[test]
     fn explorer_validation(){
@@ -552,6 +614,29 @@ mod tests{
         let result_explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node);
         assert_eq!(result_explorer.unwrap_err(), BridgeCoreError::Contract(CurrentSidePresume(South, North)).into());
     }
+
+    type SmallStore = HashArrayNodeStore<Hash24<3>, MoreCardsRanker, 256, 4>;
+
+    #[test]
+    fn bounded_store_gives_the_same_results(){
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King, Queen, Jack],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        for _ in 0..4{
+            let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply.clone(), North);
+            assert_eq!(solve_table::<DistinctCardGrouper, SmallStore>(&hands).unwrap(),
+                solve_table::<DistinctCardGrouper, DummyNodeStore>(&hands).unwrap());
+
+            let contract = Contract::new(ContractParametersGen::new(West, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+            let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+            let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract.clone(), node).unwrap();
+            let value = explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap();
+            for target in 0..=4{
+                let mut binary = BinaryExplorer::<DistinctCardGrouper, SmallStore>::new_checked(contract.clone(), node, target).unwrap();
+                assert_eq!(binary.explore_actions().unwrap(), value >= ExploreOutput::Number(target));
+            }
+        }
+    }
 }
 /*
  match self.current_side().axis(){
@@ -3,14 +3,14 @@ use log::debug;
 use smallvec::SmallVec;
 use brydz_core::contract::{Contract, ContractMechanics};
 use brydz_core::error::{BridgeCoreError, BridgeCoreErrorGen};
-use brydz_core::karty::set::CardSetStd;
+use brydz_core::karty::set::{CardSet, CardSetStd};
 use brydz_core::player::axis::Axis;
 use brydz_core::player::axis::Axis::{NorthSouth, EastWest};
 use brydz_core::player::side::Side;
 use crate::actions::ActionOptimiser;
 use crate::error::DoubleDummyError;
-use crate::explore::{ExplorerGameState, ExplorerStateUpdate};
-use crate::hash::NodeStoreTrait;
+use crate::explore::{quick_bounds, ExplorerGameState, ExplorerStateUpdate};
+use crate::hash::{NodeStoreTrait, TrickBounds};
 use crate::node::TrickNode;
 use crate::actions::CardPackVec;
 use crate::explore::ExploreOutput::Number;
@@ -20,7 +20,6 @@ use crate::explore::track::{CardPackResult, TrackStep};
 pub struct BinaryExplorer<G: ActionOptimiser, A: NodeStoreTrait>{
     game_state: ExplorerGameState<G>,
     north_south_target: u8,
-    #[allow(dead_code)]
     node_store: A
 
 }
@@ -31,6 +30,26 @@ impl<G: ActionOptimiser, A:NodeStoreTrait> BinaryExplorer<G, A>{
             node_store: A::default(), north_south_target
         })
     }
+
+    /// Creates explorer reusing node store filled during previous exploration with the same trump.
+    /// Stored bounds do not depend on target, so store can be passed between searches with different targets.
+    pub fn new_with_node_store(contract: Contract, initial_node: TrickNode, north_south_target: u8, node_store: A) -> Result<Self, BridgeCoreError>{
+        Ok(Self{game_state: ExplorerGameState::new_checked(contract, initial_node)?,
+            node_store, north_south_target
+        })
+    }
+
+    pub fn node_store(&self) -> &A{
+        &self.node_store
+    }
+
+    pub fn into_node_store(self) -> A{
+        self.node_store
+    }
+
+    pub fn north_south_target(&self) -> u8{
+        self.north_south_target
+    }
     pub fn current_side(&self) -> Side{
         self.state().contract().current_side()
     }
@@ -80,7 +99,8 @@ impl<G: ActionOptimiser, A:NodeStoreTrait> BinaryExplorer<G, A>{
             debug!("No next actions: current side: {:?}, tricks completed: {:?}, cards in current trick: {:?}", self.current_side(), self.state().contract().count_completed_tricks(), self.state().contract().current_trick().count_cards());
             return Ok((self.state().contract().total_tricks_taken_axis(Axis::NorthSouth) as u8) >= self.north_south_target)
         }
-        if self.game_state.contract().current_trick().is_empty(){
+        let trick_opening = self.game_state.contract().current_trick().is_empty();
+        if trick_opening{
             let potential = <CardSetStd as Into<u64>>::into(self.game_state.actual_node().hands()[&self.current_side()]).count_ones() as u8;
             let actual = self.game_state.contract().total_tricks_taken_axis(NorthSouth);
             if actual as u8 >= self.north_south_target{
@@ -89,34 +109,67 @@ impl<G: ActionOptimiser, A:NodeStoreTrait> BinaryExplorer<G, A>{
             if actual as u8 + potential < self.north_south_target{
                 return Ok(false)
             }
-
+            let quick = quick_bounds(self.game_state.actual_node(), self.game_state.contract().trump());
+            let bounds = match self.node_store.get_bounds(self.game_state.actual_node()){
+                Some(stored) => stored.intersect(&quick),
+                None => quick
+            };
+            if actual as u8 + bounds.lower() >= self.north_south_target{
+                return Ok(true)
+            }
+            if actual as u8 + bounds.upper() < self.north_south_target{
+                return Ok(false)
+            }
         }
-        match self.current_side().axis() {
+        let ordered_actions = self.game_state.order_actions(next_actions);
+        let result = match self.current_side().axis() {
             NorthSouth => {
-                for card_pack in next_actions {
+                let mut result = false;
+                for card_pack in ordered_actions {
                     self.update(ExplorerStateUpdate::PlaceCard(card_pack.lowest_card()))?;
                     let v = self.explore_actions()?;
                     self.update(ExplorerStateUpdate::Undo)?;
                     if v {
-                        return Ok(true);
+                        self.game_state.record_cutoff(&card_pack.lowest_card());
+                        result = true;
+                        break;
                     }
                 }
-                Ok(false)
+                result
             },
             EastWest => {
-                for card_pack in next_actions {
+                let mut result = true;
+                for card_pack in ordered_actions {
                     self.update(ExplorerStateUpdate::PlaceCard(card_pack.lowest_card()))?;
                     let v = self.explore_actions()?;
                     self.update(ExplorerStateUpdate::Undo)?;
                     if !v {
-                        return Ok(false);
+                        self.game_state.record_cutoff(&card_pack.lowest_card());
+                        result = false;
+                        break;
                     }
                 }
-                Ok(true)
+                result
             }
+        };
+        if trick_opening{
+            self.store_result(result);
         }
+        Ok(result)
 
     }
+
+    /// Stores result found from node opening trick as bound of North-South tricks.
+    fn store_result(&mut self, target_reached: bool){
+        let taken = self.game_state.contract().total_tricks_taken_axis(NorthSouth) as u8;
+        let remaining = self.game_state.actual_node().hands()[&self.current_side()].len() as u8;
+        let bounds = match target_reached{
+            true => TrickBounds::new(self.north_south_target.saturating_sub(taken), remaining),
+            false => TrickBounds::new(0, self.north_south_target.saturating_sub(taken + 1))
+        };
+        self.node_store.store_bounds(self.game_state.actual_node(), bounds);
+    }
+
     pub fn hint(&mut self) -> Result<TrackStep, DoubleDummyError>{
         let next_actions = self.state().available_actions();
         debug!("Exploring actions for side: {:?}.\nTricks completed: {:?}.\nCurrent trick: {:#}. Available action groups: {:#}",
@@ -196,19 +249,3 @@ impl<G1: ActionPrepare, G2: ActionPrepare, A: NodeStoreTrait> From<BinaryExplore
         }
     }
 } */
-/*
-impl<G: ActionOptimiser, A: NodeStoreTrait >  StatefulAgent<ContractProtocolSpec> for BinaryExplorer<G, A>{
-    type State = ExplorerGameState<G>;
-    //type StateUpdate = ExplorerStateUpdate;
-    //type Spec = ContractProtocolSpec;
-
-    fn update(&mut self, state_update: ContractStateUpdate) -> Result<(), BridgeCoreErrorGen<Card>> {
-        self.game_state.update(state_update)
-    }
-
-    fn state(&self) -> &Self::State {
-        &self.game_state
-    }
-}
-
- */
\ No newline at end of file
@@ -12,7 +12,7 @@ use log::debug;
 use smallvec::SmallVec;
 use brydz_core::karty::suits::SuitMap;
 //use brydz_core::sztorm::re_export::state::StateUpdate;
-use crate::actions::{CardPack, ActionOptimiser};
+use crate::actions::{CardPack, ActionOptimiser, VCardPack};
 use crate::explore::ExplorerStateUpdate::PlaceCard;
 use crate::node::TrickNode;
 
@@ -263,6 +263,25 @@ impl<G: ActionOptimiser> ExplorerGameState<G>{
 
     }
 
+    /// Available actions flattened to one vector and ordered by action optimiser.
+    pub fn ordered_actions(&self) -> VCardPack{
+        self.order_actions(self.available_actions())
+    }
+
+    /// Flattens actions already generated with [`available_actions`](Self::available_actions)
+    /// and orders them by action optimiser, so explorer does not generate them twice per node.
+    pub fn order_actions(&self, actions: SuitMap<SmallVec<[CardPack;CONTRACT_ACTION_ESTIMATED_SUIT_MAP_BOUND]>>) -> VCardPack{
+        let mut actions: VCardPack = actions.into_iter().collect();
+        self.action_optimiser.order_actions(self, &mut actions);
+        actions
+    }
+
+    pub(crate) fn record_cutoff(&mut self, card: &Card){
+        let mut optimiser = std::mem::take(&mut self.action_optimiser);
+        optimiser.record_cutoff(self, card);
+        self.action_optimiser = optimiser;
+    }
+
 
 
 }
@@ -310,19 +329,6 @@ impl ProtocolSpecification for DoubleDummyExplorerDomain {
     type UpdateType = ExplorerStateUpdate;
     type AgentId = Side;
 }*/
-/*
-impl<G: ActionOptimiser> State<ContractProtocolSpec> for ExplorerGameState<G>{
-    //type UpdateType = ExplorerStateUpdate;
-    //type Error = BridgeCoreError;
-
-
-
-    fn is_finished(&self) ->bool {
-        self.contract.is_completed()
-    }
-}
-
- */
 /* 
 impl<G: ActionOptimiser> BridgeContractState for ExplorerGameState<G> {
     type HandType = StackHand;
@@ -338,48 +344,6 @@ impl<G: ActionOptimiser> BridgeContractState for ExplorerGameState<G> {
 }
 
 */
-/*
-impl<G: ActionOptimiser> InformationSet<ContractProtocolSpec> for ExplorerGameState<G>{
-    //type ActionType = CardPack;
-    //type Aggregator = SmallVec<[CardPack; CONTRACT_ACTION_SPACE_BOUND]>;
-    type ActionIteratorType = SuitMap<SmallVec<[CardPack;CONTRACT_ACTION_ESTIMATED_SUIT_MAP_BOUND]>>;
-
-    //const  ActionSpaceBound: usize = CONTRACT_ACTION_SPACE_BOUND;
-
-
-
-
-    //type Id = Side;
-
-    fn id(&self) -> &Side {
-        todo!()
-    }
-
-    fn is_action_valid(&self, _action: &CardPack) -> bool {
-        todo!()
-    }
-
-    type RewardType = u32;
-
-    fn current_reward(&self) -> Self::RewardType {
-        //self.contract.total_tricks_taken_axis(self.current_side().)
-        todo!()
-    }
-    /*
-    fn side(&self) -> Side {
-        self.contract.current_side()
-    }
-
-    fn set(&self) -> &Self::HandType {
-        &self.node_stack.last().unwrap().hands()[&self.contract.current_side()]
-    }
-    */
-
-    
-}
-
-
- */
 
 
 #[cfg(test)]
@@ -0,0 +1,221 @@
+use std::cmp::{max, min};
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use brydz_core::contract::Contract;
+use brydz_core::error::{BridgeCoreError, ContractErrorGen};
+use brydz_core::player::axis::Axis;
+use log::debug;
+use crate::actions::ActionOptimiser;
+use crate::error::DoubleDummyError;
+use crate::explore::{ExploreOutput, Explorer, ExplorerStateUpdate};
+use crate::explore::track::TrackStep;
+use crate::hash::NodeStoreTrait;
+use crate::node::TrickNode;
+
+/// Explores position trying root actions starting from `rotation`-th one,
+/// so threads searching the same position enter different subtrees first.
+fn explore_rotated<G: ActionOptimiser, A: NodeStoreTrait>(explorer: &mut Explorer<G, A>, rotation: usize) -> Result<ExploreOutput, DoubleDummyError>{
+    let mut actions = explorer.state().ordered_actions();
+    if actions.len() < 2{
+        return explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity)
+    }
+    let len = actions.len();
+    actions.rotate_left(rotation % len);
+    let maximising = explorer.current_side().axis() == Axis::NorthSouth;
+    let mut value = match maximising{
+        true => ExploreOutput::MinusInfinity,
+        false => ExploreOutput::Infinity
+    };
+    for card_pack in actions{
+        explorer.update(ExplorerStateUpdate::PlaceCard(card_pack.lowest_card()))?;
+        let child = match maximising{
+            true => explorer.explore_actions(value, ExploreOutput::Infinity),
+            false => explorer.explore_actions(ExploreOutput::MinusInfinity, value)
+        }?;
+        explorer.update(ExplorerStateUpdate::Undo)?;
+        value = match maximising{
+            true => max(value, child),
+            false => min(value, child)
+        };
+    }
+    Ok(value)
+}
+
+/// Parallel search of single position (Lazy SMP). Every thread explores the same position with its
+/// own [`Explorer`] and clone of node store `A`, starting from different root action, so action
+/// orders (and killer moves learned by optimiser) diverge. Threads cooperate only by node store,
+/// so `A` should share its table between clones, like [`SharedNodeStore`](crate::hash::SharedNodeStore).
+/// Node store is kept and can be reused for next searches in the same deal.
+/// # Example:
+/// ```
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::actions::HeuristicCardOrderer;
+/// use brydz_dd::explore::{ExploreOutput, LazySmp};
+/// use brydz_dd::hash::SharedNodeStore;
+/// use brydz_dd::hash::hash24::Hash24;
+/// use brydz_dd::node::TrickNode;
+/// let contract = Contract::new(
+///     ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+/// let solver = LazySmp::<HeuristicCardOrderer, SharedNodeStore<Hash24<3>, 0x1000>>::new(4);
+/// assert_eq!(solver.solve(&contract, node), Ok(ExploreOutput::Number(1)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LazySmp<G: ActionOptimiser, A: NodeStoreTrait>{
+    threads: usize,
+    node_store: A,
+    _optimiser: PhantomData<G>,
+}
+
+impl<G, A> LazySmp<G, A>
+where G: ActionOptimiser + Send + Sync, A: NodeStoreTrait + Clone + Send + Sync{
+    pub fn new(threads: usize) -> Self{
+        Self::new_with_node_store(threads, A::default())
+    }
+
+    pub fn new_with_node_store(threads: usize, node_store: A) -> Self{
+        Self{threads: threads.max(1), node_store, _optimiser: PhantomData}
+    }
+
+    /// Solver with thread for every core reported by system.
+    pub fn with_available_parallelism() -> Self{
+        Self::new(thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1))
+    }
+
+    pub fn threads(&self) -> usize{
+        self.threads
+    }
+
+    pub fn node_store(&self) -> &A{
+        &self.node_store
+    }
+
+    fn worker(&self, contract: &Contract, node: TrickNode, stop: &Arc<AtomicBool>) -> Result<Explorer<G, A>, DoubleDummyError>{
+        let mut explorer = Explorer::<G, A>::new_with_node_store(contract.clone(), node, self.node_store.clone())?;
+        explorer.set_stop_flag(stop.clone());
+        Ok(explorer)
+    }
+
+    /// Runs helpers exploring position from different root actions until `stop` is raised.
+    fn spawn_helpers<'scope, 'env>(&'env self, scope: &'scope thread::Scope<'scope, 'env>, contract: &'env Contract, node: TrickNode, stop: &'env Arc<AtomicBool>, first: usize)
+        -> Vec<thread::ScopedJoinHandle<'scope, Result<ExploreOutput, DoubleDummyError>>>{
+        (first..self.threads).map(|worker|{
+            scope.spawn(move ||{
+                let mut explorer = self.worker(contract, node, stop)?;
+                let result = explore_rotated(&mut explorer, worker);
+                if result.is_ok(){
+                    stop.store(true, Ordering::Relaxed);
+                }
+                result
+            })
+        }).collect()
+    }
+
+    /// Finds number of tricks North-South take (counting already completed ones).
+    /// Value of the first thread that finishes is returned and others are stopped.
+    pub fn solve(&self, contract: &Contract, node: TrickNode) -> Result<ExploreOutput, DoubleDummyError>{
+        let stop = Arc::new(AtomicBool::new(false));
+        let results: Vec<Result<ExploreOutput, DoubleDummyError>> = thread::scope(|scope|{
+            self.spawn_helpers(scope, contract, node, &stop, 0).into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        });
+        debug!("Lazy SMP search finished with {} threads.", self.threads);
+        let mut error = None;
+        for result in results{
+            match result{
+                Ok(value) => return Ok(value),
+                Err(DoubleDummyError::Interrupted) => {},
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        Err(error.unwrap_or(DoubleDummyError::Interrupted))
+    }
+
+    /// Values every root action like [`Explorer::hint`]. Main thread computes hint, while helpers
+    /// fill shared node store exploring position from different root actions.
+    /// Finished position has no hint and is reported as full contract before helpers are started.
+    pub fn hint(&self, contract: &Contract, node: TrickNode) -> Result<TrackStep, DoubleDummyError>{
+        if node.flatten_hands() == 0{
+            return Err(BridgeCoreError::from(ContractErrorGen::ContractFull).into())
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        thread::scope(|scope|{
+            let helpers = self.spawn_helpers(scope, contract, node, &stop, 1);
+            let result = Explorer::<G, A>::new_with_node_store(contract.clone(), node, self.node_store.clone())
+                .and_then(|mut explorer| explorer.hint());
+            stop.store(true, Ordering::Relaxed);
+            for helper in helpers{
+                // helper results are only side effect of filling node store
+                let _ = helper.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+            }
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TRUMPS;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::deal::fair_bridge_partial_deal;
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, Jack, King, Queen, F10};
+    use brydz_core::karty::set::{CardSet, CardSetStd};
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::side::SideMap;
+    use brydz_core::player::side::Side::{North, West};
+    use crate::actions::{DistinctCardGrouper, HeuristicCardOrderer};
+    use crate::explore::{ExploreOutput, Explorer, LazySmp};
+    use crate::hash::{DummyNodeStore, NodeStoreTrait, SharedNodeStore};
+    use crate::hash::hash24::Hash24;
+    use crate::node::TrickNode;
+
+    type Store = SharedNodeStore<Hash24<3>, 0x4000>;
+
+    #[test]
+    fn lazy_smp_agrees_with_single_thread(){
+        let card_supply: Vec<Card> = Card::card_subset(vec![Ace, King, Queen, Jack, F10], vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        for strain in TRUMPS{
+            let contract = Contract::new(ContractParametersGen::new(West, Bid::init(strain, 1).unwrap()));
+            let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+            let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract.clone(), node).unwrap();
+            let expected = explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap();
+
+            let solver = LazySmp::<HeuristicCardOrderer, Store>::new(4);
+            assert_eq!(solver.solve(&contract, node), Ok(expected));
+            assert!(solver.node_store().stats().unwrap().stores > 0);
+
+            let hint = solver.hint(&contract, node).unwrap();
+            let hinted = hint.hint().copied().unwrap();
+            let best = hint.results().iter()
+                .find(|result| result.card_pack().lowest_card() == hinted)
+                .map(|result| result.raw_value());
+            assert_eq!(best, Some(expected));
+        }
+    }
+
+    #[test]
+    fn lazy_smp_hint_of_finished_position_is_error(){
+        let contract = Contract::new(ContractParametersGen::new(West, Bid::init(TRUMPS[0], 1).unwrap()));
+        let node = TrickNode::new_checked(SideMap::new_symmetric(CardSetStd::empty()), North).unwrap();
+        let solver = LazySmp::<HeuristicCardOrderer, Store>::new(2);
+        assert!(solver.hint(&contract, node).is_err());
+    }
+}
@@ -6,6 +6,12 @@ pub mod track;
 mod explore_output;
 mod binary_explorer;
 pub use binary_explorer::*;
+mod quick_tricks;
+pub use quick_tricks::*;
+mod lazy_smp;
+pub use lazy_smp::*;
+mod session;
+pub use session::*;
 
 pub use explore_output::*;
 
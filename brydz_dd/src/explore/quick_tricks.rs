@@ -0,0 +1,180 @@
+use std::cmp::{max, min};
+use smallvec::SmallVec;
+use brydz_core::cards::trump::TrumpGen;
+use brydz_core::karty::cards::Card2SymTrait;
+use brydz_core::karty::set::{CardSet, CardSetStd, HandSuitedTrait};
+use brydz_core::karty::suits::{Suit, SuitMap};
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use crate::hash::TrickBounds;
+use crate::node::TrickNode;
+
+/// Owners of remaining cards in every suit, ordered from the highest card.
+type SuitOwners = SuitMap<SmallVec<[Side; 13]>>;
+
+fn suit_owners(hands: &SideMap<CardSetStd>) -> SuitOwners{
+    SuitMap::new_from_f(|suit|{
+        let mut cards: SmallVec<[(usize, Side); 13]> = SIDES.into_iter()
+            .flat_map(|side| hands[&side].suit_iterator(&suit)
+                .map(move |card| (card.figure().usize_index(), side)))
+            .collect();
+        cards.sort_by(|a, b| b.0.cmp(&a.0));
+        cards.into_iter().map(|(_, side)| side).collect()
+    })
+}
+
+/// Number of the highest cards in suit held by `side` without interruption.
+fn top_sequence(owners: &SuitOwners, side: Side, suit: Suit) -> u8{
+    owners[suit].iter().take_while(|owner| **owner == side).count() as u8
+}
+
+/// Number of rounds of `suit` that `side` can cash before opponent holding trump can ruff.
+fn ruff_limit(hands: &SideMap<CardSetStd>, side: Side, suit: Suit, trump: &TrumpGen<Suit>) -> u8{
+    match trump{
+        TrumpGen::Colored(trump_suit) if *trump_suit != suit => [side.next(), side.prev()].into_iter()
+            .filter(|opponent| hands[opponent].contains_in_suit(trump_suit))
+            .map(|opponent| hands[&opponent].suit_iterator(&suit).count() as u8)
+            .min()
+            .unwrap_or(u8::MAX),
+        _ => u8::MAX
+    }
+}
+
+/// Tricks `side` can cash from the top when on lead.
+fn cashing_tricks(hands: &SideMap<CardSetStd>, owners: &SuitOwners, side: Side, trump: &TrumpGen<Suit>) -> u8{
+    Suit::iterator()
+        .map(|suit| min(top_sequence(owners, side, suit), ruff_limit(hands, side, suit, trump)))
+        .sum()
+}
+
+/// Quick tricks of leader's side: tricks cashed by leader or by partner if leader
+/// can cross to partner's hand with winning card.
+fn quick_tricks(hands: &SideMap<CardSetStd>, owners: &SuitOwners, leader: Side, trump: &TrumpGen<Suit>) -> u8{
+    let leader_tricks = cashing_tricks(hands, owners, leader, trump);
+    let partner = leader.partner();
+    let entry = Suit::iterator().any(|suit|
+        hands[&leader].contains_in_suit(&suit)
+            && top_sequence(owners, partner, suit) > 0
+            && ruff_limit(hands, leader, suit, trump) > 0);
+    match entry{
+        true => max(leader_tricks, cashing_tricks(hands, owners, partner, trump)),
+        false => leader_tricks
+    }
+}
+
+/// Later tricks: every card of the top trump sequence wins trick it is played to.
+fn trump_winners(owners: &SuitOwners, trump: &TrumpGen<Suit>) -> Option<(Side, u8)>{
+    match trump{
+        TrumpGen::Colored(trump_suit) => owners[*trump_suit].first()
+            .map(|holder| (*holder, top_sequence(owners, *holder, *trump_suit))),
+        TrumpGen::NoTrump => None
+    }
+}
+
+/// Fast bounds on tricks North-South can take from trick opening in `node` to the end of deal.
+/// Bounds are relative to tricks already taken, like values in node stores.
+/// Lower bound counts quick tricks of side on lead (top cards in suits, cashed until
+/// opponent with trump can ruff, with entry to partner's hand) and tricks of top trumps.
+/// Upper bound is what is left after top trumps of opponents.
+/// # Example:
+/// ```
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::explore::quick_bounds;
+/// use brydz_dd::hash::TrickBounds;
+/// use brydz_dd::node::TrickNode;
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, KING_SPADES, EIGHT_HEARTS],
+///     card_set![QUEEN_SPADES, JACK_SPADES, NINE_HEARTS],
+///     card_set![TEN_HEARTS, NINE_DIAMONDS, EIGHT_DIAMONDS],
+///     card_set![JACK_HEARTS, QUEEN_HEARTS, KING_HEARTS]);
+/// let node = TrickNode::new_checked(hands, North).unwrap();
+/// assert_eq!(quick_bounds(&node, &TrumpGen::NoTrump), TrickBounds::new(2, 3));
+/// assert_eq!(quick_bounds(&node, &TrumpGen::Colored(Hearts)), TrickBounds::exact(0));
+/// ```
+pub fn quick_bounds(node: &TrickNode, trump: &TrumpGen<Suit>) -> TrickBounds{
+    let hands = node.hands();
+    let leader = node.current_side();
+    let remaining = hands[&leader].len() as u8;
+    let owners = suit_owners(hands);
+    let mut leader_side = quick_tricks(hands, &owners, leader, trump);
+    let mut other_side = 0;
+    if let Some((holder, tricks)) = trump_winners(&owners, trump){
+        match holder.axis() == leader.axis(){
+            true => leader_side = max(leader_side, tricks),
+            false => other_side = tricks
+        }
+    }
+    let leader_side = min(leader_side, remaining);
+    let other_side = min(other_side, remaining - leader_side);
+    match leader.axis(){
+        Axis::NorthSouth => TrickBounds::new(leader_side, remaining - other_side),
+        Axis::EastWest => TrickBounds::new(other_side, remaining - leader_side)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::{TrumpGen, TRUMPS};
+    use brydz_core::contract::{Contract, ContractParametersGen};
+    use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::player::side::{SideMap, Side::*, SIDES};
+    use crate::actions::DistinctCardGrouper;
+    use crate::explore::{quick_bounds, ExploreOutput, Explorer};
+    use crate::hash::{DummyNodeStore, TrickBounds};
+    use crate::node::TrickNode;
+
+    #[test]
+    fn entry_to_partner_hand(){
+        let hands = SideMap::new(
+            card_set![TWO_SPADES, THREE_HEARTS],
+            card_set![QUEEN_CLUBS, THREE_SPADES],
+            card_set![ACE_SPADES, KING_SPADES],
+            card_set![QUEEN_SPADES, JACK_SPADES]);
+        let node = TrickNode::new_checked(hands, North).unwrap();
+        assert_eq!(quick_bounds(&node, &TrumpGen::NoTrump), TrickBounds::exact(2));
+        // East ruffs second spade
+        assert_eq!(quick_bounds(&node, &TrumpGen::Colored(Clubs)), TrickBounds::exact(1));
+    }
+
+    #[test]
+    fn quick_bounds_contain_solved_value(){
+        let deals: [SideMap<CardSetStd>; 2] = [
+            SideMap::new(
+                card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+                card_set![ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS],
+                card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+                card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS]),
+            SideMap::new(
+                card_set![ACE_SPADES, KING_SPADES, QUEEN_SPADES, TWO_HEARTS],
+                card_set![ACE_HEARTS, KING_HEARTS, TWO_SPADES, THREE_SPADES],
+                card_set![ACE_CLUBS, KING_CLUBS, ACE_DIAMONDS, THREE_HEARTS],
+                card_set![QUEEN_HEARTS, QUEEN_CLUBS, KING_DIAMONDS, FOUR_SPADES])
+        ];
+        for hands in deals{
+            for trump in TRUMPS{
+                for leader in SIDES{
+                    let contract = Contract::new(
+                        ContractParametersGen::new(leader.prev(), Bid::init(trump, 1).unwrap()));
+                    let node = TrickNode::new_checked(hands, leader).unwrap();
+                    let bounds = quick_bounds(&node, &trump);
+                    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract.clone(), node).unwrap();
+                    let track = explorer.explore_and_track_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap();
+                    let value = match track.leaf_value(){
+                        ExploreOutput::Number(n) => n,
+                        other => panic!("Unexpected explore result {other:?}")
+                    };
+                    assert!(bounds.lower() <= value && value <= bounds.upper(),
+                        "Bounds {bounds:?} do not contain {value} (trump: {trump:?}, leader: {leader:?})");
+                    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node).unwrap();
+                    assert_eq!(explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity),
+                        Ok(ExploreOutput::Number(value)));
+                }
+            }
+        }
+    }
+}
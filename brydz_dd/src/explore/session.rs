@@ -0,0 +1,212 @@
+use brydz_core::amfiteatr::state::{ContractEnvStateComplete, ContractState};
+use brydz_core::contract::{Contract, ContractMechanics};
+use brydz_core::error::{BridgeCoreError, ContractErrorGen};
+use brydz_core::player::axis::Axis;
+use crate::actions::ActionOptimiser;
+use crate::error::DoubleDummyError;
+use crate::explore::{ExploreOutput, Explorer, ExplorerGameState, ExplorerStateUpdate};
+use crate::explore::track::TrackStep;
+use crate::hash::NodeStoreTrait;
+use crate::node::TrickNode;
+
+/// Solver following one game move by move. Unlike fresh [`Explorer`] created for every position,
+/// session keeps its node store, so subtrees solved for previous hints are not explored again.
+/// When trick is completed, entries of positions that can not be reached any more are aged out
+/// of the store (see [`NodeStoreTrait::age`]). Hint of position is remembered until next update.
+/// # Example:
+/// ```
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::actions::HeuristicCardOrderer;
+/// use brydz_dd::explore::{ExplorerStateUpdate, SolverSession};
+/// use brydz_dd::node::TrickNode;
+/// use brydz_dd::solver::DefaultNodeStore;
+/// let contract = Contract::new(
+///     ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+/// let mut session = SolverSession::<HeuristicCardOrderer, DefaultNodeStore>::new_checked(contract, node).unwrap();
+/// assert_eq!(session.north_south_value(), Ok(1));
+/// let card = *session.hint().unwrap().hint().unwrap();
+/// session.update(ExplorerStateUpdate::PlaceCard(card)).unwrap();
+/// assert_eq!(session.north_south_value(), Ok(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SolverSession<G: ActionOptimiser, A: NodeStoreTrait>{
+    explorer: Explorer<G, A>,
+    placed: usize,
+    last_hint: Option<TrackStep>,
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> SolverSession<G, A>{
+    pub fn new_checked(contract: Contract, initial_node: TrickNode) -> Result<Self, DoubleDummyError>{
+        Self::new_with_node_store(contract, initial_node, A::default())
+    }
+
+    /// Session continuing with node store filled earlier in the same deal.
+    pub fn new_with_node_store(contract: Contract, initial_node: TrickNode, node_store: A) -> Result<Self, DoubleDummyError>{
+        Ok(Self{
+            explorer: Explorer::new_with_node_store(contract, initial_node, node_store)?,
+            placed: 0,
+            last_hint: None,
+        })
+    }
+
+    /// Session starting from position in game run by environment, possibly in the middle of trick.
+    pub fn new_from_env_state(state: &ContractEnvStateComplete) -> Result<Self, DoubleDummyError>{
+        let node = TrickNode::from_env_state(state).map_err(BridgeCoreError::from)?;
+        Self::new_checked(state.contract_data().clone(), node)
+    }
+
+    pub fn state(&self) -> &ExplorerGameState<G>{
+        self.explorer.state()
+    }
+
+    pub fn node_store(&self) -> &A{
+        self.explorer.node_store()
+    }
+
+    pub fn into_node_store(self) -> A{
+        self.explorer.into_node_store()
+    }
+
+    fn cards_left(&self) -> u8{
+        self.state().actual_node().flatten_hands().count_ones() as u8
+    }
+
+    fn check_not_finished(&self) -> Result<(), DoubleDummyError>{
+        match self.cards_left(){
+            0 => Err(BridgeCoreError::from(ContractErrorGen::ContractFull).into()),
+            _ => Ok(())
+        }
+    }
+
+    /// Applies move of game. Cards placed before session was created can not be undone.
+    pub fn update(&mut self, update: ExplorerStateUpdate) -> Result<(), DoubleDummyError>{
+        match update{
+            ExplorerStateUpdate::PlaceCard(_) => {
+                self.explorer.update(update)?;
+                self.placed += 1;
+                if self.state().contract().current_trick().is_empty(){
+                    let cards_left = self.cards_left();
+                    self.explorer.node_store_mut().age(cards_left);
+                }
+            },
+            ExplorerStateUpdate::Undo => {
+                if self.placed == 0{
+                    return Err(BridgeCoreError::from(ContractErrorGen::UndoOnEmptyContract).into())
+                }
+                self.explorer.update(update)?;
+                self.placed -= 1;
+            }
+        }
+        self.last_hint = None;
+        Ok(())
+    }
+
+    /// Values card groups of side on move, like [`Explorer::hint`].
+    pub fn hint(&mut self) -> Result<TrackStep, DoubleDummyError>{
+        self.check_not_finished()?;
+        if let Some(hint) = &self.last_hint{
+            return Ok(hint.clone())
+        }
+        let hint = self.explorer.hint()?;
+        self.last_hint = Some(hint.clone());
+        Ok(hint)
+    }
+
+    /// Number of tricks North-South take in the whole deal with optimal play from current position.
+    pub fn north_south_value(&mut self) -> Result<u8, DoubleDummyError>{
+        if self.cards_left() == 0{
+            return Ok(self.state().contract().total_tricks_taken_axis(Axis::NorthSouth) as u8)
+        }
+        match self.explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity)?{
+            ExploreOutput::Number(n) => Ok(n),
+            unbounded => Err(DoubleDummyError::UnboundedValue(unbounded))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::amfiteatr::re_export::env::SequentialGameState;
+    use brydz_core::amfiteatr::state::{ContractAction, ContractEnvStateComplete, ContractState};
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TRUMPS;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::deal::fair_bridge_partial_deal;
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, Jack, King, Queen, F10};
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::side::Side::{East, North, South, West};
+    use crate::actions::{DistinctCardGrouper, HeuristicCardOrderer};
+    use crate::error::DoubleDummyError;
+    use crate::explore::{ExploreOutput, Explorer, ExplorerStateUpdate, SolverSession};
+    use crate::hash::{DummyNodeStore, NodeStoreTrait};
+    use crate::node::TrickNode;
+    use crate::solver::DefaultNodeStore;
+
+    #[test]
+    fn session_follows_game(){
+        let card_supply: Vec<Card> = Card::card_subset(vec![Ace, King, Queen, Jack, F10], vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        for strain in TRUMPS{
+            let contract = Contract::new(ContractParametersGen::new(South, Bid::init(strain, 1).unwrap()));
+            let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+            let mut session = SolverSession::<HeuristicCardOrderer, DefaultNodeStore>::new_checked(contract, node).unwrap();
+            assert!(matches!(session.update(ExplorerStateUpdate::Undo), Err(DoubleDummyError::Core(_))));
+            for _ in 0..20{
+                let state = session.state();
+                let mut fresh = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(
+                    state.contract().clone(), *state.actual_node()).unwrap();
+                let expected = fresh.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap();
+
+                let hint = session.hint().unwrap();
+                let card = *hint.hint().unwrap();
+                let best = hint.results().iter()
+                    .find(|result| result.card_pack().lowest_card() == card)
+                    .map(|result| result.raw_value());
+                assert_eq!(best, Some(expected));
+                assert_eq!(session.hint().unwrap().hint(), Some(&card));
+                session.update(ExplorerStateUpdate::PlaceCard(card)).unwrap();
+                assert_eq!(ExploreOutput::Number(session.north_south_value().unwrap()), expected);
+            }
+            assert!(session.hint().is_err());
+            assert!(session.node_store().stats().unwrap().hits > 0);
+            session.update(ExplorerStateUpdate::Undo).unwrap();
+            assert_eq!(session.state().contract().count_completed_tricks(), 4);
+        }
+    }
+
+    #[test]
+    fn positions_inside_trick_are_solved(){
+        let card_supply: Vec<Card> = Card::card_subset(vec![Ace, King, Queen, Jack, F10], vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        for strain in TRUMPS{
+            let contract = Contract::new(ContractParametersGen::new(South, Bid::init(strain, 1).unwrap()));
+            let mut env = ContractEnvStateComplete::new(contract, hands[&South], hands[&West], hands[&North], hands[&East]);
+            let mut session = SolverSession::<HeuristicCardOrderer, DefaultNodeStore>::new_from_env_state(&env).unwrap();
+            let total = session.north_south_value().unwrap();
+            while !env.contract_data().is_completed(){
+                let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_from_env_state(&env).unwrap();
+                assert_eq!(explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity), Ok(ExploreOutput::Number(total)));
+                let mut fresh = SolverSession::<HeuristicCardOrderer, DefaultNodeStore>::new_from_env_state(&env).unwrap();
+                assert_eq!(fresh.north_south_value(), Ok(total));
+
+                let card = *session.hint().unwrap().hint().unwrap();
+                let side = env.contract_data().current_side();
+                env.forward(side, ContractAction::PlaceCard(card)).unwrap();
+                session.update(ExplorerStateUpdate::PlaceCard(card)).unwrap();
+                assert_eq!(session.north_south_value(), Ok(total));
+            }
+        }
+    }
+}
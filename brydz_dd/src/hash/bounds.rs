@@ -0,0 +1,70 @@
+use std::cmp::{max, min};
+
+/// Bounds of North-South tricks still to be taken from node, as found in alpha-beta window.
+/// Lower and upper bound are equal for exact value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TrickBounds{
+    lower: u8,
+    upper: u8
+}
+
+impl TrickBounds{
+    /// Creates bounds, swapping them if given in wrong order.
+    pub fn new(lower: u8, upper: u8) -> Self{
+        Self{lower: min(lower, upper), upper: max(lower, upper)}
+    }
+    pub fn exact(value: u8) -> Self{
+        Self{lower: value, upper: value}
+    }
+    pub fn lower(&self) -> u8{
+        self.lower
+    }
+    pub fn upper(&self) -> u8{
+        self.upper
+    }
+    pub fn exact_value(&self) -> Option<u8>{
+        match self.lower == self.upper{
+            true => Some(self.lower),
+            false => None
+        }
+    }
+    /// Narrows bounds with other bounds of the same node.
+    /// If they are disjoint (e.g. entry was overwritten by other node), `other` is kept.
+    /// ```
+    /// use brydz_dd::hash::TrickBounds;
+    /// assert_eq!(TrickBounds::new(2, 8).intersect(&TrickBounds::new(4, 10)), TrickBounds::new(4, 8));
+    /// assert_eq!(TrickBounds::new(2, 3).intersect(&TrickBounds::new(5, 10)), TrickBounds::new(5, 10));
+    /// ```
+    pub fn intersect(&self, other: &Self) -> Self{
+        let lower = max(self.lower, other.lower);
+        let upper = min(self.upper, other.upper);
+        match lower <= upper{
+            true => Self{lower, upper},
+            false => *other
+        }
+    }
+    /// Packs bounds into one byte, four bits each.
+    pub fn pack(&self) -> u8{
+        (self.upper << 4) | (self.lower & 0x0f)
+    }
+    pub fn unpack(packed: u8) -> Self{
+        Self{lower: packed & 0x0f, upper: packed >> 4}
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use crate::hash::TrickBounds;
+
+    #[test]
+    fn bounds_pack_round_trip(){
+        for lower in 0..=13{
+            for upper in lower..=13{
+                let bounds = TrickBounds::new(lower, upper);
+                assert_eq!(TrickBounds::unpack(bounds.pack()), bounds);
+            }
+        }
+        assert_eq!(TrickBounds::exact(7).exact_value(), Some(7));
+        assert_eq!(TrickBounds::new(7, 8).exact_value(), None);
+    }
+}
@@ -3,12 +3,19 @@ use crate::hash::Label;
 #[derive(Clone, Copy, Debug )]
 pub struct HashEntry<L: Label>{
     label: L,
-    value: u8
+    value: u8,
+    depth: u8,
+    tag: u16
 }
 
 impl <L: Label> HashEntry<L>{
     pub fn new(label: L, value: u8) -> Self{
-        Self{label, value}
+        Self{label, value, depth: 0, tag: 0}
+    }
+    /// Creates entry remembering number of cards left in node (`depth`) and `tag` made of hash
+    /// bits not used to select line, when table has fewer lines than hash values.
+    pub fn new_tagged(label: L, tag: u16, value: u8, depth: u8) -> Self{
+        Self{label, value, depth, tag}
     }
     pub fn label(&self) -> &L{
         &self.label
@@ -16,6 +23,15 @@ impl <L: Label> HashEntry<L>{
     pub fn value(&self) -> u8{
         self.value
     }
+    pub fn depth(&self) -> u8{
+        self.depth
+    }
+    pub fn tag(&self) -> u16{
+        self.tag
+    }
+    pub fn matches(&self, label: &L, tag: u16) -> bool{
+        &self.label == label && self.tag == tag
+    }
 }
 
 
@@ -29,4 +45,4 @@ mod tests{
     fn hash_entry_standard_size(){
         assert_eq!(size_of::<HashEntry<u32>>(), 8);
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,4 @@
-use crate::hash::NodeStoreTrait;
+use crate::hash::{NodeStoreTrait, TrickBounds};
 use crate::node::TrickNode;
 
 #[derive(Copy, Clone, Default, Debug)]
@@ -9,11 +9,11 @@ pub struct DummyNodeStore{
 
 
 impl NodeStoreTrait for DummyNodeStore{
-    fn get_value(&self, _node: &TrickNode) -> Option<u8> {
+    fn get_bounds(&self, _node: &TrickNode) -> Option<TrickBounds> {
         None
     }
 
-    fn store_value(&mut self, _node: &TrickNode, _value: u8) {
+    fn store_bounds(&mut self, _node: &TrickNode, _bounds: TrickBounds) {
         
     }
 }
\ No newline at end of file
@@ -1,21 +1,47 @@
+use std::cell::Cell;
 use std::marker::PhantomData;
-use log::warn;
-use brydz_core::player::side::{SideMap};
+use log::{debug, warn};
+use brydz_core::player::side::{SideMap, SIDES};
 use crate::error::HashError;
-use crate::hash::{HashEntry, Label, NodeHasher, NodeStoreTrait};
+use crate::hash::{HashEntry, Label, NodeHasher, NodeStoreStats, NodeStoreTrait, TrickBounds};
 use crate::hash::ranker::HashRanker;
 use crate::node::TrickNode;
 
+/// Decides which entry leaves full hash line when new one is stored.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ReplacementPolicy{
+    /// Entry with fewest cards left is replaced, unless new entry has even fewer.
+    DepthPreferred,
+    /// Entries are replaced in round robin order.
+    AlwaysReplace,
+    /// Last entry of line is always replaced, the rest keeps positions with most cards left.
+    /// Entry pushed out of the deep tier is moved to the last one.
+    #[default]
+    TwoTier,
+    /// Entry assessed lowest by [`HashRanker`] is replaced, unless new entry is assessed lower.
+    Ranked,
+}
+
+/// Result of storing entry in [`HashLine`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StoreOutcome{
+    Inserted,
+    Updated,
+    Evicted,
+    Rejected
+}
+
 
 #[derive(Copy, Clone)]
 pub struct HashLine<L: Label, const ENTRIES: usize>{
     entries: [HashEntry<L>; ENTRIES],
-    sizes: u8
+    sizes: u8,
+    next_victim: u8,
 
 }
 impl<L: Label, const ENTRIES: usize> HashLine<L, ENTRIES>{
     pub fn init() -> Self{
-        Self{ entries: [HashEntry::new(L::unused(), 0); ENTRIES], sizes: 0 }
+        Self{ entries: [HashEntry::new(L::unused(), 0); ENTRIES], sizes: 0, next_victim: 0 }
     }
     pub fn entries_for_side(&self) -> &[HashEntry<L>]{
         &self.entries[..self.sizes as usize]
@@ -33,42 +59,160 @@ impl<L: Label, const ENTRIES: usize> HashLine<L, ENTRIES>{
             Err(HashError::HashTableFull)
         }
     }
-    pub fn get_value(&self,  label:&L) -> Option<u8>{
-        for entry in &self.entries{
-            if entry.label() == label{
-                return Some(entry.value())
+    /// Keeps only entries accepted by `keep`, returns number of removed entries.
+    pub fn retain<F: Fn(&HashEntry<L>) -> bool>(&mut self, keep: F) -> u8{
+        let size = self.sizes as usize;
+        let mut kept = 0;
+        for i in 0..size{
+            if keep(&self.entries[i]){
+                self.entries[kept] = self.entries[i];
+                kept += 1;
+            }
+        }
+        self.sizes = kept as u8;
+        self.next_victim = 0;
+        (size - kept) as u8
+    }
+    pub fn get_value(&self,  label:&L, tag: u16) -> Option<u8>{
+        self.entries_for_side().iter()
+            .find(|entry| entry.matches(label, tag))
+            .map(|entry| entry.value())
+    }
+
+    fn lowest_index<F: Fn(&HashEntry<L>) -> i64>(entries: &[HashEntry<L>], assess: F) -> usize{
+        entries.iter().enumerate()
+            .min_by_key(|(_, entry)| assess(entry))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// Stores entry, updating value if the same node is already present.
+    /// When line is full one of entries is replaced according to `policy`,
+    /// `rank` is used only by [`ReplacementPolicy::Ranked`].
+    pub fn store<F: Fn(&HashEntry<L>) -> i64>(&mut self, entry: HashEntry<L>, policy: ReplacementPolicy, rank: F) -> StoreOutcome{
+        let size = self.sizes as usize;
+        if let Some(present) = self.entries[..size].iter_mut().find(|e| e.matches(entry.label(), entry.tag())){
+            *present = entry;
+            return StoreOutcome::Updated
+        }
+        if self.try_insert(entry).is_ok(){
+            return StoreOutcome::Inserted
+        }
+        if ENTRIES == 0{
+            return StoreOutcome::Rejected
+        }
+        match policy{
+            ReplacementPolicy::AlwaysReplace => {
+                let victim = self.next_victim as usize % ENTRIES;
+                self.entries[victim] = entry;
+                self.next_victim = ((victim + 1) % ENTRIES) as u8;
+                StoreOutcome::Evicted
+            }
+            ReplacementPolicy::DepthPreferred => {
+                let victim = Self::lowest_index(&self.entries, |e| e.depth() as i64);
+                match entry.depth() >= self.entries[victim].depth(){
+                    true => {
+                        self.entries[victim] = entry;
+                        StoreOutcome::Evicted
+                    }
+                    false => StoreOutcome::Rejected
+                }
+            }
+            ReplacementPolicy::Ranked => {
+                let victim = Self::lowest_index(&self.entries, &rank);
+                match rank(&entry) >= rank(&self.entries[victim]){
+                    true => {
+                        self.entries[victim] = entry;
+                        StoreOutcome::Evicted
+                    }
+                    false => StoreOutcome::Rejected
+                }
+            }
+            ReplacementPolicy::TwoTier => {
+                let always = ENTRIES - 1;
+                let victim = Self::lowest_index(&self.entries[..always], |e| e.depth() as i64);
+                if always > 0 && entry.depth() >= self.entries[victim].depth(){
+                    self.entries[always] = self.entries[victim];
+                    self.entries[victim] = entry;
+                } else {
+                    self.entries[always] = entry;
+                }
+                StoreOutcome::Evicted
             }
         }
-        None
     }
 
 
 }
 
 
+/// Transposition table of fixed number of lines, each line holds up to `ENTRIES` entries for
+/// every side. `LINES` is number of lines created by [`Default`], table of other size can be
+/// created with [`HashArrayNodeStore::new`]. When there are fewer lines than hash values,
+/// remaining hash bits are kept in entry tag, so there are at least
+/// [`min_lines`](HashArrayNodeStore::min_lines) lines (256 for `Hash24`).
 pub struct HashArrayNodeStore<H: NodeHasher, HR: HashRanker<H>, const LINES: usize, const ENTRIES: usize>{
     hasher: PhantomData<H>,
     hash_ranker: PhantomData<HR>,
     array: Vec<SideMap<HashLine<H::LabelType, ENTRIES>>>,
-    //fill_measure: SideMap<[u8;ENTRIES]>,
-   // hit_counter: u64,
+    policy: ReplacementPolicy,
+    stats: Cell<NodeStoreStats>,
 
 }
 impl<H: NodeHasher, HR: HashRanker<H>, const LINES: usize, const ENTRIES: usize> HashArrayNodeStore<H, HR, LINES, ENTRIES>{
     pub fn init() -> Self{
-        let mut v = Vec::with_capacity(LINES);
-        for _ in 0..LINES{
+        Self::new(LINES, ReplacementPolicy::default())
+    }
+
+    /// Smallest number of lines for which every hash of `H` fits table (with 16-bit tag).
+    pub fn min_lines() -> usize{
+        H::HASH_RANGE.div_ceil(u16::MAX as usize + 1).max(1)
+    }
+
+    /// Creates table with `lines` lines, raised to [`min_lines`](Self::min_lines) when smaller,
+    /// because nodes with hash not fitting table could never be stored.
+    pub fn new(lines: usize, policy: ReplacementPolicy) -> Self{
+        let min_lines = Self::min_lines();
+        if lines < min_lines{
+            warn!("Hash table of {lines} lines can not hold every hash, using {min_lines} lines.");
+        }
+        let lines = lines.max(min_lines);
+        let mut v = Vec::with_capacity(lines);
+        for _ in 0..lines{
             v.push(SideMap::new_symmetric( HashLine::init()));
-            //v.push(INITIAL_HASH_LINE);
         }
         Self{
             hasher: PhantomData{},
             hash_ranker: PhantomData{},
             array: v,
-            /*fill_measure: SideMap::new_symmetric([0;ENTRIES]),
-            hit_counter: 0,*/
+            policy,
+            stats: Cell::new(NodeStoreStats::default()),
         }
+    }
+
+    pub fn lines(&self) -> usize{
+        self.array.len()
+    }
+
+    pub fn policy(&self) -> ReplacementPolicy{
+        self.policy
+    }
+
+    pub fn reset_stats(&mut self){
+        self.stats.set(NodeStoreStats::default())
+    }
+
+    fn update_stats<F: FnOnce(&mut NodeStoreStats)>(&self, f: F){
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+    }
 
+    /// Splits hash into line index and tag.
+    fn position(&self, hash: H::HashType) -> Option<(usize, u16)>{
+        let hash: usize = hash.try_into().ok()?;
+        let lines = self.array.len();
+        Some((hash % lines, u16::try_from(hash / lines).ok()?))
     }
 }
 
@@ -79,53 +223,84 @@ impl<H: NodeHasher, HR: HashRanker<H>, const LINES: usize, const ENTRIES: usize>
 }
 
 impl<H: NodeHasher, HR: HashRanker<H>, const LINES: usize, const ENTRIES: usize>  NodeStoreTrait for HashArrayNodeStore<H, HR,  LINES, ENTRIES>{
-    fn get_value(&self, node: &TrickNode) -> Option<u8> {
+    fn get_bounds(&self, node: &TrickNode) -> Option<TrickBounds> {
         let (hash, label) = H::hash_and_label(node);
-         let line = match hash.try_into(){
-             Ok(index) => self.array[index],
-             Err(_) => {return None}
-         }[&node.current_side()];
-
-        /*line.get_value(&label).map(|v| {
-            self.hit_counter +=1;
-            v
-        })
-
-         */
-        line.get_value(&label)
-
-
-
+        let bounds = self.position(hash)
+            .and_then(|(index, tag)| self.array[index][&node.current_side()].get_value(&label, tag))
+            .map(TrickBounds::unpack);
+        self.update_stats(|stats| match bounds{
+            Some(_) => stats.hits += 1,
+            None => stats.misses += 1
+        });
+        bounds
+    }
 
+    fn store_bounds(&mut self, node: &TrickNode, bounds: TrickBounds) {
+        let (hash, label) = H::hash_and_label(node);
+        let (index, tag) = match self.position(hash){
+            Some(position) => position,
+            None => {
+                debug!("Hash of node {node:?} does not fit table of {} lines.", self.lines());
+                self.update_stats(|stats| stats.rejections += 1);
+                return;
+            }
+        };
+        let lines = self.array.len();
+        let depth = node.flatten_hands().count_ones() as u8;
+        let line = &mut self.array[index][&node.current_side()];
+        let full = line.number_of_entries_for_side() as usize >= ENTRIES;
+        let bounds = match line.get_value(&label, tag){
+            Some(known) => TrickBounds::unpack(known).intersect(&bounds),
+            None => bounds
+        };
+        let outcome = line.store(HashEntry::new_tagged(label, tag, bounds.pack(), depth), self.policy, |entry|{
+            match H::HashType::try_from(entry.tag() as usize * lines + index){
+                Ok(entry_hash) => HR::assess(&entry_hash, entry.label()),
+                Err(_) => i64::MIN
+            }
+        });
+        self.update_stats(|stats|{
+            stats.stores += 1;
+            if full && outcome != StoreOutcome::Updated{
+                stats.collisions += 1;
+            }
+            match outcome{
+                StoreOutcome::Evicted => stats.evictions += 1,
+                StoreOutcome::Rejected => stats.rejections += 1,
+                StoreOutcome::Inserted | StoreOutcome::Updated => {}
+            }
+        });
+    }
 
+    fn stats(&self) -> Option<NodeStoreStats> {
+        Some(self.stats.get())
     }
 
-    fn store_value(&mut self, node: &TrickNode, value: u8) {
-        let (hash, label) = H::hash_and_label(node);
-        let mut line = match hash.try_into(){
-             Ok(index) => self.array[index],
-             Err(_) => {
-                 panic!("Error creating usize from hash for node {node:?}.");
-             }
-        }[&node.current_side()];
-
-        let hash_entry = HashEntry::new(label, value);
-        match line.try_insert(hash_entry){
-            Ok(_) => {}
-            Err(_) => {
-                warn!("Trying to store in full hash bucket. Bucket optimisation is not yet implemented")
+    fn age(&mut self, cards_left: u8) {
+        let mut removed = 0u64;
+        for lines in self.array.iter_mut(){
+            for side in SIDES{
+                removed += lines[&side].retain(|entry| entry.depth() <= cards_left) as u64;
             }
         }
+        self.update_stats(|stats| stats.evictions += removed);
     }
 }
 
 
 #[cfg(test)]
 mod tests{
+    use brydz_core::deal::fair_bridge_partial_deal;
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, King};
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::side::Side::{East, North};
     use brydz_core::player::side::SideMap;
-    use crate::hash::{HashArrayNodeStore, HashEntry, HashLine};
+    use crate::hash::{HashArrayNodeStore, HashEntry, HashLine, NodeStoreTrait, ReplacementPolicy, StoreOutcome, TrickBounds};
     use crate::hash::hash24::Hash24;
     use crate::hash::ranker::MoreCardsRanker;
+    use crate::node::TrickNode;
 
     #[test]
     fn assert_option_hash_entry_array_size(){
@@ -144,7 +319,7 @@ mod tests{
 
     #[test]
     fn assert_hash_array_size(){
-        assert_eq!(std::mem::size_of::<HashArrayNodeStore<Hash24<3>, MoreCardsRanker, 0x1000000, 8>>(), 24)
+        assert_eq!(std::mem::size_of::<HashArrayNodeStore<Hash24<3>, MoreCardsRanker, 0x1000000, 8>>(), 80)
         //4362076200
     }
     #[test]
@@ -153,7 +328,97 @@ mod tests{
         //4362076200
     }
 
+    fn full_line(policy: ReplacementPolicy) -> HashLine<u32, 2>{
+        let mut line = HashLine::<u32, 2>::init();
+        assert_eq!(line.store(HashEntry::new_tagged(1, 0, 1, 8), policy, |e| e.depth() as i64), StoreOutcome::Inserted);
+        assert_eq!(line.store(HashEntry::new_tagged(2, 0, 2, 4), policy, |e| e.depth() as i64), StoreOutcome::Inserted);
+        line
+    }
 
-}
+    #[test]
+    fn hash_line_replacement_policies(){
+        let mut line = full_line(ReplacementPolicy::DepthPreferred);
+        assert_eq!(line.store(HashEntry::new_tagged(3, 0, 3, 2), ReplacementPolicy::DepthPreferred, |_| 0), StoreOutcome::Rejected);
+        assert_eq!(line.store(HashEntry::new_tagged(3, 0, 3, 6), ReplacementPolicy::DepthPreferred, |_| 0), StoreOutcome::Evicted);
+        assert_eq!((line.get_value(&1, 0), line.get_value(&2, 0), line.get_value(&3, 0)), (Some(1), None, Some(3)));
 
-//const INITIAL_HASH_LINE: HashLine<u32, 8> = HashLine::<u32, 8>::init();
\ No newline at end of file
+        let mut line = full_line(ReplacementPolicy::AlwaysReplace);
+        assert_eq!(line.store(HashEntry::new_tagged(3, 0, 3, 2), ReplacementPolicy::AlwaysReplace, |_| 0), StoreOutcome::Evicted);
+        assert_eq!((line.get_value(&1, 0), line.get_value(&3, 0)), (None, Some(3)));
+
+        let mut line = full_line(ReplacementPolicy::TwoTier);
+        // shallow entry goes to always replaced slot
+        assert_eq!(line.store(HashEntry::new_tagged(3, 0, 3, 2), ReplacementPolicy::TwoTier, |_| 0), StoreOutcome::Evicted);
+        assert_eq!((line.get_value(&1, 0), line.get_value(&2, 0), line.get_value(&3, 0)), (Some(1), None, Some(3)));
+        // deeper entry takes deep slot and pushes previous one to the other
+        assert_eq!(line.store(HashEntry::new_tagged(4, 0, 4, 10), ReplacementPolicy::TwoTier, |_| 0), StoreOutcome::Evicted);
+        assert_eq!((line.get_value(&1, 0), line.get_value(&3, 0), line.get_value(&4, 0)), (Some(1), None, Some(4)));
+
+        let mut line = full_line(ReplacementPolicy::Ranked);
+        assert_eq!(line.store(HashEntry::new_tagged(3, 0, 3, 0), ReplacementPolicy::Ranked, |e| *e.label() as i64), StoreOutcome::Evicted);
+        assert_eq!((line.get_value(&1, 0), line.get_value(&3, 0)), (None, Some(3)));
+        assert_eq!(line.store(HashEntry::new_tagged(3, 1, 5, 0), ReplacementPolicy::Ranked, |_| 0), StoreOutcome::Evicted);
+        assert_eq!(line.store(HashEntry::new_tagged(3, 1, 7, 0), ReplacementPolicy::Ranked, |_| 0), StoreOutcome::Updated);
+        assert_eq!(line.get_value(&3, 1), Some(7));
+    }
+
+    #[test]
+    fn small_hash_array_counts_usage(){
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let node = TrickNode::new_checked(fair_bridge_partial_deal::<CardSetStd>(card_supply, North), North).unwrap();
+        let mut store = HashArrayNodeStore::<Hash24<3>, MoreCardsRanker, 0x1000000, 2>::new(256, ReplacementPolicy::TwoTier);
+        assert_eq!(store.lines(), 256);
+        assert_eq!(store.get_value(&node), None);
+        store.store_value(&node, 1);
+        assert_eq!(store.get_value(&node), Some(1));
+        let stats = store.stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.stores, stats.collisions), (1, 1, 1, 0));
+        // bounds found in separate searches are narrowed to exact value
+        let east_node = TrickNode::new_checked(*node.hands(), East).unwrap();
+        store.store_bounds(&east_node, TrickBounds::new(0, 1));
+        assert_eq!(store.get_value(&east_node), None);
+        store.store_bounds(&east_node, TrickBounds::new(1, 2));
+        assert_eq!(store.get_value(&east_node), Some(1));
+        store.reset_stats();
+        assert_eq!(store.stats().unwrap().hits, 0);
+    }
+
+    #[test]
+    fn too_small_table_is_enlarged(){
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let node = TrickNode::new_checked(fair_bridge_partial_deal::<CardSetStd>(card_supply, North), North).unwrap();
+        type Store = HashArrayNodeStore<Hash24<3>, MoreCardsRanker, 0x1000000, 2>;
+        assert_eq!(Store::min_lines(), 256);
+        let mut store = Store::new(16, ReplacementPolicy::TwoTier);
+        assert_eq!(store.lines(), 256);
+        store.store_value(&node, 1);
+        assert_eq!(store.get_value(&node), Some(1));
+        assert_eq!(store.stats().unwrap().rejections, 0);
+    }
+
+    #[test]
+    fn aging_drops_unreachable_entries(){
+        let mut line = full_line(ReplacementPolicy::TwoTier);
+        assert_eq!(line.retain(|entry| entry.depth() <= 4), 1);
+        assert_eq!((line.get_value(&1, 0), line.get_value(&2, 0)), (None, Some(2)));
+        assert_eq!(line.store(HashEntry::new_tagged(3, 0, 3, 2), ReplacementPolicy::TwoTier, |_| 0), StoreOutcome::Inserted);
+
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let node = TrickNode::new_checked(fair_bridge_partial_deal::<CardSetStd>(card_supply, North), North).unwrap();
+        let mut store = HashArrayNodeStore::<Hash24<3>, MoreCardsRanker, 0x1000000, 2>::new(256, ReplacementPolicy::TwoTier);
+        store.store_value(&node, 1);
+        store.age(8);
+        assert_eq!(store.get_value(&node), Some(1));
+        store.age(4);
+        assert_eq!(store.get_value(&node), None);
+        assert_eq!(store.stats().unwrap().evictions, 1);
+    }
+
+
+}
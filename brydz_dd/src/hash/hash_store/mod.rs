@@ -1,11 +1,66 @@
 mod dummy_store;
 mod hash_array_store;
+mod shared_store;
+mod verified_store;
 
 pub use dummy_store::*;
 pub use hash_array_store::*;
+pub use shared_store::*;
+pub use verified_store::*;
+use crate::hash::TrickBounds;
 use crate::node::TrickNode;
 
+/// Counters of node store usage.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeStoreStats{
+    pub hits: u64,
+    pub misses: u64,
+    pub stores: u64,
+    /// Stores to bucket already filled with other entries
+    pub collisions: u64,
+    /// Entries removed to make place for new ones
+    pub evictions: u64,
+    /// New entries dropped by replacement policy
+    pub rejections: u64,
+    /// Hits returning bounds of other node, counted only by [`VerifiedNodeStore`]
+    pub false_hits: u64,
+}
+
+impl NodeStoreStats{
+    pub fn hit_rate(&self) -> f64{
+        match self.hits + self.misses{
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64
+        }
+    }
+
+    /// Part of hits that were hash collisions (see [`VerifiedNodeStore`]).
+    pub fn false_hit_rate(&self) -> f64{
+        match self.hits{
+            0 => 0.0,
+            hits => self.false_hits as f64 / hits as f64
+        }
+    }
+}
+
+/// Storage of North-South tricks to be taken from nodes opening tricks.
+/// Values are relative to tricks already taken when node is reached.
 pub trait NodeStoreTrait: Default{
-    fn get_value(&self, node: &TrickNode) -> Option<u8>;
-    fn store_value(&mut self, node: &TrickNode, value: u8);
-}
\ No newline at end of file
+    fn get_bounds(&self, node: &TrickNode) -> Option<TrickBounds>;
+    /// Stores bounds, narrowing ones already known for the node.
+    fn store_bounds(&mut self, node: &TrickNode, bounds: TrickBounds);
+    fn get_value(&self, node: &TrickNode) -> Option<u8>{
+        self.get_bounds(node).and_then(|bounds| bounds.exact_value())
+    }
+    fn store_value(&mut self, node: &TrickNode, value: u8){
+        self.store_bounds(node, TrickBounds::exact(value))
+    }
+    /// Usage counters, if store collects them.
+    fn stats(&self) -> Option<NodeStoreStats>{
+        None
+    }
+    /// Called when game moved forward and only `cards_left` cards remain in all hands.
+    /// Nodes with more cards can not be reached any more, so their entries may be dropped
+    /// to make place for new ones.
+    fn age(&mut self, _cards_left: u8){}
+}
@@ -0,0 +1,256 @@
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use brydz_core::karty::set::CardSet;
+use crate::hash::{NodeHasher, NodeStoreStats, NodeStoreTrait, TrickBounds};
+use crate::node::TrickNode;
+
+const SLOTS_IN_BUCKET: usize = 2;
+const VALID_FLAG: u64 = 1 << 16;
+
+/// Entry of lockless table. Verification key is kept xor-ed with data, so entry torn by
+/// concurrent writes fails verification instead of returning bounds of other node.
+#[derive(Debug, Default)]
+struct SharedSlot{
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+impl SharedSlot{
+    fn load(&self, key: u64) -> Option<u64>{
+        let data = self.data.load(Ordering::Relaxed);
+        let check = self.check.load(Ordering::Relaxed);
+        (data & VALID_FLAG != 0 && check ^ data == key).then_some(data)
+    }
+
+    fn save(&self, key: u64, data: u64){
+        self.check.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+
+    /// Number of cards in hands of node stored in slot, `None` for empty slot.
+    fn cards(&self) -> Option<u8>{
+        let data = self.data.load(Ordering::Relaxed);
+        (data & VALID_FLAG != 0).then_some((data >> 8) as u8)
+    }
+
+    fn clear(&self){
+        self.data.store(0, Ordering::Relaxed);
+        self.check.store(0, Ordering::Relaxed);
+    }
+}
+
+fn encode(bounds: TrickBounds, cards: u8) -> u64{
+    bounds.pack() as u64 | (cards as u64) << 8 | VALID_FLAG
+}
+
+#[derive(Default)]
+struct SharedCounters{
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+    evictions: AtomicU64,
+}
+
+struct SharedTable{
+    slots: Vec<SharedSlot>,
+    lines: usize,
+    counters: SharedCounters,
+}
+
+/// Node store that can be used by many explorers working in parallel on the same deal.
+/// Clones of store share one table, which is accessed without locks: every bucket (line and side
+/// on lead) has one slot preferring nodes with more cards and one always replaced slot.
+/// Entries are verified with complete set of remaining cards, so hash collisions and torn writes
+/// are detected. Store is meant for one deal at a time.
+/// # Example:
+/// ```
+/// use brydz_core::karty::{card_set, cards::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::hash::{NodeStoreTrait, SharedNodeStore, TrickBounds};
+/// use brydz_dd::hash::hash24::Hash24;
+/// use brydz_dd::node::TrickNode;
+/// let hands = SideMap::new(card_set![ACE_SPADES], card_set![KING_SPADES],
+///     card_set![QUEEN_SPADES], card_set![JACK_SPADES]);
+/// let node = TrickNode::new_checked(hands, North).unwrap();
+/// let mut store = SharedNodeStore::<Hash24<3>, 0x100>::default();
+/// let view = store.clone();
+/// store.store_bounds(&node, TrickBounds::exact(1));
+/// assert_eq!(view.get_value(&node), Some(1));
+/// ```
+pub struct SharedNodeStore<H: NodeHasher, const LINES: usize>{
+    hasher: PhantomData<H>,
+    table: Arc<SharedTable>,
+}
+
+impl<H: NodeHasher, const LINES: usize> SharedNodeStore<H, LINES>{
+    pub fn new(lines: usize) -> Self{
+        let lines = lines.max(1);
+        let mut slots = Vec::with_capacity(lines * 4 * SLOTS_IN_BUCKET);
+        slots.resize_with(lines * 4 * SLOTS_IN_BUCKET, SharedSlot::default);
+        Self{
+            hasher: PhantomData,
+            table: Arc::new(SharedTable{slots, lines, counters: SharedCounters::default()})
+        }
+    }
+
+    pub fn lines(&self) -> usize{
+        self.table.lines
+    }
+
+    /// Number of stores sharing this table.
+    pub fn handles(&self) -> usize{
+        Arc::strong_count(&self.table)
+    }
+
+    fn bucket(&self, node: &TrickNode) -> Option<&[SharedSlot]>{
+        let hash: usize = H::hash(node).try_into().ok()?;
+        let first = ((hash % self.table.lines) * 4 + node.current_side().index() as usize) * SLOTS_IN_BUCKET;
+        self.table.slots.get(first..first + SLOTS_IN_BUCKET)
+    }
+}
+
+impl<H: NodeHasher, const LINES: usize> Clone for SharedNodeStore<H, LINES>{
+    fn clone(&self) -> Self {
+        Self{hasher: PhantomData, table: self.table.clone()}
+    }
+}
+
+impl<H: NodeHasher, const LINES: usize> Default for SharedNodeStore<H, LINES>{
+    fn default() -> Self {
+        Self::new(LINES)
+    }
+}
+
+impl<H: NodeHasher, const LINES: usize> Debug for SharedNodeStore<H, LINES>{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedNodeStore")
+            .field("lines", &self.table.lines)
+            .field("handles", &self.handles())
+            .finish()
+    }
+}
+
+impl<H: NodeHasher, const LINES: usize> NodeStoreTrait for SharedNodeStore<H, LINES>{
+    fn get_bounds(&self, node: &TrickNode) -> Option<TrickBounds> {
+        let key = node.flatten_hands();
+        let found = self.bucket(node)
+            .and_then(|bucket| bucket.iter().find_map(|slot| slot.load(key)))
+            .map(|data| TrickBounds::unpack(data as u8));
+        let counter = match found{
+            Some(_) => &self.table.counters.hits,
+            None => &self.table.counters.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    fn store_bounds(&mut self, node: &TrickNode, bounds: TrickBounds) {
+        let key = node.flatten_hands();
+        let cards = node.hands()[&node.current_side()].len() as u8;
+        if let Some(bucket) = self.bucket(node){
+            // known bounds are read from bucket directly, not to count lookup in stats
+            let bounds = match bucket.iter().find_map(|slot| slot.load(key)){
+                Some(known) => TrickBounds::unpack(known as u8).intersect(&bounds),
+                None => bounds
+            };
+            let data = encode(bounds, cards);
+            let preferred = &bucket[0];
+            let slot = match preferred.cards(){
+                None => preferred,
+                Some(_) if preferred.load(key).is_some() => preferred,
+                Some(stored_cards) if stored_cards <= cards => {
+                    self.table.counters.evictions.fetch_add(1, Ordering::Relaxed);
+                    preferred
+                },
+                Some(_) => &bucket[1]
+            };
+            slot.save(key, data);
+            self.table.counters.stores.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn stats(&self) -> Option<NodeStoreStats> {
+        let counters = &self.table.counters;
+        Some(NodeStoreStats{
+            hits: counters.hits.load(Ordering::Relaxed),
+            misses: counters.misses.load(Ordering::Relaxed),
+            stores: counters.stores.load(Ordering::Relaxed),
+            evictions: counters.evictions.load(Ordering::Relaxed),
+            ..Default::default()
+        })
+    }
+
+    fn age(&mut self, cards_left: u8) {
+        for slot in &self.table.slots{
+            // slots remember cards of single hand at trick opening
+            if slot.cards().is_some_and(|cards| cards as usize * 4 > cards_left as usize){
+                slot.clear();
+                self.table.counters.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::thread;
+    use brydz_core::karty::{card_set, cards::*};
+    use brydz_core::karty::set::CardSet;
+    use brydz_core::player::side::{SideMap, Side::*};
+    use crate::hash::{NodeStoreTrait, SharedNodeStore, TrickBounds};
+    use crate::hash::hash24::Hash24;
+    use crate::node::TrickNode;
+
+    #[test]
+    fn shared_store_between_threads(){
+        let hands = SideMap::new(
+            card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+            card_set![ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS],
+            card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+            card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS]);
+        let north = TrickNode::new_checked(hands, North).unwrap();
+        let east = TrickNode::new_checked(hands, East).unwrap();
+        // single line, so every node goes to the same bucket of side
+        let store = SharedNodeStore::<Hash24<3>, 1>::default();
+        thread::scope(|scope|{
+            let mut first = store.clone();
+            let mut second = store.clone();
+            scope.spawn(move || first.store_bounds(&north, TrickBounds::new(1, 3)));
+            scope.spawn(move || second.store_bounds(&east, TrickBounds::new(0, 2)));
+        });
+        let mut store = store;
+        store.store_bounds(&north, TrickBounds::new(2, 4));
+        assert_eq!(store.get_bounds(&north), Some(TrickBounds::new(2, 3)));
+        assert_eq!(store.get_bounds(&east), Some(TrickBounds::new(0, 2)));
+
+        let mut smaller = hands;
+        smaller.north.remove_card(&ACE_SPADES).unwrap();
+        smaller.east.remove_card(&ACE_HEARTS).unwrap();
+        smaller.south.remove_card(&ACE_DIAMONDS).unwrap();
+        smaller.west.remove_card(&KING_SPADES).unwrap();
+        let later = TrickNode::new_checked(smaller, North).unwrap();
+        assert_eq!(store.get_bounds(&later), None);
+        // node with less cards does not replace deeper one, it goes to second slot
+        store.store_bounds(&later, TrickBounds::exact(1));
+        assert_eq!(store.get_bounds(&later), Some(TrickBounds::exact(1)));
+        assert_eq!(store.get_bounds(&north), Some(TrickBounds::new(2, 3)));
+        assert_eq!(store.handles(), 1);
+        assert_eq!(store.stats().unwrap().stores, 4);
+    }
+
+    #[test]
+    fn storing_does_not_count_lookups(){
+        let hands = SideMap::new(card_set![ACE_SPADES], card_set![KING_SPADES],
+            card_set![QUEEN_SPADES], card_set![JACK_SPADES]);
+        let node = TrickNode::new_checked(hands, North).unwrap();
+        let mut store = SharedNodeStore::<Hash24<3>, 0x100>::default();
+        store.store_bounds(&node, TrickBounds::new(0, 1));
+        store.store_bounds(&node, TrickBounds::exact(1));
+        let stats = store.stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.stores), (0, 0, 2));
+        assert_eq!(store.get_bounds(&node), Some(TrickBounds::exact(1)));
+        assert_eq!(store.stats().unwrap().hits, 1);
+    }
+}
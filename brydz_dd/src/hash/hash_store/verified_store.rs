@@ -0,0 +1,158 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use log::warn;
+use brydz_core::player::side::SIDES;
+use crate::hash::{NodeStoreStats, NodeStoreTrait, TrickBounds};
+use crate::node::TrickNode;
+
+/// Complete description of node: hands and side on lead.
+type NodeKey = ([u64; 4], u8);
+
+fn node_key(node: &TrickNode) -> NodeKey{
+    (SIDES.map(|side| node.hands()[&side].into()), node.current_side().index())
+}
+
+/// Debug wrapper of node store. Besides storing in inner store, it keeps full nodes with their
+/// bounds in hash map and verifies every hit of inner store. Hit is false when node was never
+/// stored or bounds of inner store do not contain bounds stored for node. False hits are counted,
+/// logged and returned as misses, so exploration stays correct.
+/// It is slow and memory hungry, meant for measuring collision rate of hashers.
+/// # Example:
+/// ```
+/// use brydz_core::karty::{card_set, cards::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::hash::{HashArrayNodeStore, NodeStoreTrait, TrickBounds, VerifiedNodeStore};
+/// use brydz_dd::hash::hash24::Hash24;
+/// use brydz_dd::hash::ranker::MoreCardsRanker;
+/// use brydz_dd::node::TrickNode;
+/// let hands = SideMap::new(card_set![ACE_SPADES], card_set![KING_SPADES],
+///     card_set![QUEEN_SPADES], card_set![JACK_SPADES]);
+/// // the same cards in play, but held by other sides
+/// let other_deal = SideMap::new(card_set![KING_SPADES], card_set![ACE_SPADES],
+///     card_set![QUEEN_SPADES], card_set![JACK_SPADES]);
+/// let mut store = VerifiedNodeStore::<HashArrayNodeStore<Hash24<3>, MoreCardsRanker, 0x100, 2>>::default();
+/// store.store_bounds(&TrickNode::new_checked(hands, North).unwrap(), TrickBounds::exact(1));
+/// assert_eq!(store.get_bounds(&TrickNode::new_checked(other_deal, North).unwrap()), None);
+/// assert_eq!(store.false_hits(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VerifiedNodeStore<A: NodeStoreTrait>{
+    inner: A,
+    nodes: HashMap<NodeKey, TrickBounds>,
+    verified_hits: Cell<u64>,
+    false_hits: Cell<u64>,
+}
+
+impl<A: NodeStoreTrait> VerifiedNodeStore<A>{
+    pub fn new(inner: A) -> Self{
+        Self{inner, nodes: HashMap::new(), verified_hits: Cell::new(0), false_hits: Cell::new(0)}
+    }
+
+    pub fn inner(&self) -> &A{
+        &self.inner
+    }
+
+    pub fn verified_hits(&self) -> u64{
+        self.verified_hits.get()
+    }
+
+    pub fn false_hits(&self) -> u64{
+        self.false_hits.get()
+    }
+
+    /// Part of inner store hits that returned bounds of other node.
+    pub fn collision_rate(&self) -> f64{
+        match self.verified_hits() + self.false_hits(){
+            0 => 0.0,
+            hits => self.false_hits() as f64 / hits as f64
+        }
+    }
+}
+
+impl<A: NodeStoreTrait> NodeStoreTrait for VerifiedNodeStore<A>{
+    fn get_bounds(&self, node: &TrickNode) -> Option<TrickBounds> {
+        let found = self.inner.get_bounds(node)?;
+        let consistent = match self.nodes.get(&node_key(node)){
+            Some(stored) => found.lower() <= stored.lower() && stored.upper() <= found.upper(),
+            None => false
+        };
+        match consistent{
+            true => {
+                self.verified_hits.set(self.verified_hits.get() + 1);
+                Some(found)
+            },
+            false => {
+                warn!("False hit of node store for node {node:?}: {found:?}, stored: {:?}", self.nodes.get(&node_key(node)));
+                self.false_hits.set(self.false_hits.get() + 1);
+                None
+            }
+        }
+    }
+
+    fn store_bounds(&mut self, node: &TrickNode, bounds: TrickBounds) {
+        self.inner.store_bounds(node, bounds);
+        let entry = self.nodes.entry(node_key(node)).or_insert(bounds);
+        *entry = entry.intersect(&bounds);
+    }
+
+    fn stats(&self) -> Option<NodeStoreStats> {
+        let mut stats = self.inner.stats().unwrap_or_default();
+        stats.false_hits = self.false_hits();
+        Some(stats)
+    }
+
+    fn age(&mut self, cards_left: u8) {
+        self.inner.age(cards_left);
+        self.nodes.retain(|(hands, _), _| hands.iter().map(|hand| hand.count_ones()).sum::<u32>() <= cards_left as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TRUMPS;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::deal::fair_bridge_partial_deal;
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, Jack, King, Queen, F10};
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::side::Side::{North, West};
+    use crate::actions::HeuristicCardOrderer;
+    use crate::explore::{ExploreOutput, Explorer};
+    use crate::hash::{DummyNodeStore, HashArrayNodeStore, NodeHasher, NodeStoreTrait, VerifiedNodeStore};
+    use crate::hash::hash24::Hash24;
+    use crate::hash::ranker::MoreCardsRanker;
+    use crate::hash::zobrist::Zobrist64;
+    use crate::node::TrickNode;
+
+    type Verified<H> = VerifiedNodeStore<HashArrayNodeStore<H, MoreCardsRanker, 0x100, 4>>;
+
+    fn check_no_false_hits<H: NodeHasher>(){
+        let card_supply: Vec<Card> = Card::card_subset(vec![Ace, King, Queen, Jack, F10], vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        for strain in TRUMPS{
+            let contract = Contract::new(ContractParametersGen::new(West, Bid::init(strain, 1).unwrap()));
+            let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+            let mut plain = Explorer::<HeuristicCardOrderer, DummyNodeStore>::new_checked(contract.clone(), node).unwrap();
+            let mut verified = Explorer::<HeuristicCardOrderer, Verified<H>>::new_checked(contract, node).unwrap();
+            assert_eq!(verified.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity),
+                plain.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity));
+            let store = verified.node_store();
+            assert!(store.verified_hits() > 0);
+            assert_eq!(store.false_hits(), 0);
+            assert_eq!(store.collision_rate(), 0.0);
+            assert_eq!(store.stats().unwrap().false_hit_rate(), 0.0);
+        }
+    }
+
+    #[test]
+    fn zobrist_has_no_false_hits(){
+        check_no_false_hits::<Zobrist64>();
+    }
+
+    #[test]
+    fn hash24_has_no_false_hits_within_deal(){
+        check_no_false_hits::<Hash24<3>>();
+    }
+}
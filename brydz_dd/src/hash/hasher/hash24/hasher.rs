@@ -8,6 +8,7 @@ pub struct Hash24<const SHIFT_LOWER: i32>{
 impl<const SHIFT_LOWER: i32> NodeHasher for Hash24<SHIFT_LOWER>{
     type HashType = u32;
     type LabelType = u32;
+    const HASH_RANGE: usize = 0x1000000;
 
     fn hash(node: &TrickNode) -> Self::HashType {
         let cards_still_in_game = node.flatten_hands();
@@ -1,6 +1,7 @@
 mod nohash;
 //mod hash_28_a;
 pub mod hash24;
+pub mod zobrist;
 
 use std::fmt::Debug;
 //pub use nohash::*;
@@ -9,8 +10,10 @@ use crate::hash::Label;
 
 use crate::node::TrickNode;
 pub trait NodeHasher {
-    type HashType: TryInto<usize> + Debug;
+    type HashType: TryInto<usize> + TryFrom<usize> + Debug;
     type LabelType: Label;
+    /// Number of distinct hash values, every hash is lower.
+    const HASH_RANGE: usize;
 
 
     fn hash(node: &TrickNode) -> Self::HashType;
@@ -0,0 +1,125 @@
+use brydz_core::karty::cards::{Card, DECK_SIZE};
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use crate::hash::NodeHasher;
+use crate::node::TrickNode;
+
+const CARD_COUNT_BITS: u64 = 0x3f;
+const HASH_BITS: u64 = 0xffffff;
+
+const fn splitmix64(state: u64) -> u64{
+    let mut z = state.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+const fn generate_keys() -> [[u64; DECK_SIZE]; 4]{
+    let mut keys = [[0; DECK_SIZE]; 4];
+    let mut state = 0x2545f4914f6cdd1d;
+    let mut i = 0;
+    while i < 4 * DECK_SIZE{
+        state = splitmix64(state);
+        keys[i / DECK_SIZE][i % DECK_SIZE] = state;
+        i += 1;
+    }
+    keys
+}
+
+/// Fixed random keys of card in hand of side.
+static ZOBRIST_KEYS: [[u64; DECK_SIZE]; 4] = generate_keys();
+
+/// Key of `card` held by `side`. Key of position is xor of keys of all cards in hands,
+/// so it is updated with one xor when card leaves hand.
+pub fn zobrist_card_key(side: Side, card: &Card) -> u64{
+    ZOBRIST_KEYS[side.index() as usize][card.position()]
+}
+
+/// Computes key of hands from scratch.
+pub fn zobrist_key(hands: &SideMap<CardSetStd>) -> u64{
+    let mut key = 0;
+    for side in SIDES{
+        for card in hands[&side]{
+            key ^= zobrist_card_key(side, &card);
+        }
+    }
+    key
+}
+
+/// Hasher using 64-bit Zobrist key kept in [`TrickNode`] and updated incrementally when cards
+/// are removed from hands. Unlike [`Hash24`](crate::hash::hash24::Hash24) it depends on owners
+/// of cards, not only on cards remaining in game, so one node store may be used for many deals.
+/// Hash is made of lowest 24 bits of key, label keeps the rest of key and number of cards
+/// (in lowest 6 bits, replacing the same bits of key).
+/// ```
+/// use brydz_core::karty::{card_set, cards::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::hash::NodeHasher;
+/// use brydz_dd::hash::zobrist::Zobrist64;
+/// use brydz_dd::node::TrickNode;
+/// let hands = SideMap::new(card_set![ACE_SPADES], card_set![KING_SPADES],
+///     card_set![QUEEN_SPADES], card_set![JACK_SPADES]);
+/// let swapped = SideMap::new(card_set![KING_SPADES], card_set![ACE_SPADES],
+///     card_set![QUEEN_SPADES], card_set![JACK_SPADES]);
+/// let node = TrickNode::new_checked(hands, North).unwrap();
+/// let (hash, label) = Zobrist64::hash_and_label(&node);
+/// assert_eq!(Zobrist64::count_cards(&hash, &label), 4);
+/// assert_ne!(Zobrist64::hash_and_label(&TrickNode::new_checked(swapped, North).unwrap()), (hash, label));
+/// ```
+pub struct Zobrist64{
+
+}
+
+impl NodeHasher for Zobrist64{
+    type HashType = u32;
+    type LabelType = u64;
+    const HASH_RANGE: usize = HASH_BITS as usize + 1;
+
+    fn hash(node: &TrickNode) -> Self::HashType {
+        (node.zobrist() & HASH_BITS) as u32
+    }
+
+    fn label(node: &TrickNode) -> Self::LabelType {
+        let cards = node.flatten_hands().count_ones() as u64;
+        (node.zobrist() & !CARD_COUNT_BITS) | cards
+    }
+
+    fn hash_and_label(node: &TrickNode) -> (Self::HashType, Self::LabelType) {
+        (Self::hash(node), Self::label(node))
+    }
+
+    fn count_cards(_hash: &Self::HashType, label: &Self::LabelType) -> u32 {
+        (label & CARD_COUNT_BITS) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::deal::fair_bridge_deal;
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::player::side::Side::{East, North};
+    use crate::hash::NodeHasher;
+    use crate::hash::zobrist::{zobrist_key, Zobrist64};
+    use crate::node::TrickNode;
+
+    #[test]
+    fn zobrist_key_is_updated_incrementally(){
+        let hands = fair_bridge_deal::<CardSetStd>();
+        let mut node = TrickNode::new_checked(hands, North).unwrap();
+        let (hash, label) = Zobrist64::hash_and_label(&node);
+        assert_eq!(Zobrist64::count_cards(&hash, &label), 52);
+
+        let mut played = hands;
+        for side in [North, East]{
+            node.set_current_side(side);
+            let card = node.hands()[&side].into_iter().next().unwrap();
+            node.remove_card_current_side(&card).unwrap();
+            played[&side] = node.hands()[&side];
+            assert_eq!(node.zobrist(), zobrist_key(&played));
+        }
+        let (hash, label) = Zobrist64::hash_and_label(&node);
+        assert_eq!(Zobrist64::count_cards(&hash, &label), 50);
+        assert_eq!(node, TrickNode::new_checked(played, East).unwrap());
+    }
+}
@@ -21,4 +21,9 @@ impl Label for u32{
     fn unused() -> Self {
         u32::MAX
     }
-}
\ No newline at end of file
+}
+impl Label for u64{
+    fn unused() -> Self {
+        u64::MAX
+    }
+}
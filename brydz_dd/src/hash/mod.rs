@@ -18,6 +18,7 @@ mod hasher;
 mod hash_store;
 mod hash_entry;
 mod label;
+mod bounds;
 pub mod ranker;
 
 
@@ -25,5 +26,6 @@ pub use hasher::*;
 pub use label::*;
 pub use hash_store::*;
 pub use hash_entry::*;
+pub use bounds::*;
 
 
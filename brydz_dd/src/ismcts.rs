@@ -0,0 +1,337 @@
+//! Information set Monte Carlo tree search (single observer variant).
+//!
+//! Search tree is built from the point of view of deciding player: nodes are sequences of cards
+//! played from current position and are shared by all determinizations (layouts of hidden cards
+//! sampled from player's belief) in which these cards are legal. Therefore player can not plan
+//! different continuations for layouts it can not distinguish, which is the main weakness of
+//! perfect information Monte Carlo. No double dummy solver is used, positions below tree are
+//! evaluated with rollouts of [`RolloutPolicy`].
+use std::marker::PhantomData;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use smallvec::SmallVec;
+use brydz_core::amfiteatr::HeuristicPolicy;
+use brydz_core::amfiteatr::re_export::agent::Policy;
+use brydz_core::amfiteatr::re_export::error::AmfiteatrError;
+use brydz_core::amfiteatr::re_export::scheme::Scheme;
+use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_core::amfiteatr::state::{ContractAction, CreatedContractInfoSet};
+use brydz_core::contract::{Contract, ContractMechanics};
+use brydz_core::deal::BiasedHandDistribution;
+use brydz_core::karty::cards::Card;
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::player::side::{Side, SideMap};
+use crate::error::DoubleDummyError;
+use crate::play::{legal_cards, play_card};
+use crate::single_dummy::SingleDummyInfoSet;
+
+/// Default exploration constant of UCB, rewards are fractions of tricks in range `[0, 1]`.
+pub const DEFAULT_EXPLORATION: f64 = 0.7;
+
+/// Policy playing cards in rollouts, when all hands of determinized deal are known.
+pub trait RolloutPolicy{
+    /// Card for side on move, `hands` are cards not played yet. When `None` or illegal card is returned,
+    /// random legal card is played.
+    fn rollout_card(&self, contract: &Contract, hands: &SideMap<CardSetStd>, rng: &mut StdRng) -> Option<Card>;
+}
+
+impl<R: RolloutPolicy + ?Sized> RolloutPolicy for Box<R>{
+    fn rollout_card(&self, contract: &Contract, hands: &SideMap<CardSetStd>, rng: &mut StdRng) -> Option<Card> {
+        self.as_ref().rollout_card(contract, hands, rng)
+    }
+}
+
+/// Plays uniformly random legal cards.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomRollout{}
+
+impl RolloutPolicy for RandomRollout{
+    fn rollout_card(&self, contract: &Contract, hands: &SideMap<CardSetStd>, rng: &mut StdRng) -> Option<Card> {
+        let legal = legal_cards(contract, &hands[&contract.current_side()]);
+        match legal.is_empty(){
+            true => None,
+            false => Some(legal[rng.random_range(0..legal.len())])
+        }
+    }
+}
+
+/// Information set of agent deciding in determinized deal (declarer when dummy is on move), knowing
+/// own hand and dummy (after opening lead), with fair distribution assumption.
+pub fn rollout_information_set<I: CreatedContractInfoSet>(contract: &Contract, hands: &SideMap<CardSetStd>) -> I{
+    let agent = match contract.current_side() == contract.dummy(){
+        true => contract.declarer(),
+        false => contract.current_side()
+    };
+    let dummy_hand = match contract.count_completed_tricks() == 0 && contract.current_trick().is_empty(){
+        true => None,
+        false => Some(hands[&contract.dummy()])
+    };
+    I::create_new(agent, hands[&agent], contract.clone(), dummy_hand, BiasedHandDistribution::default())
+}
+
+/// Plays cards of agent policy, information set is created with [`rollout_information_set`].
+#[derive(Debug, Clone, Default)]
+pub struct PolicyRollout<P>{
+    policy: P,
+}
+
+impl<P> PolicyRollout<P>{
+    pub fn new(policy: P) -> Self{
+        Self{policy}
+    }
+
+    pub fn policy(&self) -> &P{
+        &self.policy
+    }
+}
+
+impl<P: Policy<ContractDP>> RolloutPolicy for PolicyRollout<P>
+where P::InfoSetType: CreatedContractInfoSet{
+    fn rollout_card(&self, contract: &Contract, hands: &SideMap<CardSetStd>, _rng: &mut StdRng) -> Option<Card> {
+        let info_set: P::InfoSetType = rollout_information_set(contract, hands);
+        match self.policy.select_action(&info_set).ok()?{
+            ContractAction::PlaceCard(card) => Some(card),
+            ContractAction::ShowHand(_) => None
+        }
+    }
+}
+
+/// Rollouts with [`HeuristicPolicy`].
+pub type HeuristicRollout = PolicyRollout<HeuristicPolicy>;
+
+struct SearchNode{
+    /// Card leading to node (`None` for root).
+    card: Option<Card>,
+    /// Side that played the card.
+    mover: Side,
+    children: Vec<usize>,
+    visits: u32,
+    /// Number of visits of parent, in which card of node was legal.
+    availability: u32,
+    /// Sum of rewards of mover's axis.
+    reward: f64,
+}
+
+impl SearchNode{
+    fn new(card: Option<Card>, mover: Side) -> Self{
+        Self{card, mover, children: Vec::new(), visits: 0, availability: 0, reward: 0.0}
+    }
+
+    fn ucb(&self, exploration: f64) -> f64{
+        self.reward / self.visits as f64
+            + exploration * ((self.availability.max(1) as f64).ln() / self.visits as f64).sqrt()
+    }
+}
+
+/// Information set Monte Carlo tree search policy. Every iteration samples layout of hidden cards
+/// consistent with information set `I` (and its belief about hands), descends tree with UCB
+/// restricted to cards legal in layout, expands one card and finishes deal with rollout policy `R`.
+/// Reward of every card is fraction of remaining tricks taken by axis of side playing it.
+/// Card of root visited most often is played.
+///
+/// Search is seeded from policy seed and position, so for the same position it is deterministic.
+/// # Example:
+/// ```
+/// use brydz_core::amfiteatr::re_export::agent::Policy;
+/// use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetSimple};
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractParametersGen};
+/// use brydz_core::deal::fair_bridge_deal;
+/// use brydz_core::karty::set::{CardSet, CardSetStd};
+/// use brydz_core::player::side::Side::*;
+/// use brydz_dd::ismcts::{IsmctsPolicy, RandomRollout};
+/// let contract = Contract::new(
+///     ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 3).unwrap()));
+/// let hands = fair_bridge_deal::<CardSetStd>();
+/// let policy = IsmctsPolicy::<ContractAgentInfoSetSimple, _>::new(RandomRollout{}, 50).with_seed(5);
+/// let east = ContractAgentInfoSetSimple::new(East, hands[&East], contract, None);
+/// let action = policy.select_action(&east).unwrap();
+/// assert!(matches!(action, ContractAction::PlaceCard(card) if hands[&East].contains(&card)));
+/// assert_eq!(policy.select_action(&east).unwrap(), action);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IsmctsPolicy<I, R: RolloutPolicy>{
+    rollout: R,
+    iterations: usize,
+    exploration: f64,
+    seed: u64,
+    _info_set: PhantomData<I>,
+}
+
+impl<I, R: RolloutPolicy> IsmctsPolicy<I, R>{
+    pub fn new(rollout: R, iterations: usize) -> Self{
+        Self{rollout, iterations: iterations.max(1), exploration: DEFAULT_EXPLORATION, seed: 0, _info_set: PhantomData}
+    }
+
+    pub fn with_exploration(mut self, exploration: f64) -> Self{
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self{
+        self.seed = seed;
+        self
+    }
+
+    pub fn iterations(&self) -> usize{
+        self.iterations
+    }
+
+    pub fn rollout(&self) -> &R{
+        &self.rollout
+    }
+
+    /// Searches for card to be played (by player or by dummy for declarer). Works with any information
+    /// set that can be viewed as single dummy position, not only with `I`.
+    pub fn search<J: SingleDummyInfoSet>(&self, info_set: &J) -> Result<Card, DoubleDummyError>{
+        let view = info_set.single_dummy_view()?;
+        let legal = view.legal_cards();
+        if legal.len() == 1{
+            return Ok(legal[0])
+        }
+        let root_contract = view.contract();
+        let mut rng = StdRng::seed_from_u64(view.position_seed(self.seed));
+        let mut nodes = vec![SearchNode::new(None, view.player())];
+
+        for _ in 0..self.iterations{
+            let mut hands = view.sample_layout(&mut rng)?;
+            let mut contract = root_contract.clone();
+            let mut path = vec![0];
+            let mut node = 0;
+            // selection and expansion
+            while !contract.is_completed(){
+                let side = contract.current_side();
+                let legal = legal_cards(&contract, &hands[&side]);
+                let children = std::mem::take(&mut nodes[node].children);
+                for &child in &children{
+                    if nodes[child].card.is_some_and(|card| legal.contains(&card)){
+                        nodes[child].availability += 1;
+                    }
+                }
+                nodes[node].children = children;
+                let untried: SmallVec<[Card; 13]> = legal.iter()
+                    .filter(|card| nodes[node].children.iter().all(|child| nodes[*child].card != Some(**card)))
+                    .copied()
+                    .collect();
+                if !untried.is_empty(){
+                    let card = untried[rng.random_range(0..untried.len())];
+                    play_card(&mut contract, &mut hands, side, card)?;
+                    nodes.push(SearchNode::new(Some(card), side));
+                    let child = nodes.len() - 1;
+                    nodes[node].children.push(child);
+                    path.push(child);
+                    break;
+                }
+                let child = nodes[node].children.iter().copied()
+                    .filter(|child| nodes[*child].card.is_some_and(|card| legal.contains(&card)))
+                    .max_by(|a, b| nodes[*a].ucb(self.exploration).total_cmp(&nodes[*b].ucb(self.exploration)))
+                    .ok_or(DoubleDummyError::NoConsistentLayout)?;
+                let card = nodes[child].card.ok_or(DoubleDummyError::NoConsistentLayout)?;
+                play_card(&mut contract, &mut hands, side, card)?;
+                path.push(child);
+                node = child;
+            }
+            // rollout
+            while !contract.is_completed(){
+                let side = contract.current_side();
+                let legal = legal_cards(&contract, &hands[&side]);
+                if legal.is_empty(){
+                    return Err(DoubleDummyError::NoConsistentLayout)
+                }
+                let card = self.rollout.rollout_card(&contract, &hands, &mut rng)
+                    .filter(|card| legal.contains(card))
+                    .unwrap_or_else(|| legal[rng.random_range(0..legal.len())]);
+                play_card(&mut contract, &mut hands, side, card)?;
+            }
+            let tricks = (contract.count_completed_tricks() - root_contract.count_completed_tricks()).max(1) as f64;
+            for &index in &path[1..]{
+                let axis = nodes[index].mover.axis();
+                let gained = contract.total_tricks_taken_axis(axis) - root_contract.total_tricks_taken_axis(axis);
+                nodes[index].visits += 1;
+                nodes[index].reward += gained as f64 / tricks;
+            }
+        }
+        nodes[0].children.iter()
+            .max_by_key(|child| nodes[**child].visits)
+            .and_then(|child| nodes[*child].card)
+            .ok_or(DoubleDummyError::NoConsistentLayout)
+    }
+
+    /// Action of agent seeing game through `info_set` (of any single dummy type): dummy shows its
+    /// hand, other agents play card found by [`search`](Self::search).
+    pub fn select_action_for<J: SingleDummyInfoSet>(&self, info_set: &J) -> Result<ContractAction, AmfiteatrError<ContractDP>>{
+        if info_set.side() == info_set.dummy_side(){
+            return Ok(ContractAction::ShowHand(*info_set.hand()))
+        }
+        Ok(ContractAction::PlaceCard(self.search(info_set)?))
+    }
+}
+
+impl<I: SingleDummyInfoSet, R: RolloutPolicy> Policy<ContractDP> for IsmctsPolicy<I, R>{
+    type InfoSetType = I;
+
+    fn select_action(&self, state: &Self::InfoSetType) -> Result<<ContractDP as Scheme>::ActionType, AmfiteatrError<ContractDP>> {
+        self.select_action_for(state)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use brydz_core::amfiteatr::HeuristicPolicy;
+    use brydz_core::amfiteatr::re_export::agent::{InformationSet, Policy};
+    use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetAssuming, ContractAgentInfoSetSimple};
+    use brydz_core::contract::{Contract, ContractMechanics};
+    use brydz_core::karty::set::CardSet;
+    use brydz_core::player::side::{Side, Side::*};
+    use crate::ismcts::{HeuristicRollout, IsmctsPolicy, RandomRollout, RolloutPolicy};
+    use crate::test_util::late_position;
+
+    /// Last five tricks are left in tested position.
+    const TRICKS_PLAYED: usize = 8;
+
+    fn agent_on_move(contract: &Contract) -> Side{
+        match contract.current_side(){
+            North => South,
+            side => side
+        }
+    }
+
+    #[test]
+    fn rollouts_play_legal_cards(){
+        let (contract, hands) = late_position(TRICKS_PLAYED);
+        let mut rng = StdRng::seed_from_u64(1);
+        let heuristic = HeuristicRollout::new(HeuristicPolicy::new());
+        let card = heuristic.rollout_card(&contract, &hands, &mut rng).unwrap();
+        assert!(hands[&contract.current_side()].contains(&card));
+        let card = RandomRollout{}.rollout_card(&contract, &hands, &mut rng).unwrap();
+        assert!(hands[&contract.current_side()].contains(&card));
+    }
+
+    #[test]
+    fn search_works_with_simple_and_assuming_information_sets(){
+        let (contract, hands) = late_position(TRICKS_PLAYED);
+        let agent = agent_on_move(&contract);
+        let simple = ContractAgentInfoSetSimple::new(agent, hands[&agent], contract.clone(), Some(hands[&North]));
+        let assuming = ContractAgentInfoSetAssuming::new_fair(agent, hands[&agent], contract.clone(), Some(hands[&North]));
+        let policy = IsmctsPolicy::<ContractAgentInfoSetSimple, _>::new(HeuristicRollout::new(HeuristicPolicy::new()), 200)
+            .with_seed(9);
+        let action = policy.select_action(&simple).unwrap();
+        assert!(simple.is_action_valid(&action));
+        assert_eq!(policy.select_action(&simple).unwrap(), action);
+
+        let policy = IsmctsPolicy::<ContractAgentInfoSetAssuming, _>::new(RandomRollout{}, 200);
+        let action = policy.select_action(&assuming).unwrap();
+        assert!(assuming.is_action_valid(&action));
+    }
+
+    #[test]
+    fn dummy_shows_hand_with_any_information_set(){
+        let (contract, hands) = late_position(TRICKS_PLAYED);
+        let assuming = ContractAgentInfoSetAssuming::new_fair(North, hands[&North], contract.clone(), Some(hands[&North]));
+        let policy = IsmctsPolicy::<ContractAgentInfoSetSimple, _>::new(RandomRollout{}, 10);
+        assert_eq!(policy.select_action_for(&assuming).unwrap(), ContractAction::ShowHand(hands[&North]));
+    }
+}
@@ -5,5 +5,21 @@ pub mod actions;
 pub mod explore;
 pub mod hash;
 pub mod node;
+pub mod par;
+pub mod table;
+pub mod batch;
+pub mod cache;
+pub mod policy;
+pub mod solver;
+pub mod analysis;
+pub mod play;
+pub mod single_dummy;
+pub mod ismcts;
+pub mod cfr;
+#[cfg(test)]
+pub(crate) mod test_util;
+
+pub use solver::solve;
+pub use analysis::analyse_game;
 
 //pub use tree::*;
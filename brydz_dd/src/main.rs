@@ -1,19 +1,49 @@
-use brydz_core::{
-    contract::{Contract, ContractParametersGen, ContractMechanics},
-    karty::{card_set, cards::*, suits::Suit::*, },
-    bidding::Bid,
-    cards::trump::Trump,
-    player::side::{SideMap, Side::*}};
-use brydz_dd::{node::TrickNode};
-use brydz_dd::explore::{BinaryExplorer, ExploreOutput, Explorer, ExplorerStateUpdate};
-use brydz_dd::actions::{DistinctCardGrouper, NeighbourCardGrouper};
-use brydz_dd::hash::{DummyNodeStore, HashArrayNodeStore};
-use brydz_dd::hash::hash24::Hash24;
-use brydz_dd::hash::ranker::MoreCardsRanker;
-//use brydz_core::karty::stack_hand;
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+use anyhow::{anyhow, bail};
+use clap::Parser;
+use log::{info, LevelFilter};
+use brydz_core::deal::{parse_pbn, ContractGameDescription};
+use brydz_dd::batch::{completed_indices, run_batch, BatchMode, BatchTask, InputFormat};
+use brydz_dd::cache::DoubleDummyCache;
+use brydz_dd::solver::{DefaultNodeStore, DefaultOptimiser};
 
-fn _setup_logger() -> Result<(), fern::InitError> {
-    fern::Dispatch::new()
+/// Solves double dummy deals read from file.
+/// Results are written as JSON lines, one per deal.
+#[derive(Parser)]
+pub struct CliBatchOptions{
+    /// Deals as RON/YAML list of contract game descriptions or PBN file
+    #[arg(short = 'i', long = "input")]
+    pub input: PathBuf,
+    /// Format of input, by default guessed from file extension
+    #[arg(short = 'f', long = "format", value_enum)]
+    pub format: Option<InputFormat>,
+    #[arg(short = 'o', long = "output")]
+    pub output: PathBuf,
+    #[arg(short = 'm', long = "mode", value_enum, default_value_t = BatchMode::Table)]
+    pub mode: BatchMode,
+    /// Skip deals already present in output file and append the rest
+    #[arg(short = 'r', long = "resume")]
+    pub resume: bool,
+    /// Binary file with results of previous runs, consulted before solving and extended with new results
+    #[arg(short = 'c', long = "cache")]
+    pub cache: Option<PathBuf>,
+    /// Number of rayon worker threads, by default number of cores
+    #[arg(short = 't', long = "threads")]
+    pub threads: Option<usize>,
+    /// Report progress every that many solved deals
+    #[arg(short = 'p', long = "progress", default_value_t = 10)]
+    pub progress_every: usize,
+    #[arg(short = 'l', long = "log", default_value_t = LevelFilter::Info)]
+    pub log_level: LevelFilter,
+    #[arg(long = "log_file")]
+    pub log_file: Option<PathBuf>,
+}
+
+pub fn setup_logger(options: &CliBatchOptions) -> Result<(), fern::InitError> {
+    let dispatch = fern::Dispatch::new()
         .format(|out, message, record| {
             out.finish(format_args!(
                 "{}[{}][{}] {}",
@@ -23,201 +53,91 @@ fn _setup_logger() -> Result<(), fern::InitError> {
                 message
             ))
         })
-        .level(log::LevelFilter::Debug)
-        .chain(std::io::stdout())
-        .chain(fern::log_file("output.log")?)
+        .level(options.log_level);
+
+    match &options.log_file{
+        None => dispatch.chain(std::io::stderr()),
+        Some(f) => dispatch.chain(fern::log_file(f)?)
+    }
         .apply()?;
     Ok(())
 }
-#[allow(dead_code)]
-fn dbg_explore(){
 
-    let contract = Contract::new(
-    ContractParametersGen::new(West, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
-        card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
-        card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
-        card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node ).unwrap();
-    let mut explorer2 = explorer.clone();
-    let mut explorer3 = explorer.clone();
-    let result = explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap();
-    let result_t = explorer2.explore_and_track_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap();
-    let result_h = explorer3.hint().unwrap();
-    println!("Result: {result:?}");
-     println!("Result: \n{result_t}");
-    println!("Hint: {result_h}");
-    explorer3.update(ExplorerStateUpdate::PlaceCard(KING_CLUBS)).unwrap();
-    let result_h = explorer3.hint().unwrap();
-    println!("Hint: {result_h}");
-}
-/*
-fn example_8_cards(){
-    let contract = Contract::new(
-    ContractSpec::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        stack_hand![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, EIGHT_HEARTS, TEN_SPADES, SEVEN_SPADES, NINE_DIAMONDS],
-        stack_hand![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS, SEVEN_DIAMONDS, QUEEN_CLUBS, NINE_CLUBS, SEVEN_CLUBS, NINE_HEARTS],
-        stack_hand![ACE_HEARTS, JACK_HEARTS, KING_SPADES, EIGHT_SPADES, KING_DIAMONDS, QUEEN_DIAMONDS, EIGHT_DIAMONDS, JACK_CLUBS],
-        stack_hand![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES, SEVEN_HEARTS, EIGHT_CLUBS]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::new_checked(contract, node, ExploreOutput::MinusInfinity, ExploreOutput::Infinity, DistinctCardGrouper{}).unwrap();
-    let result_h = explorer.hint().unwrap();
-    println!("Hint: {}", result_h);
+fn read_tasks(options: &CliBatchOptions) -> anyhow::Result<Vec<BatchTask>>{
+    let format = match options.format{
+        Some(format) => format,
+        None => InputFormat::from_path(&options.input)
+            .ok_or_else(|| anyhow!("Can not guess format of {:?}, use --format", options.input))?
+    };
+    let content = std::fs::read_to_string(&options.input)?;
+    let tasks = match format{
+        InputFormat::Ron => ron::from_str::<Vec<ContractGameDescription>>(&content)?
+            .iter().map(BatchTask::from).collect(),
+        InputFormat::Yaml => serde_yaml::from_str::<Vec<ContractGameDescription>>(&content)?
+            .iter().map(BatchTask::from).collect(),
+        InputFormat::Pbn => parse_pbn(&content)?
+            .iter().map(BatchTask::from).collect(),
+    };
+    Ok(tasks)
 }
-*/
 
-#[allow(dead_code)]
-fn example_6_cards(){
-    let contract = Contract::new(
-    ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, TEN_SPADES,  NINE_DIAMONDS],
-        card_set![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS,  QUEEN_CLUBS, NINE_CLUBS,  NINE_HEARTS],
-        card_set![ACE_HEARTS, JACK_HEARTS, KING_SPADES,  KING_DIAMONDS, QUEEN_DIAMONDS,  JACK_CLUBS],
-        card_set![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node).unwrap();
-    let result_h = explorer.hint().unwrap();
-    println!("Hint: {result_h}");
-}
+fn main() -> anyhow::Result<()>{
+    let cli = CliBatchOptions::parse();
+    setup_logger(&cli)?;
 
-#[allow(dead_code)]
-fn example_6_cards_n(){
-    let contract = Contract::new(
-    ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, TEN_SPADES,  NINE_DIAMONDS],
-        card_set![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS,  QUEEN_CLUBS, NINE_CLUBS,  NINE_HEARTS],
-        card_set![ACE_HEARTS, JACK_HEARTS, KING_SPADES,  KING_DIAMONDS, QUEEN_DIAMONDS,  JACK_CLUBS],
-        card_set![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::<NeighbourCardGrouper, DummyNodeStore>::new_checked(contract, node).unwrap();
-    let result_h = explorer.hint().unwrap();
-    println!("Hint: {result_h}");
-}
+    if let Some(threads) = cli.threads{
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+    }
 
-#[allow(dead_code)]
-fn example_6_cards_concurrent(){
-    let contract = Contract::new(
-    ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, TEN_SPADES,  NINE_DIAMONDS],
-        card_set![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS,  QUEEN_CLUBS, NINE_CLUBS,  NINE_HEARTS],
-        card_set![ACE_HEARTS, JACK_HEARTS, KING_SPADES,  KING_DIAMONDS, QUEEN_DIAMONDS,  JACK_CLUBS],
-        card_set![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node).unwrap();
-    let result_h = explorer.hint_concurrent().unwrap();
-    println!("Hint: {result_h}");
-    //explorer.update_state(ExplorerStateUpdate::PlaceCard(JACK_CLUBS)).unwrap();
-    //let result_h = explorer.hint().unwrap();
-    //println!("Placed: {:#}.\n Hint: {}", JACK_CLUBS, result_h);
-}
+    let tasks = read_tasks(&cli)?;
+    info!("Read {} deals from {:?}", tasks.len(), cli.input);
 
-#[allow(dead_code)]
-fn example_6_cards_binary(){
-    let contract = Contract::new(
-    ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, TEN_SPADES,  NINE_DIAMONDS],
-        card_set![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS,  QUEEN_CLUBS, NINE_CLUBS,  NINE_HEARTS],
-        card_set![ACE_HEARTS, JACK_HEARTS, KING_SPADES,  KING_DIAMONDS, QUEEN_DIAMONDS,  JACK_CLUBS],
-        card_set![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = BinaryExplorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node, 3).unwrap();
-    let result_h = explorer.hint().unwrap();
-    println!("Hint: {result_h}");
-}
+    let completed = match cli.resume && cli.output.exists(){
+        true => {
+            let previous = std::fs::read_to_string(&cli.output)?;
+            let completed = completed_indices(&previous);
+            if completed.iter().any(|i| *i >= tasks.len()){
+                bail!("Output {:?} contains results for deals not present in input", cli.output)
+            }
+            // last line may have been interrupted while writing
+            if !previous.is_empty() && !previous.ends_with('\n'){
+                OpenOptions::new().append(true).open(&cli.output)?.write_all(b"\n")?;
+            }
+            info!("Resuming, {} deals already solved", completed.len());
+            completed
+        },
+        false => Default::default()
+    };
 
-#[allow(dead_code)]
-fn example_7_cards(){
-    let contract = Contract::new(
-    ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, TEN_SPADES,  NINE_DIAMONDS, EIGHT_HEARTS],
-        card_set![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS,  QUEEN_CLUBS, NINE_CLUBS,  NINE_HEARTS, EIGHT_SPADES],
-        card_set![ACE_HEARTS, JACK_HEARTS, KING_SPADES,  KING_DIAMONDS, QUEEN_DIAMONDS,  JACK_CLUBS, EIGHT_DIAMONDS],
-        card_set![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES, EIGHT_CLUBS]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node).unwrap();
-    let result_h = explorer.hint().unwrap();
-    println!("Hint: {result_h}");
-    //explorer.update_state(ExplorerStateUpdate::PlaceCard(JACK_CLUBS)).unwrap();
-    //let result_h = explorer.hint().unwrap();
-    //println!("Placed: {:#}.\n Hint: {}", JACK_CLUBS, result_h);
-}
-
-#[allow(dead_code)]
-fn example_7_cards_hash(){
-    let contract = Contract::new(
-    ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, TEN_SPADES,  NINE_DIAMONDS, EIGHT_HEARTS],
-        card_set![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS,  QUEEN_CLUBS, NINE_CLUBS,  NINE_HEARTS, EIGHT_SPADES],
-        card_set![ACE_HEARTS, JACK_HEARTS, KING_SPADES,  KING_DIAMONDS, QUEEN_DIAMONDS,  JACK_CLUBS, EIGHT_DIAMONDS],
-        card_set![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES, EIGHT_CLUBS]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::<DistinctCardGrouper, HashArrayNodeStore<Hash24<3>, MoreCardsRanker,0x1000000,8 >>
-        ::new_checked(contract, node).unwrap();
-    let result_h = explorer.hint().unwrap();
-    println!("Hint: {result_h}");
-    explorer.update(ExplorerStateUpdate::PlaceCard(JACK_CLUBS)).unwrap();
-    let result_h = explorer.hint().unwrap();
-    println!("Placed: {JACK_CLUBS:#}.\n Hint: {result_h}");
-}
-#[allow(dead_code)]
-fn example_7_cards_concurrent(){
-    let contract = Contract::new(
-    ContractParametersGen::new(East, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_CLUBS, KING_CLUBS, TEN_CLUBS, TEN_HEARTS, TEN_SPADES,  NINE_DIAMONDS, EIGHT_HEARTS],
-        card_set![ACE_DIAMONDS, JACK_DIAMONDS, TEN_DIAMONDS,  QUEEN_CLUBS, NINE_CLUBS,  NINE_HEARTS, EIGHT_SPADES],
-        card_set![ACE_HEARTS, JACK_HEARTS, KING_SPADES,  KING_DIAMONDS, QUEEN_DIAMONDS,  JACK_CLUBS, EIGHT_DIAMONDS],
-        card_set![KING_HEARTS, QUEEN_HEARTS, ACE_SPADES, QUEEN_SPADES, JACK_SPADES, NINE_SPADES, EIGHT_CLUBS]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node).unwrap();
-    let result_h = explorer.hint_concurrent().unwrap();
-    println!("Hint: {result_h}");
-}
-#[allow(dead_code)]
-fn debug_binary_explorer_4(){
-    let contract = Contract::new(
-    ContractParametersGen::new(West, Bid::init(Trump::Colored(Diamonds), 1).unwrap()));
-    let hands = SideMap::new(
-        card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
-        card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
-        card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
-        card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
-    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
-    let mut explorer = BinaryExplorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node, 1).unwrap();
-    //let mut explorer2 = explorer.clone();
-    //let mut explorer3 = explorer.clone();
-    let result = explorer.hint().unwrap();
-    println!("Result: {result:#}");
-    explorer.update(ExplorerStateUpdate::PlaceCard(JACK_CLUBS)).unwrap();
-    println!("Result: {:#}", explorer.hint().unwrap());
-    explorer.update(ExplorerStateUpdate::PlaceCard(KING_HEARTS)).unwrap();
-    println!("Result: {:#}", explorer.hint().unwrap());
-    explorer.update(ExplorerStateUpdate::PlaceCard(ACE_CLUBS)).unwrap();
-    println!("Result: {:#}", explorer.hint().unwrap());
-    explorer.update(ExplorerStateUpdate::PlaceCard(QUEEN_DIAMONDS)).unwrap();
-    println!("Result: {:#}", explorer.hint().unwrap());
-    //let result_t = explorer2.explore_and_track_actions().unwrap();
-    //let result_h = explorer3.hint().unwrap();
+    let cache = match &cli.cache{
+        Some(path) => {
+            let cache = DoubleDummyCache::open(path)?;
+            info!("Opened cache {path:?} with {} tables and {} contract results", cache.tables_len(), cache.contracts_len());
+            Some(cache)
+        },
+        None => None
+    };
 
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(cli.resume)
+        .truncate(!cli.resume)
+        .open(&cli.output)?;
+    let start = Instant::now();
+    let every = cli.progress_every.max(1);
+    let solved = run_batch::<DefaultOptimiser, DefaultNodeStore, _, _>(
+        &tasks, cli.mode, cache.as_ref(), &completed, BufWriter::new(file), |done, total|{
+            if done % every == 0 || done == total{
+                let elapsed = start.elapsed();
+                let remaining = elapsed.mul_f64((total - done) as f64 / done as f64);
+                info!("Solved {done}/{total} deals, elapsed: {:.1}s, estimated remaining: {:.1}s",
+                    elapsed.as_secs_f64(), remaining.as_secs_f64());
+            }
+        })?;
+    info!("Finished, solved {solved} deals in {:.1}s", start.elapsed().as_secs_f64());
+    if let Some(cache) = &cache{
+        info!("Cache hits: {}, misses: {}", cache.hits(), cache.misses());
+    }
+    Ok(())
 }
-
-fn main() {
-    //setup_logger().unwrap();
-    //dbg_explore();
-    //example_7_cards_concurrent();
-    //example_7_cards();
-    example_6_cards_n();
-    //example_6_cards_concurrent();
-    //example_6_cards_binary();
-
-    //debug_binary_explorer_4()
-}
\ No newline at end of file
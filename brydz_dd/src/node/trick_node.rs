@@ -1,3 +1,5 @@
+use brydz_core::amfiteatr::state::{ContractEnvStateComplete, ContractState};
+use brydz_core::contract::{Contract, ContractMechanics};
 use brydz_core::error::CardSetErrorGen;
 use brydz_core::karty::cards::{Card, Card2SymTrait};
 use brydz_core::karty::error::CardSetError;
@@ -6,23 +8,26 @@ use brydz_core::karty::suits::Suit;
 use brydz_core::karty::symbol::CardSymbol;
 use brydz_core::player::side::{Side, SideMap, SIDES};
 use brydz_core::player::side::Side::{East, North, South, West};
+use crate::hash::zobrist::{zobrist_card_key, zobrist_key};
 //use crate::hash::{StateHash24, PartialHash, StateHash24EntryDistinguish};
 
 
 /// ```
 /// use brydz_dd::node::TrickNode;
-/// assert_eq!(std::mem::size_of::<TrickNode>(), 4*8 + 8)
+/// assert_eq!(std::mem::size_of::<TrickNode>(), 4*8 + 8 + 8)
 /// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct TrickNode {
     hands: SideMap<CardSetStd>,
     current_side: Side,
+    /// Zobrist key of hands, see [`Zobrist64`](crate::hash::zobrist::Zobrist64).
+    zobrist: u64,
 
 }
 
 impl TrickNode{
     pub fn new(hands: SideMap<CardSetStd>, current_side: Side) -> Self{
-        Self{hands, current_side}
+        Self{hands, current_side, zobrist: zobrist_key(&hands)}
     }
 
     /// ```
@@ -80,7 +85,68 @@ impl TrickNode{
                 }
             }
         }
-        Ok(Self{hands, current_side})
+        Ok(Self::new(hands, current_side))
+    }
+
+    /// Node of position in `contract`, which may be in the middle of trick. `hands` are cards not
+    /// played yet, side on move is taken from contract. Hands completed with cards of current trick
+    /// must be valid for [`TrickNode::new_checked`].
+    /// ```
+    /// use brydz_core::bidding::Bid;
+    /// use brydz_core::cards::trump::TrumpGen;
+    /// use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    /// use brydz_core::error::CardSetErrorGen;
+    /// use brydz_core::karty::{card_set, cards::*};
+    /// use brydz_core::karty::set::CardSet;
+    /// use brydz_core::player::side::{SideMap, Side::*};
+    /// use brydz_dd::node::TrickNode;
+    /// let mut contract = Contract::new(
+    ///     ContractParametersGen::new(West, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+    /// let mut hands = SideMap::new(
+    ///     card_set![ACE_SPADES, QUEEN_SPADES], card_set![ACE_HEARTS, KING_DIAMONDS],
+    ///     card_set![ACE_DIAMONDS, ACE_CLUBS], card_set![KING_SPADES, QUEEN_DIAMONDS]);
+    /// contract.insert_card(North, ACE_SPADES).unwrap();
+    /// assert!(TrickNode::new_in_contract(hands, &contract).is_err());
+    /// hands.north.remove_card(&ACE_SPADES).unwrap();
+    /// assert!(TrickNode::new_checked(hands, East).is_err());
+    /// let node = TrickNode::new_in_contract(hands, &contract).unwrap();
+    /// assert_eq!(node.current_side(), East);
+    /// hands.east.remove_card(&ACE_HEARTS).unwrap();
+    /// assert_eq!(TrickNode::new_in_contract(hands, &contract), Err(CardSetErrorGen::DifferentLengths(2, 1)));
+    /// ```
+    pub fn new_in_contract(hands: SideMap<CardSetStd>, contract: &Contract) -> Result<Self, CardSetError>{
+        let mut before_trick = hands;
+        for side in SIDES{
+            if let Some(card) = contract.current_trick()[side]{
+                before_trick[&side].insert_card(card)?;
+            }
+        }
+        Self::new_checked(before_trick, contract.current_side())?;
+        Ok(Self::new(hands, contract.current_side()))
+    }
+
+    /// Node of position in game run by environment, see [`TrickNode::new_in_contract`].
+    /// ```
+    /// use brydz_core::amfiteatr::re_export::env::SequentialGameState;
+    /// use brydz_core::amfiteatr::state::{ContractAction, ContractEnvStateComplete};
+    /// use brydz_core::bidding::Bid;
+    /// use brydz_core::cards::trump::TrumpGen;
+    /// use brydz_core::contract::{Contract, ContractParametersGen};
+    /// use brydz_core::karty::{card_set, cards::*};
+    /// use brydz_core::player::side::Side::*;
+    /// use brydz_dd::node::TrickNode;
+    /// let contract = Contract::new(
+    ///     ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+    /// let mut state = ContractEnvStateComplete::new(contract,
+    ///     card_set![ACE_SPADES, QUEEN_SPADES], card_set![ACE_HEARTS, KING_DIAMONDS],
+    ///     card_set![ACE_DIAMONDS, ACE_CLUBS], card_set![KING_SPADES, QUEEN_DIAMONDS]);
+    /// state.forward(East, ContractAction::PlaceCard(KING_DIAMONDS)).unwrap();
+    /// let node = TrickNode::from_env_state(&state).unwrap();
+    /// assert_eq!(node.current_side(), South);
+    /// assert_eq!(node.hands()[&East], card_set![ACE_HEARTS]);
+    /// ```
+    pub fn from_env_state(state: &ContractEnvStateComplete) -> Result<Self, CardSetError>{
+        Self::new_in_contract(SideMap::new_with_fn(|side| state[side]), state.contract_data())
     }
 
     pub fn flatten_hands(&self) -> u64{
@@ -89,10 +155,6 @@ impl TrickNode{
             .union(&self.hands[&West])
             .union(&self.hands[&North]).into()
     }
-    #[allow(dead_code)]
-    fn hands_mut(&mut self) -> &mut SideMap<CardSetStd>{
-        &mut self.hands
-    }
     pub fn hands(&self) -> &SideMap<CardSetStd>{
         &self.hands
     }
@@ -100,7 +162,12 @@ impl TrickNode{
         self.current_side
     }
     pub fn remove_card_current_side(&mut self, card: &Card) -> Result<(), CardSetError> {
-        self.hands[&self.current_side].remove_card(card)
+        self.hands[&self.current_side].remove_card(card)?;
+        self.zobrist ^= zobrist_card_key(self.current_side, card);
+        Ok(())
+    }
+    pub fn zobrist(&self) -> u64{
+        self.zobrist
     }
     pub fn set_current_side(&mut self, side: Side){
         self.current_side = side;
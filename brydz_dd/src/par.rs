@@ -0,0 +1,35 @@
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::player::side::{Side, SideMap};
+use brydz_core::score::{par, ParResult, Vulnerability};
+use brydz_core::score::tables::ScoringTable;
+use crate::actions::ActionOptimiser;
+use crate::error::DoubleDummyError;
+use crate::hash::NodeStoreTrait;
+use crate::table::solve_table;
+
+/// Computes par of deal given by hands, solving its double dummy table first.
+/// # Example:
+/// ```
+/// use brydz_core::deal::fair_bridge_partial_deal;
+/// use brydz_core::karty::cards::Card;
+/// use brydz_core::karty::cards::Card2SymTrait;
+/// use brydz_core::karty::figures::{Ace, King};
+/// use brydz_core::karty::set::CardSetStd;
+/// use brydz_core::karty::suits::Suit::*;
+/// use brydz_core::player::side::Side::North;
+/// use brydz_core::score::Vulnerability;
+/// use brydz_core::score::tables::SCORING_TABLE_STANDARD;
+/// use brydz_dd::actions::DistinctCardGrouper;
+/// use brydz_dd::hash::DummyNodeStore;
+/// use brydz_dd::par::par_of_deal;
+/// let card_supply: Vec<Card> = Card::card_subset(vec![Ace, King], vec![Spades, Hearts, Diamonds, Clubs]).collect();
+/// let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+/// // No contract can be made with only two tricks in deal
+/// let result = par_of_deal::<DistinctCardGrouper, DummyNodeStore>(&hands, Vulnerability::None, North, &SCORING_TABLE_STANDARD).unwrap();
+/// assert!(result.is_passed_out());
+/// ```
+pub fn par_of_deal<G, A>(hands: &SideMap<CardSetStd>, vulnerability: Vulnerability, dealer: Side, scoring: &ScoringTable) -> Result<ParResult, DoubleDummyError>
+where G: ActionOptimiser + Send, A: NodeStoreTrait + Send{
+    let table = solve_table::<G, A>(hands)?;
+    Ok(par(&table, vulnerability, dealer, scoring)?)
+}
@@ -0,0 +1,38 @@
+//! Card play helpers shared by searches that play positions out on known (or determinized) hands.
+//! Nothing here uses double dummy solver.
+use smallvec::SmallVec;
+use brydz_core::contract::{Contract, ContractMechanics};
+use brydz_core::error::BridgeCoreError;
+use brydz_core::karty::cards::Card;
+use brydz_core::karty::set::{CardSet, CardSetStd, HandSuitedTrait};
+use brydz_core::player::side::{Side, SideMap};
+use crate::error::DoubleDummyError;
+
+/// Cards from `hand` that can be placed in current trick of `contract` (following suit if possible).
+/// ```
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::Spades};
+/// use brydz_core::player::side::Side::*;
+/// use brydz_dd::play::legal_cards;
+/// let mut contract = Contract::new(ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Spades), 1).unwrap()));
+/// contract.insert_card(North, TWO_CLUBS).unwrap();
+/// let hand = card_set![ACE_CLUBS, KING_CLUBS, ACE_HEARTS];
+/// assert_eq!(legal_cards(&contract, &hand).len(), 2);
+/// let hand = card_set![ACE_DIAMONDS, ACE_HEARTS];
+/// assert_eq!(legal_cards(&contract, &hand).len(), 2);
+/// ```
+pub fn legal_cards(contract: &Contract, hand: &CardSetStd) -> SmallVec<[Card; 13]>{
+    match contract.current_trick().called_suit(){
+        Some(called) if hand.contains_in_suit(&called) => hand.suit_iterator(&called).collect(),
+        _ => hand.into_iter().collect()
+    }
+}
+
+/// Places `card` of `side` in `contract` and removes it from side's hand.
+pub fn play_card(contract: &mut Contract, hands: &mut SideMap<CardSetStd>, side: Side, card: Card) -> Result<(), DoubleDummyError>{
+    contract.insert_card(side, card).map_err(BridgeCoreError::from)?;
+    hands[&side].remove_card(&card).map_err(BridgeCoreError::from)?;
+    Ok(())
+}
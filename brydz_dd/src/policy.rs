@@ -0,0 +1,414 @@
+use std::marker::PhantomData;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use brydz_core::amfiteatr::re_export::agent::{Policy, PresentPossibleActions};
+use brydz_core::amfiteatr::re_export::error::AmfiteatrError;
+use brydz_core::amfiteatr::re_export::scheme::Scheme;
+use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetAllKnowing, ContractAgentInfoSetSimple, ContractInfoSet};
+use brydz_core::contract::{Contract, ContractMechanics, ContractParameters};
+use brydz_core::deal::BiasedHandDistribution;
+use brydz_core::error::BridgeCoreError;
+use brydz_core::karty::set::{CardSet, CardSetStd};
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{SideMap, SIDES};
+use brydz_core::score::Vulnerability;
+use crate::actions::ActionOptimiser;
+use crate::analysis::card_values_in_store;
+use crate::error::DoubleDummyError;
+use crate::explore::BinaryExplorer;
+use crate::explore::track::TrackStep;
+use crate::hash::NodeStoreTrait;
+use crate::node::TrickNode;
+use crate::single_dummy::{SingleDummyAnalyser, SingleDummyObjective, SingleDummyView};
+
+impl From<DoubleDummyError> for AmfiteatrError<ContractDP>{
+    fn from(value: DoubleDummyError) -> Self {
+        match value{
+            DoubleDummyError::Core(e) => Self::Game{source: e},
+            other => Self::NoActionAvailable {
+                context: format!("Double dummy exploration failed: {other}")
+            }
+        }
+    }
+}
+
+/// Position seen by agent knowing all hands, in form accepted by explorers.
+fn explorer_position(info_set: &ContractAgentInfoSetAllKnowing) -> Result<(Contract, TrickNode), DoubleDummyError>{
+    let contract = info_set.contract().clone();
+    let node = TrickNode::new_in_contract(*info_set.deal(), &contract).map_err(BridgeCoreError::from)?;
+    Ok((contract, node))
+}
+
+/// Common part of explorer backed policies. Dummy shows hand, otherwise card hinted by explorer
+/// is played (by declarer also for dummy). When explorer has no hint, lowest card of first
+/// available pack is played.
+fn select_with_hint<F>(info_set: &ContractAgentInfoSetAllKnowing, hint: F) -> Result<ContractAction, AmfiteatrError<ContractDP>>
+where F: FnOnce(Contract, TrickNode) -> Result<(TrackStep, Option<ContractAction>), DoubleDummyError>{
+    if info_set.is_dummy(){
+        return Ok(ContractAction::ShowHand(*info_set.hand()))
+    }
+    let (contract, node) = explorer_position(info_set)?;
+    let (track_step, fallback) = hint(contract, node)?;
+    track_step.hint()
+        .map(|card| ContractAction::PlaceCard(*card))
+        .or(fallback)
+        .ok_or_else(|| AmfiteatrError::NoActionAvailable {
+            context: format!("Double dummy policy of {:?}", info_set.side())
+        })
+}
+
+/// Policy playing double dummy optimal card, that is card maximising number of tricks taken
+/// by acting side when every player plays optimally with knowledge of all hands.
+/// Cards are valued with MTD(f) search ([`card_values_in_store`]). Node store is kept between
+/// decisions and reused while the same deal (and contract) is played, new deal starts with empty one.
+/// # Example:
+/// ```
+/// use brydz_core::amfiteatr::re_export::agent::Policy;
+/// use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetAllKnowing};
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::actions::DistinctCardGrouper;
+/// use brydz_dd::hash::DummyNodeStore;
+/// use brydz_dd::policy::DoubleDummyPolicy;
+/// let contract = Contract::new(
+///     ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// let policy = DoubleDummyPolicy::<DistinctCardGrouper, DummyNodeStore>::new();
+/// let north = ContractAgentInfoSetAllKnowing::new(North, hands, contract.clone());
+/// assert!(matches!(policy.select_action(&north).unwrap(), ContractAction::PlaceCard(_)));
+/// let dummy = ContractAgentInfoSetAllKnowing::new(East, hands, contract);
+/// assert_eq!(policy.select_action(&dummy).unwrap(), ContractAction::ShowHand(hands.east));
+/// ```
+#[derive(Debug)]
+pub struct DoubleDummyPolicy<G: ActionOptimiser, A: NodeStoreTrait>{
+    /// Node store filled in deal, with contract parameters and initial hands identifying the deal.
+    node_store: Mutex<Option<(ContractParameters, SideMap<CardSetStd>, A)>>,
+    _optimiser: PhantomData<G>,
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> DoubleDummyPolicy<G, A>{
+    pub fn new() -> Self{
+        Self{node_store: Mutex::new(None), _optimiser: PhantomData}
+    }
+
+    /// Node store of deal of information set, taken out of policy (empty one for new deal).
+    fn take_node_store(&self, info_set: &ContractAgentInfoSetAllKnowing) -> A{
+        match self.node_store.lock().unwrap_or_else(PoisonError::into_inner).take(){
+            Some((parameters, deal, store)) if &parameters == info_set.contract().contract_spec()
+                && &deal == info_set.initial_deal() => store,
+            _ => A::default()
+        }
+    }
+
+    fn put_node_store(&self, info_set: &ContractAgentInfoSetAllKnowing, store: A){
+        *self.node_store.lock().unwrap_or_else(PoisonError::into_inner) =
+            Some((info_set.contract().contract_spec().clone(), *info_set.initial_deal(), store));
+    }
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> Clone for DoubleDummyPolicy<G, A>{
+    /// Clone starts with empty node store.
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> Default for DoubleDummyPolicy<G, A>{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> Policy<ContractDP> for DoubleDummyPolicy<G, A>{
+    type InfoSetType = ContractAgentInfoSetAllKnowing;
+
+    fn select_action(&self, state: &Self::InfoSetType) -> Result<<ContractDP as Scheme>::ActionType, AmfiteatrError<ContractDP>> {
+        if state.is_dummy(){
+            return Ok(ContractAction::ShowHand(*state.hand()))
+        }
+        let contract = state.contract();
+        let (values, store) = card_values_in_store::<G, A>(contract, state.deal(), self.take_node_store(state))?;
+        self.put_node_store(state, store);
+        let values = values.into_iter().map(|(card, north_south)| (card, north_south as i32));
+        let best = match contract.current_side().axis(){
+            Axis::NorthSouth => values.max_by_key(|(_, north_south)| *north_south),
+            Axis::EastWest => values.min_by_key(|(_, north_south)| *north_south),
+        };
+        best.map(|(card, _)| ContractAction::PlaceCard(card))
+            .ok_or_else(|| AmfiteatrError::NoActionAvailable {
+                context: format!("Double dummy policy of {:?}", state.side())
+            })
+    }
+}
+
+/// Policy backed by [`BinaryExplorer`], playing card that keeps contract made (for declarer)
+/// or defeated (for defenders) if it is still possible. When result of contract is already
+/// decided first available card is played. Target is meaningful for complete deals.
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryDoubleDummyPolicy<G: ActionOptimiser, A: NodeStoreTrait>{
+    _optimiser: PhantomData<G>,
+    _node_store: PhantomData<A>,
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> BinaryDoubleDummyPolicy<G, A>{
+    pub fn new() -> Self{
+        Self{_optimiser: PhantomData, _node_store: PhantomData}
+    }
+
+    /// Number of tricks North-South must take to make (or defeat) contract.
+    fn north_south_target(contract: &Contract, node: &TrickNode) -> u8{
+        let tricks_in_deal = contract.count_completed_tricks() as u8
+            + SIDES.iter().map(|side| node.hands()[side].len()).max().unwrap_or(0) as u8;
+        let declarer_target = contract.contract_spec().bid().number_normalised();
+        match contract.declarer().axis(){
+            Axis::NorthSouth => declarer_target,
+            Axis::EastWest => (tricks_in_deal + 1).saturating_sub(declarer_target)
+        }
+    }
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> Default for BinaryDoubleDummyPolicy<G, A>{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> Policy<ContractDP> for BinaryDoubleDummyPolicy<G, A>{
+    type InfoSetType = ContractAgentInfoSetAllKnowing;
+
+    fn select_action(&self, state: &Self::InfoSetType) -> Result<<ContractDP as Scheme>::ActionType, AmfiteatrError<ContractDP>> {
+        select_with_hint(state, |contract, node|{
+            let target = Self::north_south_target(&contract, &node);
+            let mut explorer = BinaryExplorer::<G, A>::new_checked(contract, node, target)?;
+            let fallback = explorer.state().available_actions().into_iter().next()
+                .map(|pack| ContractAction::PlaceCard(pack.lowest_card()));
+            if fallback.is_none(){
+                return Ok((TrackStep::new(explorer.current_side()), None))
+            }
+            Ok((explorer.hint()?, fallback))
+        })
+    }
+}
+
+/// Perfect information Monte Carlo (PIMC) policy working on [`ContractAgentInfoSetSimple`].
+/// At every decision layouts of hidden cards consistent with information set (and optional
+/// distribution assumption) are sampled, every layout is solved double dummy and card with the best
+/// average outcome (of selected [`SingleDummyObjective`]) is played.
+/// Dummy shows hand, single legal card is played without sampling.
+///
+/// Sampling is seeded from policy seed and position, so the same position gets the same card,
+/// unless time limit stops sampling before all layouts are solved.
+/// # Example:
+/// ```
+/// use brydz_core::amfiteatr::re_export::agent::Policy;
+/// use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetSimple};
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractParametersGen};
+/// use brydz_core::karty::set::CardSetStd;
+/// use brydz_core::player::side::Side::*;
+/// use brydz_core::score::Vulnerability;
+/// use brydz_dd::actions::HeuristicCardOrderer;
+/// use brydz_dd::policy::PimcPolicy;
+/// use brydz_dd::solver::DefaultNodeStore;
+/// let contract = Contract::new(
+///     ContractParametersGen::new(North, Bid::init(TrumpGen::NoTrump, 3).unwrap()));
+/// let policy = PimcPolicy::<HeuristicCardOrderer, DefaultNodeStore>::new(8, Vulnerability::None)
+///     .with_seed(17);
+/// let dummy = ContractAgentInfoSetSimple::new(South, CardSetStd::empty(), contract, None);
+/// assert_eq!(policy.select_action(&dummy).unwrap(), ContractAction::ShowHand(CardSetStd::empty()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PimcPolicy<G: ActionOptimiser, A: NodeStoreTrait>{
+    analyser: SingleDummyAnalyser<G, A>,
+    objective: SingleDummyObjective,
+    time_limit: Option<Duration>,
+    distribution: Option<BiasedHandDistribution>,
+    seed: u64,
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> PimcPolicy<G, A>{
+    /// Policy solving up to `samples` layouts per decision, maximising expected score.
+    pub fn new(samples: usize, vulnerability: Vulnerability) -> Self{
+        Self{
+            analyser: SingleDummyAnalyser::new(samples, vulnerability),
+            objective: SingleDummyObjective::default(),
+            time_limit: None,
+            distribution: None,
+            seed: 0,
+        }
+    }
+
+    pub fn with_objective(mut self, objective: SingleDummyObjective) -> Self{
+        self.objective = objective;
+        self
+    }
+
+    /// Stops sampling of decision after time limit (at least one layout is always solved).
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self{
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Hidden cards are sampled according to distribution instead of uniformly.
+    pub fn with_distribution(mut self, distribution: BiasedHandDistribution) -> Self{
+        self.distribution = Some(distribution);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self{
+        self.seed = seed;
+        self
+    }
+
+    pub fn objective(&self) -> SingleDummyObjective{
+        self.objective
+    }
+
+    pub fn samples(&self) -> usize{
+        self.analyser.samples()
+    }
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> Policy<ContractDP> for PimcPolicy<G, A>{
+    type InfoSetType = ContractAgentInfoSetSimple;
+
+    fn select_action(&self, state: &Self::InfoSetType) -> Result<<ContractDP as Scheme>::ActionType, AmfiteatrError<ContractDP>> {
+        if state.is_dummy(){
+            return Ok(ContractAction::ShowHand(*state.hand()))
+        }
+        let legal = state.available_actions();
+        if legal.len() <= 1{
+            return legal.into_iter().next().ok_or_else(|| AmfiteatrError::NoActionAvailable {
+                context: format!("PIMC policy of {:?}", state.side())
+            })
+        }
+        let contract = state.contract_data();
+        let view = SingleDummyView::new_checked(contract.clone(), state.side(), *state.hand(),
+            state.dummy_hand().copied(), self.distribution.clone().unwrap_or_default())?;
+        let mut rng = StdRng::seed_from_u64(view.position_seed(self.seed));
+        let deadline = self.time_limit.map(|limit| Instant::now() + limit);
+        let analysis = self.analyser.analyse_until(&view, &mut rng, deadline)?;
+        let declaring = state.side().axis() == contract.declarer().axis();
+        analysis.best_by(self.objective, declaring)
+            .map(|stats| ContractAction::PlaceCard(stats.card))
+            .ok_or_else(|| AmfiteatrError::NoActionAvailable {
+                context: format!("PIMC policy of {:?} found no card in sampled layouts", state.side())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::amfiteatr::re_export::agent::{InformationSet, Policy};
+    use brydz_core::amfiteatr::state::{ContractAction, ContractAgentInfoSetAllKnowing, ContractAgentInfoSetSimple};
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+    use brydz_core::deal::fair_bridge_deal;
+    use brydz_core::karty::set::{CardSet, CardSetStd, HandSuitedTrait};
+    use brydz_core::player::side::{SideMap, Side::*};
+    use brydz_core::score::Vulnerability;
+    use crate::actions::{ActionOptimiser, DistinctCardGrouper, HeuristicCardOrderer, NeighbourCardGrouper};
+    use crate::hash::DummyNodeStore;
+    use crate::policy::{DoubleDummyPolicy, PimcPolicy};
+    use crate::single_dummy::SingleDummyObjective;
+    use crate::solver::DefaultNodeStore;
+
+    fn play_out<G: ActionOptimiser>(hands: SideMap<CardSetStd>, mut contract: Contract) -> Contract{
+        let policy = DoubleDummyPolicy::<G, DummyNodeStore>::new();
+        let mut hands = hands;
+        let tricks = hands.north.len();
+        for _ in 0..tricks * 4{
+            let side = contract.current_side();
+            let agent = match side == contract.dummy(){
+                true => contract.declarer(),
+                false => side
+            };
+            let info_set = ContractAgentInfoSetAllKnowing::new(agent, hands, contract.clone());
+            match policy.select_action(&info_set).unwrap(){
+                ContractAction::PlaceCard(card) => {
+                    contract.insert_card(side, card).unwrap();
+                    hands[&side].remove_card(&card).unwrap();
+                },
+                other => panic!("Expected card to be played, got {other:?}")
+            }
+        }
+        contract
+    }
+
+    #[test]
+    fn double_dummy_players_reach_solved_value(){
+        let contract = Contract::new(
+            ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+        let hands = SideMap::new(
+            card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+            card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+            card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+            card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+        // explorers are started in the middle of tricks, so both groupers must handle it
+        let distinct = play_out::<DistinctCardGrouper>(hands, contract.clone());
+        assert_eq!(distinct.total_tricks_taken_axis(West.axis()), 3);
+        let neighbour = play_out::<NeighbourCardGrouper>(hands, contract);
+        assert_eq!(neighbour.total_tricks_taken_axis(West.axis()), 3);
+    }
+
+    #[test]
+    fn double_dummy_policy_drops_store_of_other_deal(){
+        let contract = Contract::new(
+            ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+        let hands = SideMap::new(
+            card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+            card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+            card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+            card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+        // the same cards with hands of East and West swapped, not distinguished by Hash24
+        let swapped = SideMap::new(hands.north, hands.west, hands.south, hands.east);
+        let policy = DoubleDummyPolicy::<HeuristicCardOrderer, DefaultNodeStore>::new();
+        let first = ContractAgentInfoSetAllKnowing::new(North, hands, contract.clone());
+        assert_eq!(policy.select_action(&first).unwrap(), policy.select_action(&first).unwrap());
+        let second = ContractAgentInfoSetAllKnowing::new(North, swapped, contract);
+        let fresh = DoubleDummyPolicy::<HeuristicCardOrderer, DefaultNodeStore>::new();
+        assert_eq!(policy.select_action(&second).unwrap(), fresh.select_action(&second).unwrap());
+    }
+
+    #[test]
+    fn pimc_policy_is_deterministic(){
+        let mut hands = fair_bridge_deal::<CardSetStd>();
+        let mut contract = Contract::new(ContractParametersGen::new(South, Bid::init(TrumpGen::Colored(Spades), 3).unwrap()));
+        for _ in 0..10*4{
+            let side = contract.current_side();
+            let hand = &hands[&side];
+            let card = contract.current_trick().called_suit()
+                .filter(|suit| hand.contains_in_suit(suit))
+                .and_then(|suit| hand.suit_iterator(&suit).next())
+                .or_else(|| hand.into_iter().next())
+                .unwrap();
+            contract.insert_card(side, card).unwrap();
+            hands[&side].remove_card(&card).unwrap();
+        }
+        let agent = match contract.current_side(){
+            North => South,
+            side => side
+        };
+        let info_set = ContractAgentInfoSetSimple::new(agent, hands[&agent], contract, Some(hands[&North]));
+        let policy = PimcPolicy::<HeuristicCardOrderer, DefaultNodeStore>::new(6, Vulnerability::None).with_seed(3);
+        let action = policy.select_action(&info_set).unwrap();
+        assert!(info_set.is_action_valid(&action));
+        assert_eq!(policy.select_action(&info_set).unwrap(), action);
+        let policy = PimcPolicy::<HeuristicCardOrderer, DefaultNodeStore>::new(6, Vulnerability::None)
+            .with_objective(SingleDummyObjective::Contract);
+        assert!(info_set.is_action_valid(&policy.select_action(&info_set).unwrap()));
+    }
+}
@@ -0,0 +1,565 @@
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::time::Instant;
+use clap::ValueEnum;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use brydz_core::amfiteatr::re_export::agent::InformationSet;
+use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_core::amfiteatr::state::{ContractAgentInfoSetAssuming, ContractAgentInfoSetSimple, ContractInfoSet};
+use brydz_core::contract::{Contract, ContractMechanics};
+use brydz_core::deal::BiasedHandDistribution;
+use brydz_core::error::{BridgeCoreError, ContractErrorGen};
+use brydz_core::karty::cards::{Card, Card2SymTrait, STANDARD_DECK};
+use brydz_core::karty::set::{CardSet, CardSetStd};
+use brydz_core::karty::suits::SuitMap;
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use brydz_core::score::tables::ScoringTable;
+use brydz_core::score::Vulnerability;
+use crate::actions::ActionOptimiser;
+use crate::analysis::card_values_with;
+use crate::error::DoubleDummyError;
+use crate::hash::NodeStoreTrait;
+use crate::play::legal_cards;
+
+/// Number of attempts to sample layout before giving up.
+const LAYOUT_TRIES: usize = 64;
+
+/// Cards played in contract, including current trick.
+pub(crate) fn played_cards(contract: &Contract) -> Result<CardSetStd, DoubleDummyError>{
+    let mut played = CardSetStd::empty();
+    for trick in contract.completed_tricks().iter().chain(std::iter::once(contract.current_trick())){
+        for side in SIDES{
+            if let Some(card) = trick[side]{
+                played.insert_card(card).map_err(BridgeCoreError::from)?;
+            }
+        }
+    }
+    Ok(played)
+}
+
+/// Suits in which side did not follow called suit.
+pub(crate) fn shown_voids(contract: &Contract) -> SideMap<SuitMap<bool>>{
+    let mut voids = SideMap::new_symmetric(SuitMap::new_symmetric(false));
+    let mut tricks = contract.completed_tricks();
+    tricks.push(contract.current_trick().clone());
+    for trick in tricks{
+        if let Some(called) = trick.called_suit(){
+            for side in SIDES{
+                if matches!(trick[side], Some(card) if card.suit() != called){
+                    voids[&side][called] = true;
+                }
+            }
+        }
+    }
+    voids
+}
+
+/// Knowledge of single player at his decision: own hand, dummy (when it is already exposed),
+/// cards played so far (in contract) and belief about hidden hands.
+#[derive(Debug, Clone)]
+pub struct SingleDummyView{
+    contract: Contract,
+    player: Side,
+    hand: CardSetStd,
+    dummy_hand: Option<CardSetStd>,
+    distribution: BiasedHandDistribution,
+}
+
+impl SingleDummyView{
+    /// `hand` and `dummy_hand` are cards that are not played yet.
+    /// Player must be on move, declarer is on move also when it is turn of dummy.
+    pub fn new_checked(contract: Contract, player: Side, hand: CardSetStd, dummy_hand: Option<CardSetStd>, distribution: BiasedHandDistribution)
+        -> Result<Self, DoubleDummyError>{
+        let on_move = contract.current_side();
+        let decides = match on_move == contract.dummy(){
+            true => contract.declarer(),
+            false => on_move
+        };
+        if decides != player{
+            return Err(BridgeCoreError::from(ContractErrorGen::CurrentSidePresume(decides, player)).into())
+        }
+        Ok(Self{contract, player, hand, dummy_hand, distribution})
+    }
+
+    pub fn contract(&self) -> &Contract{
+        &self.contract
+    }
+
+    pub fn player(&self) -> Side{
+        self.player
+    }
+
+    pub fn hand(&self) -> &CardSetStd{
+        &self.hand
+    }
+
+    pub fn dummy_hand(&self) -> Option<&CardSetStd>{
+        self.dummy_hand.as_ref()
+    }
+
+    pub fn distribution(&self) -> &BiasedHandDistribution{
+        &self.distribution
+    }
+
+    /// Seed for sampling in this position, mixed from given seed, player's cards and number of played cards.
+    pub fn position_seed(&self, seed: u64) -> u64{
+        let played = (self.contract.count_completed_tricks() * 4
+            + self.contract.current_trick().count_cards() as usize) as u64;
+        let mut x = seed
+            ^ u64::from(self.hand)
+            ^ (played << 56)
+            ^ ((self.player.index() as u64) << 62);
+        // splitmix64 finaliser
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^ (x >> 31)
+    }
+
+    /// Cards that can be played by side on move (player's or dummy's).
+    pub fn legal_cards(&self) -> SmallVec<[Card; 13]>{
+        let hand = match self.contract.current_side() == self.player{
+            true => self.hand,
+            false => self.dummy_hand.unwrap_or(CardSetStd::empty())
+        };
+        legal_cards(&self.contract, &hand)
+    }
+
+    /// Hands known to player.
+    fn known_hands(&self) -> SmallVec<[(Side, CardSetStd); 2]>{
+        let mut known = SmallVec::new();
+        known.push((self.player, self.hand));
+        if let Some(dummy_hand) = self.dummy_hand{
+            let dummy = self.contract.dummy();
+            if dummy != self.player{
+                known.push((dummy, dummy_hand));
+            }
+        }
+        known
+    }
+
+    /// Number of tricks not completed yet, including current one.
+    fn tricks_left(&self) -> usize{
+        match self.contract.current_trick()[self.player]{
+            Some(_) => self.hand.len() + 1,
+            None => self.hand.len()
+        }
+    }
+
+    /// Number of cards not played yet by side.
+    fn cards_left(&self, side: Side) -> usize{
+        match self.contract.current_trick()[side]{
+            Some(_) => self.tricks_left() - 1,
+            None => self.tricks_left()
+        }
+    }
+
+    /// Samples cards not played yet of every side, consistent with the view.
+    /// Hidden cards are those not seen by player that are possible for any side in distribution.
+    /// Every card is given to hidden side with weight of its probability in distribution,
+    /// skipping sides that are full or showed void in suit.
+    pub fn sample_layout<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<SideMap<CardSetStd>, DoubleDummyError>{
+        let known = self.known_hands();
+        let hidden: SmallVec<[Side; 3]> = SIDES.into_iter()
+            .filter(|side| known.iter().all(|(k, _)| k != side))
+            .collect();
+        let mut seen = CardSetStd::empty();
+        for (_, hand) in &known{
+            for card in *hand{
+                seen.insert_card(card).map_err(BridgeCoreError::from)?;
+            }
+        }
+        for card in played_cards(&self.contract)?{
+            seen.insert_card(card).map_err(BridgeCoreError::from)?;
+        }
+        let voids = shown_voids(&self.contract);
+        let possible = |card: &Card, side: &Side| !voids[side][card.suit()];
+        let weight = |card: &Card, side: &Side| f32::from(self.distribution.card_probabilities(card)[side]);
+        let mut unseen: Vec<Card> = STANDARD_DECK.into_iter()
+            .filter(|card| !seen.contains(card) && SIDES.iter().any(|side| weight(card, side) > 0.0))
+            .collect();
+
+        'tries: for _ in 0..LAYOUT_TRIES{
+            unseen.shuffle(rng);
+            // cards with less possible owners go first, so they do not find hands filled
+            unseen.sort_by_key(|card| hidden.iter().filter(|side| possible(card, *side)).count());
+            let mut layout = SideMap::new_symmetric(CardSetStd::empty());
+            for (side, hand) in &known{
+                layout[side] = *hand;
+            }
+            let mut free = SideMap::new_with_fn(|side| self.cards_left(side));
+            for card in &unseen{
+                let candidates: SmallVec<[(Side, f32); 3]> = hidden.iter()
+                    .filter(|side| free[*side] > 0 && possible(card, *side))
+                    .map(|side| (*side, weight(card, side)))
+                    .collect();
+                if candidates.is_empty(){
+                    continue 'tries;
+                }
+                let total: f32 = candidates.iter().map(|(_, w)| w).sum();
+                let side = match total > 0.0{
+                    true => {
+                        let mut sample = rng.random_range(0.0..total);
+                        candidates.iter().find(|(_, w)|{
+                            sample -= w;
+                            sample < 0.0
+                        }).unwrap_or(&candidates[candidates.len() - 1]).0
+                    },
+                    false => candidates[rng.random_range(0..candidates.len())].0
+                };
+                layout[&side].insert_card(*card).map_err(BridgeCoreError::from)?;
+                free[&side] -= 1;
+            }
+            if hidden.iter().all(|side| free[side] == 0){
+                return Ok(layout)
+            }
+        }
+        Err(DoubleDummyError::NoConsistentLayout)
+    }
+}
+
+/// Information set of card playing agent, that can be viewed as [`SingleDummyView`].
+pub trait SingleDummyInfoSet: ContractInfoSet + InformationSet<ContractDP>{
+    /// Belief about hidden hands, fair distribution by default.
+    fn hands_distribution(&self) -> BiasedHandDistribution{
+        BiasedHandDistribution::default()
+    }
+
+    fn single_dummy_view(&self) -> Result<SingleDummyView, DoubleDummyError>{
+        SingleDummyView::new_checked(self.contract_data().clone(), self.side(), *self.hand(),
+            self.dummy_hand().copied(), self.hands_distribution())
+    }
+}
+
+impl SingleDummyInfoSet for ContractAgentInfoSetSimple{}
+
+impl SingleDummyInfoSet for ContractAgentInfoSetAssuming{
+    fn hands_distribution(&self) -> BiasedHandDistribution {
+        self.distribution_assumption().clone()
+    }
+}
+
+/// Aggregated results of card over sampled layouts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingleDummyCardStats{
+    pub card: Card,
+    /// Expected number of tricks of player's axis in the whole deal.
+    pub expected_tricks: f64,
+    /// Probability that contract is made.
+    pub making_probability: f64,
+    /// Expected score of player's axis.
+    pub expected_score: f64,
+}
+
+/// Criterion of choosing card from single dummy statistics.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SingleDummyObjective{
+    /// Maximise expected number of tricks.
+    Tricks,
+    /// Maximise probability of making contract (of defeating it for defenders).
+    Contract,
+    /// Maximise expected score.
+    #[default]
+    Score,
+}
+
+impl SingleDummyObjective{
+    /// Value of card statistics for player, `declaring` tells if player is on declarer's axis.
+    pub fn value(&self, stats: &SingleDummyCardStats, declaring: bool) -> f64{
+        match (self, declaring){
+            (Self::Tricks, _) => stats.expected_tricks,
+            (Self::Contract, true) => stats.making_probability,
+            (Self::Contract, false) => 1.0 - stats.making_probability,
+            (Self::Score, _) => stats.expected_score,
+        }
+    }
+}
+
+/// Result of single dummy analysis, cards are sorted from the best expected score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingleDummyAnalysis{
+    pub player: Side,
+    pub samples: usize,
+    pub cards: Vec<SingleDummyCardStats>,
+}
+
+impl SingleDummyAnalysis{
+    /// Card with the best expected score.
+    pub fn best(&self) -> Option<&SingleDummyCardStats>{
+        self.cards.first()
+    }
+
+    pub fn card_stats(&self, card: &Card) -> Option<&SingleDummyCardStats>{
+        self.cards.iter().find(|stats| stats.card == *card)
+    }
+
+    /// Card with the best value of objective, ties are resolved by order of cards (expected score first).
+    /// `declaring` tells if player is on declarer's axis.
+    pub fn best_by(&self, objective: SingleDummyObjective, declaring: bool) -> Option<&SingleDummyCardStats>{
+        let mut best: Option<(&SingleDummyCardStats, f64)> = None;
+        for stats in &self.cards{
+            let value = objective.value(stats, declaring);
+            match best{
+                Some((_, best_value)) if value <= best_value => {},
+                _ => best = Some((stats, value))
+            }
+        }
+        best.map(|(stats, _)| stats)
+    }
+}
+
+impl Display for SingleDummyAnalysis{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} ({} layouts):", self.player, self.samples)?;
+        for stats in &self.cards{
+            writeln!(f, "{:#}\ttricks: {:.2}\tmaking: {:.1}%\tscore: {:.1}",
+                stats.card, stats.expected_tricks, stats.making_probability * 100.0, stats.expected_score)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct CardTotals{
+    tricks: u64,
+    made: u64,
+    score: i64,
+}
+
+/// Single dummy analysis with Monte-Carlo sampling: layouts of hidden cards are sampled
+/// from player's view, every layout is solved double dummy for every legal card and results are
+/// averaged. Equivalent cards are solved once per layout, as groups of optimiser `G`.
+#[derive(Debug, Clone)]
+pub struct SingleDummyAnalyser<G: ActionOptimiser, A: NodeStoreTrait>{
+    samples: usize,
+    vulnerability: Vulnerability,
+    scoring: ScoringTable,
+    _phantom: PhantomData<(G, A)>,
+}
+
+impl<G: ActionOptimiser, A: NodeStoreTrait> SingleDummyAnalyser<G, A>{
+    pub fn new(samples: usize, vulnerability: Vulnerability) -> Self{
+        Self::new_with_scoring(samples, vulnerability, ScoringTable::default())
+    }
+
+    pub fn new_with_scoring(samples: usize, vulnerability: Vulnerability, scoring: ScoringTable) -> Self{
+        Self{samples: samples.max(1), vulnerability, scoring, _phantom: PhantomData}
+    }
+
+    pub fn samples(&self) -> usize{
+        self.samples
+    }
+
+    /// Solves every card group of side on move in one layout, returns tricks of declarer for cards.
+    /// Every layout gets own node store: stores hashing only remaining cards (like
+    /// [`DefaultNodeStore`](crate::solver::DefaultNodeStore)) do not tell layouts apart.
+    fn solve_layout(&self, contract: &Contract, layout: &SideMap<CardSetStd>) -> Result<Vec<(Card, u8)>, DoubleDummyError>{
+        let tricks_in_deal = contract.count_completed_tricks() as u8
+            + SIDES.iter().map(|s| layout[s].len()).max().unwrap_or(0) as u8;
+        let values = card_values_with::<G, A>(contract, layout)?;
+        Ok(values.into_iter()
+            .map(|(card, north_south)| (card, match contract.declarer().axis(){
+                Axis::NorthSouth => north_south,
+                Axis::EastWest => tricks_in_deal - north_south
+            }))
+            .collect())
+    }
+
+    /// Samples layouts and aggregates double dummy results of legal cards.
+    pub fn analyse<R: Rng + ?Sized>(&self, view: &SingleDummyView, rng: &mut R) -> Result<SingleDummyAnalysis, DoubleDummyError>{
+        self.analyse_until(view, rng, None)
+    }
+
+    /// Like [`analyse`](Self::analyse), but stops sampling at deadline (at least one layout is always solved).
+    /// Number of solved layouts is reported in [`SingleDummyAnalysis::samples`].
+    pub fn analyse_until<R: Rng + ?Sized>(&self, view: &SingleDummyView, rng: &mut R, deadline: Option<Instant>) -> Result<SingleDummyAnalysis, DoubleDummyError>{
+        let contract = view.contract();
+        let declarer = contract.declarer();
+        let for_declarer = view.player().axis() == declarer.axis();
+        let bid_tricks = contract.contract_spec().bid().number_normalised();
+        let vulnerable = self.vulnerability.is_vulnerable(declarer.axis());
+        let mut totals: Vec<(Card, CardTotals)> = Vec::new();
+        let tricks_in_deal = (contract.count_completed_tricks() + view.tricks_left()) as u64;
+
+        let mut samples = 0;
+        while samples < self.samples{
+            if samples > 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline){
+                break;
+            }
+            samples += 1;
+            let layout = view.sample_layout(rng)?;
+            let values = self.solve_layout(contract, &layout)?;
+            for (card, declarer_tricks) in values{
+                let score = self.scoring.score(contract.contract_spec(), declarer_tricks, vulnerable)? as i64;
+                let index = match totals.iter().position(|(c, _)| *c == card){
+                    Some(i) => i,
+                    None => {
+                        totals.push((card, CardTotals::default()));
+                        totals.len() - 1
+                    }
+                };
+                let entry = &mut totals[index].1;
+                entry.made += (declarer_tricks >= bid_tricks) as u64;
+                match for_declarer{
+                    true => {
+                        entry.tricks += declarer_tricks as u64;
+                        entry.score += score;
+                    },
+                    false => {
+                        entry.tricks += tricks_in_deal - declarer_tricks as u64;
+                        entry.score -= score;
+                    }
+                }
+            }
+        }
+        let n = samples as f64;
+        let mut cards: Vec<SingleDummyCardStats> = totals.into_iter().map(|(card, totals)| SingleDummyCardStats{
+            card,
+            expected_tricks: totals.tricks as f64 / n,
+            making_probability: totals.made as f64 / n,
+            expected_score: totals.score as f64 / n,
+        }).collect();
+        cards.sort_by(|a, b| b.expected_score.total_cmp(&a.expected_score)
+            .then(b.expected_tricks.total_cmp(&a.expected_tricks))
+            .then(a.card.position().cmp(&b.card.position())));
+        Ok(SingleDummyAnalysis{player: view.player(), samples, cards})
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::time::Instant;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use brydz_core::contract::{Contract, ContractMechanics};
+    use brydz_core::deal::BiasedHandDistribution;
+    use brydz_core::karty::{card_set, cards::*};
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::set::{CardSet, CardSetStd, HandSuitedTrait};
+    use brydz_core::player::side::{SideMap, Side::*, SIDES};
+    use brydz_core::score::Vulnerability;
+    use crate::actions::HeuristicCardOrderer;
+    use crate::analysis::card_values_with;
+    use crate::error::DoubleDummyError;
+    use crate::single_dummy::{shown_voids, SingleDummyAnalyser, SingleDummyObjective, SingleDummyView};
+    use crate::solver::DefaultNodeStore;
+    use crate::test_util::{late_position, two_tricks_left};
+
+    /// Last four tricks are left in tested position.
+    const TRICKS_PLAYED: usize = 9;
+
+    fn declarer_view(contract: &Contract, hands: &SideMap<CardSetStd>) -> SingleDummyView{
+        let player = match contract.current_side(){
+            North => South,
+            side => side
+        };
+        SingleDummyView::new_checked(contract.clone(), player, hands[&player], Some(hands[&North]),
+            BiasedHandDistribution::default()).unwrap()
+    }
+
+    #[test]
+    fn sampled_layouts_are_consistent(){
+        let (contract, hands) = late_position(TRICKS_PLAYED);
+        let view = declarer_view(&contract, &hands);
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20{
+            let layout = view.sample_layout(&mut rng).unwrap();
+            assert_eq!(layout[&North], hands[&North]);
+            assert_eq!(layout[&view.player()], hands[&view.player()]);
+            for side in SIDES{
+                assert_eq!(layout[&side].len(), hands[&side].len());
+                for card in layout[&side]{
+                    assert!(!shown_voids(view.contract())[&side][card.suit()]);
+                }
+            }
+            let mut cards = CardSetStd::empty();
+            for side in SIDES{
+                for card in layout[&side]{
+                    cards.insert_card(card).unwrap();
+                }
+            }
+            assert_eq!(cards.len(), 16 - contract.current_trick().count_cards() as usize);
+        }
+    }
+
+    #[test]
+    fn single_dummy_statistics(){
+        let (contract, hands) = late_position(TRICKS_PLAYED);
+        let view = declarer_view(&contract, &hands);
+        let analyser = SingleDummyAnalyser::<HeuristicCardOrderer, DefaultNodeStore>::new(5, Vulnerability::None);
+        let analysis = analyser.analyse(&view, &mut StdRng::seed_from_u64(11)).unwrap();
+        let on_move = hands[&contract.current_side()];
+        let legal = match contract.current_trick().called_suit(){
+            Some(suit) if on_move.contains_in_suit(&suit) => on_move.suit_iterator(&suit).count(),
+            _ => on_move.len()
+        };
+        assert_eq!(analysis.cards.len(), legal);
+        assert_eq!(analysis.samples, 5);
+        for stats in &analysis.cards{
+            assert!((0.0..=1.0).contains(&stats.making_probability));
+            assert!(stats.expected_tricks <= 13.0);
+        }
+        let best = analysis.best().unwrap();
+        assert!(analysis.cards.iter().all(|stats| stats.expected_score <= best.expected_score));
+        assert_eq!(analysis.to_string().lines().count(), legal + 1);
+    }
+
+    #[test]
+    fn layouts_differing_in_owners_are_solved_apart(){
+        // dummy leads from ace and queen of spades, king falls under ace when defenders split king
+        // and jack, so value of layouts depends on owners of the same hidden cards
+        let contract = two_tricks_left();
+        let hands = SideMap::new(card_set![ACE_SPADES, QUEEN_SPADES], card_set![KING_SPADES, ACE_CLUBS],
+            card_set![TEN_SPADES, ACE_HEARTS], card_set![JACK_SPADES, ACE_DIAMONDS]);
+        let view = declarer_view(&contract, &hands);
+        let samples = 12;
+        let analyser = SingleDummyAnalyser::<HeuristicCardOrderer, DefaultNodeStore>::new(samples, Vulnerability::None);
+        let analysis = analyser.analyse(&view, &mut StdRng::seed_from_u64(13)).unwrap();
+        let mut rng = StdRng::seed_from_u64(13);
+        let mut totals: Vec<(Card, u64)> = Vec::new();
+        let mut ace_values = Vec::new();
+        for _ in 0..samples{
+            let layout = view.sample_layout(&mut rng).unwrap();
+            for (card, north_south) in card_values_with::<HeuristicCardOrderer, DefaultNodeStore>(&contract, &layout).unwrap(){
+                if card == ACE_SPADES{
+                    ace_values.push(north_south);
+                }
+                match totals.iter_mut().find(|(c, _)| *c == card){
+                    Some((_, total)) => *total += north_south as u64,
+                    None => totals.push((card, north_south as u64))
+                }
+            }
+        }
+        assert!(ace_values.iter().any(|value| *value != ace_values[0]));
+        for (card, total) in totals{
+            assert_eq!(analysis.card_stats(&card).unwrap().expected_tricks, total as f64 / samples as f64);
+        }
+    }
+
+    #[test]
+    fn deadline_stops_sampling(){
+        let (contract, hands) = late_position(TRICKS_PLAYED);
+        let view = declarer_view(&contract, &hands);
+        let analyser = SingleDummyAnalyser::<HeuristicCardOrderer, DefaultNodeStore>::new(50, Vulnerability::None);
+        let analysis = analyser.analyse_until(&view, &mut StdRng::seed_from_u64(3), Some(Instant::now())).unwrap();
+        assert_eq!(analysis.samples, 1);
+        let best = analysis.best_by(SingleDummyObjective::Tricks, true).unwrap();
+        assert!(analysis.cards.iter().all(|stats| stats.expected_tricks <= best.expected_tricks));
+    }
+
+    #[test]
+    fn view_of_player_not_on_move_is_rejected(){
+        let (contract, hands) = late_position(TRICKS_PLAYED);
+        let decides = match contract.current_side(){
+            North => South,
+            side => side
+        };
+        let player = SIDES.into_iter().find(|side| *side != decides && *side != North).unwrap();
+        let result = SingleDummyView::new_checked(contract.clone(), player, hands[&player], None, BiasedHandDistribution::default());
+        assert!(matches!(result, Err(DoubleDummyError::Core(_))));
+    }
+}
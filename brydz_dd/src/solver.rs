@@ -0,0 +1,154 @@
+use brydz_core::contract::{Contract, ContractMechanics};
+use brydz_core::error::BridgeCoreError;
+use brydz_core::karty::set::{CardSet, CardSetStd};
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{SideMap, SIDES};
+use log::debug;
+use crate::actions::{ActionOptimiser, HeuristicCardOrderer};
+use crate::cache::{cache_north_south, cached_north_south};
+use crate::error::DoubleDummyError;
+use crate::explore::{quick_bounds, BinaryExplorer};
+use crate::hash::{HashArrayNodeStore, NodeStoreTrait, TrickBounds};
+use crate::hash::hash24::Hash24;
+use crate::hash::ranker::MoreCardsRanker;
+use crate::node::TrickNode;
+
+/// Node store used by [`solve`].
+pub type DefaultNodeStore = HashArrayNodeStore<Hash24<3>, MoreCardsRanker, 0x10000, 4>;
+/// Action optimiser used by [`solve`].
+pub type DefaultOptimiser = HeuristicCardOrderer;
+
+/// Number of tricks that are not completed yet, including current one.
+fn tricks_left(node: &TrickNode) -> u8{
+    SIDES.iter().map(|side| node.hands()[side].len()).max().unwrap_or(0) as u8
+}
+
+/// Finds exact number of tricks North-South take (counting already completed tricks) with sequence
+/// of null-window searches of [`BinaryExplorer`] (MTD(f) driver).
+/// Window starts from quick bounds of position and first target is taken from their middle.
+/// Every search narrows window by one and next target is set next to the previous one.
+/// Node store is passed from one search to the next, because stored bounds do not depend on target.
+/// Returns tricks and node store to be reused.
+pub fn mtdf<G: ActionOptimiser, A: NodeStoreTrait>(contract: Contract, node: TrickNode, node_store: A) -> Result<(u8, A), DoubleDummyError>{
+    let taken = contract.total_tricks_taken_axis(Axis::NorthSouth) as u8;
+    let quick = match contract.current_trick().is_empty(){
+        true => quick_bounds(&node, contract.trump()),
+        false => TrickBounds::new(0, tricks_left(&node))
+    };
+    let (mut lower, mut upper) = (taken + quick.lower(), taken + quick.upper());
+    let mut guess = (lower + upper + 1) / 2;
+    let mut node_store = node_store;
+    let mut searches = 0;
+    while lower < upper{
+        let target = guess.clamp(lower + 1, upper);
+        let mut explorer = BinaryExplorer::<G, A>::new_with_node_store(contract.clone(), node, target, node_store)?;
+        let reached = explorer.explore_actions()?;
+        node_store = explorer.into_node_store();
+        searches += 1;
+        match reached{
+            true => {
+                lower = target;
+                guess = target + 1;
+            },
+            false => {
+                upper = target - 1;
+                guess = target - 1;
+            }
+        }
+    }
+    debug!("MTD(f) finished after {searches} null-window searches with North-South tricks: {lower}.");
+    Ok((lower, node_store))
+}
+
+/// Solves contract from its current position with chosen optimiser and node store.
+/// Returns number of tricks taken by declarer in the whole deal.
+/// Positions opening trick are looked up in [`global_cache`](crate::cache::global_cache) first, when it is installed.
+pub fn solve_with<G: ActionOptimiser, A: NodeStoreTrait>(contract: &Contract, hands: &SideMap<CardSetStd>) -> Result<u8, DoubleDummyError>{
+    let node = TrickNode::new_in_contract(*hands, contract).map_err(BridgeCoreError::from)?;
+    let tricks_in_deal = contract.count_completed_tricks() as u8 + tricks_left(&node);
+    let north_south = match cached_north_south(contract, hands){
+        Some(north_south) => north_south,
+        None => {
+            let (north_south, _) = mtdf::<G, A>(contract.clone(), node, A::default())?;
+            cache_north_south(contract, hands, north_south);
+            north_south
+        }
+    };
+    Ok(match contract.declarer().axis(){
+        Axis::NorthSouth => north_south,
+        Axis::EastWest => tricks_in_deal - north_south
+    })
+}
+
+/// Solves contract from its current position (`hands` are cards not played yet),
+/// returning number of tricks taken by declarer in the whole deal.
+/// Uses MTD(f) driver with [`DefaultOptimiser`] and [`DefaultNodeStore`].
+/// # Example:
+/// ```
+/// use brydz_core::bidding::Bid;
+/// use brydz_core::cards::trump::TrumpGen;
+/// use brydz_core::contract::{Contract, ContractParametersGen};
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// let contract = Contract::new(
+///     ContractParametersGen::new(West, Bid::init(TrumpGen::Colored(Diamonds), 1).unwrap()));
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// assert_eq!(brydz_dd::solve(&contract, &hands), Ok(3));
+/// ```
+pub fn solve(contract: &Contract, hands: &SideMap<CardSetStd>) -> Result<u8, DoubleDummyError>{
+    solve_with::<DefaultOptimiser, DefaultNodeStore>(contract, hands)
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TRUMPS;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::deal::fair_bridge_partial_deal;
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, Jack, King, Queen};
+    use brydz_core::karty::set::{CardSet, CardSetStd};
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::side::Side::North;
+    use brydz_core::player::side::SIDES;
+    use brydz_core::player::axis::Axis;
+    use crate::actions::DistinctCardGrouper;
+    use crate::explore::{ExploreOutput, Explorer};
+    use crate::hash::DummyNodeStore;
+    use crate::node::TrickNode;
+    use crate::solve;
+
+    #[test]
+    fn mtdf_agrees_with_alpha_beta(){
+        let card_supply: Vec<Card> = Card::card_subset(vec![Ace, King, Queen, Jack], vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        for _ in 0..3{
+            let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply.clone(), North);
+            for strain in TRUMPS{
+                for declarer in SIDES{
+                    let parameters = ContractParametersGen::new(declarer, Bid::init(strain, 1).unwrap());
+                    let mut contract = Contract::new(parameters);
+                    let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+                    let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract.clone(), node).unwrap();
+                    let expected = match (explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap(), declarer.axis()){
+                        (ExploreOutput::Number(north_south), Axis::NorthSouth) => north_south,
+                        (ExploreOutput::Number(north_south), Axis::EastWest) => 4 - north_south,
+                        (other, _) => panic!("Unbounded result: {other:?}")
+                    };
+                    assert_eq!(solve(&contract, &hands), Ok(expected));
+                    // solved also from the middle of trick, opening lead is made by defender,
+                    // so declarer can not take less than with optimal lead
+                    let leader = contract.current_side();
+                    let lead = *card_supply.iter().find(|card| hands[&leader].contains(card)).unwrap();
+                    let mut partial_hands = hands;
+                    partial_hands[&leader].remove_card(&lead).unwrap();
+                    contract.insert_card(leader, lead).unwrap();
+                    assert!(solve(&contract, &partial_hands).unwrap() >= expected);
+                }
+            }
+        }
+    }
+}
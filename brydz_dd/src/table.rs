@@ -0,0 +1,148 @@
+use rayon::prelude::*;
+use brydz_core::bidding::Bid;
+use brydz_core::cards::trump::{Trump, TRUMPS};
+use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+use brydz_core::error::BridgeCoreError;
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::karty::suits::Suit;
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{SideMap, SIDES};
+use crate::actions::ActionOptimiser;
+use crate::cache::{global_cache, solve_contract_cached, solve_table_cached};
+use crate::error::DoubleDummyError;
+use crate::hash::NodeStoreTrait;
+use crate::node::TrickNode;
+use crate::solver::mtdf;
+
+pub use brydz_core::score::DoubleDummyTable;
+
+/// Solves contract from the opening lead with MTD(f) driver and given node store, returning tricks
+/// of declarer and the store to be reused.
+fn solve_with_store<G: ActionOptimiser, A: NodeStoreTrait>(hands: &SideMap<CardSetStd>, parameters: ContractParametersGen<Suit>, node_store: A) -> Result<(u8, A), DoubleDummyError>{
+    let tricks_in_deal = <CardSetStd as Into<u64>>::into(hands.north).count_ones() as u8;
+    let declarer = parameters.declarer();
+    let contract = Contract::new(parameters);
+    let node = TrickNode::new_checked(*hands, contract.current_side()).map_err(BridgeCoreError::from)?;
+    let (north_south, node_store) = mtdf::<G, A>(contract, node, node_store)?;
+    let tricks = match declarer.axis(){
+        Axis::NorthSouth => north_south,
+        Axis::EastWest => tricks_in_deal - north_south
+    };
+    Ok((tricks, node_store))
+}
+
+/// Solves single contract, returning number of tricks taken by declarer.
+/// Result is looked up in [`global_cache`] first, when it is installed.
+pub fn solve_contract<G: ActionOptimiser, A: NodeStoreTrait>(hands: &SideMap<CardSetStd>, parameters: ContractParametersGen<Suit>) -> Result<u8, DoubleDummyError>{
+    match global_cache(){
+        Some(cache) => solve_contract_cached::<G, A>(cache, hands, parameters),
+        None => solve_contract_uncached::<G, A>(hands, parameters)
+    }
+}
+
+pub(crate) fn solve_contract_uncached<G: ActionOptimiser, A: NodeStoreTrait>(hands: &SideMap<CardSetStd>, parameters: ContractParametersGen<Suit>) -> Result<u8, DoubleDummyError>{
+    solve_with_store::<G, A>(hands, parameters, A::default()).map(|(tricks, _)| tricks)
+}
+
+/// Solves every declarer in one strain. Values stored in node store do not depend on who was
+/// the opening leader, so single store is passed from one declarer to the next.
+pub fn solve_strain<G: ActionOptimiser, A: NodeStoreTrait>(hands: &SideMap<CardSetStd>, strain: Trump) -> Result<SideMap<u8>, DoubleDummyError>{
+    let mut node_store = A::default();
+    let mut tricks = SideMap::new_symmetric(0u8);
+    for declarer in SIDES{
+        let parameters = ContractParametersGen::new(declarer,
+            Bid::init(strain, 1).map_err(BridgeCoreError::from)?);
+        let (declarer_tricks, store) = solve_with_store::<G, A>(hands, parameters, node_store)?;
+        tricks[&declarer] = declarer_tricks;
+        node_store = store;
+    }
+    Ok(tricks)
+}
+
+/// Solves complete double dummy table (each declarer in each strain) of a deal.
+/// Strains are solved in parallel rayon tasks, so tables of many deals can be solved in parallel
+/// without oversubscribing cores. Table is looked up in [`global_cache`] first, when it is installed.
+/// ```
+/// use brydz_core::cards::trump::TrumpGen::Colored;
+/// use brydz_core::karty::{card_set, cards::*, suits::Suit::*};
+/// use brydz_core::player::side::{SideMap, Side::*};
+/// use brydz_dd::actions::DistinctCardGrouper;
+/// use brydz_dd::hash::DummyNodeStore;
+/// use brydz_dd::table::solve_table;
+/// let hands = SideMap::new(
+///     card_set![ACE_SPADES, QUEEN_SPADES, JACK_CLUBS, KING_CLUBS],
+///     card_set!(ACE_HEARTS, KING_DIAMONDS, KING_HEARTS, JACK_DIAMONDS),
+///     card_set![ACE_DIAMONDS, ACE_CLUBS, QUEEN_HEARTS, QUEEN_CLUBS],
+///     card_set![KING_SPADES, QUEEN_DIAMONDS, JACK_SPADES, JACK_HEARTS ]);
+/// let table = solve_table::<DistinctCardGrouper, DummyNodeStore>(&hands).unwrap();
+/// // West playing in diamonds, North leads
+/// assert_eq!(table.tricks(West, &Colored(Diamonds)), 3);
+/// ```
+pub fn solve_table<G, A>(hands: &SideMap<CardSetStd>) -> Result<DoubleDummyTable, DoubleDummyError>
+where G: ActionOptimiser + Send, A: NodeStoreTrait + Send{
+    match global_cache(){
+        Some(cache) => solve_table_cached::<G, A>(cache, hands),
+        None => solve_table_uncached::<G, A>(hands)
+    }
+}
+
+pub(crate) fn solve_table_uncached<G, A>(hands: &SideMap<CardSetStd>) -> Result<DoubleDummyTable, DoubleDummyError>
+where G: ActionOptimiser + Send, A: NodeStoreTrait + Send{
+    let strain_results: Vec<(Trump, Result<SideMap<u8>, DoubleDummyError>)> = TRUMPS.par_iter()
+        .map(|strain| (*strain, solve_strain::<G, A>(hands, *strain)))
+        .collect();
+
+    let mut table = DoubleDummyTable::default();
+    for (strain, result) in strain_results{
+        let tricks = result?;
+        for declarer in SIDES{
+            table.set_tricks(declarer, &strain, tricks[&declarer])?;
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TRUMPS;
+    use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+    use brydz_core::deal::fair_bridge_partial_deal;
+    use brydz_core::karty::cards::{Card, Card2SymTrait};
+    use brydz_core::karty::figures::{Ace, Jack, King, Queen};
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::axis::Axis;
+    use brydz_core::player::side::Side::North;
+    use brydz_core::player::side::SIDES;
+    use crate::actions::DistinctCardGrouper;
+    use crate::explore::{ExploreOutput, Explorer};
+    use crate::hash::DummyNodeStore;
+    use crate::node::TrickNode;
+    use crate::table::solve_table;
+
+    #[test]
+    fn table_matches_separate_explorers(){
+        let card_supply: Vec<Card> = Card::card_subset(
+            vec![Ace, King, Queen, Jack],
+            vec![Spades, Hearts, Diamonds, Clubs]).collect();
+        let hands = fair_bridge_partial_deal::<CardSetStd>(card_supply, North);
+        let table = solve_table::<DistinctCardGrouper, DummyNodeStore>(&hands).unwrap();
+        for declarer in SIDES{
+            for strain in TRUMPS{
+                let contract = Contract::new(ContractParametersGen::new(declarer, Bid::init(strain, 1).unwrap()));
+                let node = TrickNode::new_checked(hands, contract.current_side()).unwrap();
+                let mut explorer = Explorer::<DistinctCardGrouper, DummyNodeStore>::new_checked(contract, node).unwrap();
+                let north_south = match explorer.explore_actions(ExploreOutput::MinusInfinity, ExploreOutput::Infinity).unwrap(){
+                    ExploreOutput::Number(n) => n,
+                    other => panic!("Unbounded result: {other:?}")
+                };
+                let expected = match declarer.axis(){
+                    Axis::NorthSouth => north_south,
+                    Axis::EastWest => 4 - north_south
+                };
+                assert_eq!(table.tricks(declarer, &strain), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Positions shared by tests of several modules.
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use brydz_core::bidding::Bid;
+use brydz_core::cards::trump::TrumpGen;
+use brydz_core::contract::{Contract, ContractMechanics, ContractParametersGen};
+use brydz_core::deal::fair_bridge_deal_with_rng;
+use brydz_core::karty::cards::*;
+use brydz_core::karty::set::{CardSet, CardSetStd, HandSuitedTrait};
+use brydz_core::karty::suits::Suit::Hearts;
+use brydz_core::player::side::SideMap;
+use brydz_core::player::side::Side::South;
+
+/// Seeded random deal in two hearts by South, played with the lowest legal cards
+/// for `tricks_played` tricks. Returns contract and cards left in hands.
+pub(crate) fn late_position(tricks_played: usize) -> (Contract, SideMap<CardSetStd>){
+    let mut hands = fair_bridge_deal_with_rng::<CardSetStd, _>(&mut StdRng::seed_from_u64(5));
+    let mut contract = Contract::new(ContractParametersGen::new(South, Bid::init(TrumpGen::Colored(Hearts), 2).unwrap()));
+    for _ in 0..tricks_played * 4{
+        let side = contract.current_side();
+        let hand = &hands[&side];
+        let card = contract.current_trick().called_suit()
+            .filter(|suit| hand.contains_in_suit(suit))
+            .and_then(|suit| hand.suit_iterator(&suit).next())
+            .or_else(|| hand.into_iter().next())
+            .unwrap();
+        contract.insert_card(side, card).unwrap();
+        hands[&side].remove_card(&card).unwrap();
+    }
+    (contract, hands)
+}
+
+/// Eleven tricks in which every side followed suit, so no void is shown: clubs, diamonds and
+/// hearts from two to king and spades from two to nine. Dummy (North) wins the last one and
+/// leads, aces of minor suits and hearts and spades from ten up are left.
+pub(crate) fn two_tricks_left() -> Contract{
+    let mut contract = Contract::new(ContractParametersGen::new(South, Bid::init(TrumpGen::NoTrump, 1).unwrap()));
+    let tricks = [
+        [TWO_CLUBS, THREE_CLUBS, FOUR_CLUBS, FIVE_CLUBS],
+        [SIX_CLUBS, SEVEN_CLUBS, EIGHT_CLUBS, NINE_CLUBS],
+        [TEN_CLUBS, JACK_CLUBS, QUEEN_CLUBS, KING_CLUBS],
+        [TWO_DIAMONDS, THREE_DIAMONDS, FOUR_DIAMONDS, FIVE_DIAMONDS],
+        [SIX_DIAMONDS, SEVEN_DIAMONDS, EIGHT_DIAMONDS, NINE_DIAMONDS],
+        [TEN_DIAMONDS, JACK_DIAMONDS, QUEEN_DIAMONDS, KING_DIAMONDS],
+        [TWO_HEARTS, THREE_HEARTS, FOUR_HEARTS, FIVE_HEARTS],
+        [SIX_HEARTS, SEVEN_HEARTS, EIGHT_HEARTS, NINE_HEARTS],
+        [TEN_HEARTS, JACK_HEARTS, QUEEN_HEARTS, KING_HEARTS],
+        [TWO_SPADES, THREE_SPADES, FOUR_SPADES, FIVE_SPADES],
+        [SIX_SPADES, SEVEN_SPADES, EIGHT_SPADES, NINE_SPADES],
+    ];
+    for card in tricks.into_iter().flatten(){
+        contract.insert_card(contract.current_side(), card).unwrap();
+    }
+    contract
+}
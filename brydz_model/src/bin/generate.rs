@@ -2,13 +2,14 @@
 
 use std::path::PathBuf;
 use clap::Parser;
-use log::{debug, error, LevelFilter};
+use log::{debug, error, info, LevelFilter};
 use rand::{rng, Rng};
 use ron::ser::{to_string_pretty, PrettyConfig};
 use brydz_core::deal::{BiasedHandDistribution, DealDistribution};
 use brydz_model::options::{DataFormat, GenerateSubcommand};
 use std::io::Write;
 use brydz_model::generate::generate_contracts;
+use brydz_model::dataset::export_dataset;
 
 #[derive(Parser)]
 pub struct CliGenerationOptions {
@@ -144,6 +145,11 @@ fn main() -> anyhow::Result<()>{
 
             //anyhow::Result::Ok(());
         }
+        GenerateSubcommand::Dataset(dataset_options) => {
+            debug!("Dataset options: {:?}", dataset_options);
+            let records = export_dataset(&dataset_options)?;
+            info!("Written {records} records to {:?}", dataset_options.output);
+        }
     }
 
 
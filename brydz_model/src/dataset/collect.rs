@@ -0,0 +1,202 @@
+use std::fs::File;
+use std::io::BufWriter;
+use log::info;
+use amfiteatr_core::agent::{InformationSet, Policy};
+use amfiteatr_core::env::SequentialGameState;
+use amfiteatr_core::error::AmfiteatrError;
+use amfiteatr_core::scheme::Renew;
+use amfiteatr_rl::tensor_data::{ContextEncodeTensor, TensorEncoding};
+use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_core::amfiteatr::state::{ContractAction, ContractEnvStateComplete, ContractInfoSetEncoding, ContractInformationSet, ContractState};
+use brydz_core::contract::ContractMechanics;
+use brydz_core::deal::ContractGameDescription;
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use brydz_core::karty::set::CardSet;
+use brydz_dd::analysis::card_values_in_store;
+use brydz_dd::cache::{install_global_cache, DoubleDummyCache};
+use brydz_dd::solver::{DefaultNodeStore, DefaultOptimiser};
+use crate::dataset::{DatasetRecord, DatasetWriter};
+use crate::generate::generate_contracts;
+use crate::model::agent::{initial_information_set, tensor_encoding, BAgent};
+use crate::model::policy::ContractPolicy;
+use crate::options::contract::{AgentConfig, AgentPolicyInnerConfig, InformationSetRepresentation, InformationSetSelection, ModelConfig};
+use crate::options::contract_generation::GenContractOptions;
+use crate::options::dataset::DatasetOptions;
+
+/// Plays games with configured policies and records every card decision: information set of
+/// acting agent (of selected type, independent of information sets used by policies) and double
+/// dummy values of legal cards.
+pub struct DatasetCollector{
+    policies: SideMap<ContractPolicy>,
+    policy_views: SideMap<ContractInformationSet>,
+    recorded_views: SideMap<ContractInformationSet>,
+    encoding: ContractInfoSetEncoding,
+}
+
+impl DatasetCollector{
+    pub fn new(agents: &SideMap<AgentConfig>, recorded: InformationSetSelection, representation: &InformationSetRepresentation) -> anyhow::Result<Self>{
+        Ok(Self{
+            policies: SideMap::new(
+                BAgent::create_policy(&agents.north)?,
+                BAgent::create_policy(&agents.east)?,
+                BAgent::create_policy(&agents.south)?,
+                BAgent::create_policy(&agents.west)?),
+            policy_views: SideMap::new_with_fn(|side| initial_information_set(agents[&side].information_set_type, side)),
+            recorded_views: SideMap::new_with_fn(|side| initial_information_set(recorded, side)),
+            encoding: tensor_encoding(representation),
+        })
+    }
+
+    /// Number of values in encoded information set.
+    pub fn tensor_size(&self) -> usize{
+        self.encoding.desired_shape().iter().product::<i64>() as usize
+    }
+
+    /// Records decision of player, solving positions with node store of the game.
+    fn record(&self, env: &ContractEnvStateComplete, player: Side, node_store: DefaultNodeStore) -> anyhow::Result<(DatasetRecord, DefaultNodeStore)>{
+        let contract = env.contract_data();
+        let hands = SideMap::new_with_fn(|side| env[side]);
+        let tricks_in_deal = contract.count_completed_tricks() as u8
+            + SIDES.iter().map(|side| hands[side].len()).max().unwrap_or(0) as u8;
+        let (card_values, node_store) = card_values_in_store::<DefaultOptimiser, DefaultNodeStore>(contract, &hands, node_store)
+            .map_err(AmfiteatrError::<ContractDP>::from)?;
+        let card_values = card_values.into_iter()
+            .map(|(card, north_south)| (card, match player.axis(){
+                Axis::NorthSouth => north_south,
+                Axis::EastWest => tricks_in_deal - north_south
+            }))
+            .collect();
+        let information_set = self.recorded_views[&player].try_to_tensor(&self.encoding)?.try_into()?;
+        Ok((DatasetRecord{player, information_set, card_values}, node_store))
+    }
+
+    /// Plays game from description to the end, returning records of all card decisions.
+    pub fn play_game(&mut self, description: &ContractGameDescription) -> anyhow::Result<Vec<DatasetRecord>>{
+        let mut env = ContractEnvStateComplete::from(description);
+        for side in SIDES{
+            self.policy_views[&side].renew_from((&side, description))?;
+            self.recorded_views[&side].renew_from((&side, description))?;
+        }
+        let mut records = Vec::new();
+        // every position of game comes from the same deal, so bounds stored for one decision
+        // stay valid for the next ones
+        let mut node_store = DefaultNodeStore::default();
+        while let Some(player) = env.current_player(){
+            let action = self.policies[&player].select_action(&self.policy_views[&player])?;
+            if let ContractAction::PlaceCard(_) = action{
+                let (record, store) = self.record(&env, player, node_store)?;
+                records.push(record);
+                node_store = store;
+            }
+            for (side, update) in env.forward(player, action)?{
+                self.policy_views[&side].update(update)?;
+                self.recorded_views[&side].update(update)?;
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// Plays games given in options and writes dataset file. Returns number of written records.
+pub fn export_dataset(options: &DatasetOptions) -> anyhow::Result<u64>{
+    let agents = match &options.config{
+        None => SideMap::new_symmetric(AgentConfig{
+            policy: AgentPolicyInnerConfig::Heuristic,
+            information_set_type: InformationSetSelection::Simple,
+            ..AgentConfig::default()
+        }),
+        Some(path) => {
+            let s = std::fs::read_to_string(path)
+                .map_err(|_e| anyhow::format_err!("Can't open config file {path:?}"))?;
+            serde_yaml::from_str::<ModelConfig>(&s)?.agents
+        }
+    };
+    let descriptions: Vec<ContractGameDescription> = match &options.input{
+        Some(path) => {
+            let s = std::fs::read_to_string(path)
+                .map_err(|_e| anyhow::format_err!("Can't open game descriptions file {path:?}"))?;
+            ron::from_str(&s).or_else(|_| serde_yaml::from_str(&s))?
+        },
+        None => generate_contracts(&GenContractOptions{
+            game_count: options.game_count,
+            ..GenContractOptions::default()
+        })?
+    };
+
+    if let Some(path) = &options.dd_cache{
+        let cache = DoubleDummyCache::open(path)?;
+        info!("Loaded double dummy cache {path:?} with {} tables and {} positions", cache.tables_len(), cache.contracts_len());
+        install_global_cache(cache).map_err(|_| anyhow::format_err!("Double dummy cache is already installed"))?;
+    }
+    let mut collector = DatasetCollector::new(&agents, options.information_set_type, &options.information_set_conversion)?;
+    let file = BufWriter::new(File::create(&options.output)?);
+    let mut writer = DatasetWriter::new(file, &options.information_set_conversion, collector.tensor_size())?;
+    for (i, description) in descriptions.iter().enumerate(){
+        for record in collector.play_game(description)?{
+            writer.write_record(&record)?;
+        }
+        if (i + 1) % 100 == 0{
+            info!("Recorded {} games, {} decisions", i + 1, writer.records());
+        }
+    }
+    writer.flush()?;
+    Ok(writer.records())
+}
+
+#[cfg(test)]
+mod tests{
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::ContractParameters;
+    use brydz_core::deal::{ContractGameDescription, DealDistribution};
+    use brydz_core::karty::cards::{Card2SymTrait, STANDARD_DECK};
+    use brydz_core::karty::set::{CardSet, CardSetStd};
+    use brydz_core::karty::suits::Suit;
+    use brydz_core::karty::suits::Suit::{Clubs, Diamonds, Hearts, Spades};
+    use brydz_core::player::axis::Axis;
+    use brydz_core::player::side::SideMap;
+    use brydz_core::player::side::Side::{East, North, South, West};
+    use crate::dataset::DatasetCollector;
+    use crate::options::contract::{AgentConfig, AgentPolicyInnerConfig, InformationSetRepresentation, InformationSetSelection};
+
+    fn suit(suit: Suit) -> CardSetStd{
+        let mut cards = CardSetStd::empty();
+        for card in STANDARD_DECK.iter().filter(|card| card.suit() == suit){
+            cards.insert_card(*card).unwrap();
+        }
+        cards
+    }
+
+    #[test]
+    fn collector_records_every_card_decision(){
+        let agents = SideMap::new_symmetric(AgentConfig{
+            policy: AgentPolicyInnerConfig::Heuristic,
+            information_set_type: InformationSetSelection::Simple,
+            ..AgentConfig::default()
+        });
+        let mut collector = DatasetCollector::new(&agents, InformationSetSelection::Simple, &InformationSetRepresentation::Sparse).unwrap();
+        // North leads spades and takes every trick, whatever the others discard
+        let description = ContractGameDescription::new(
+            ContractParameters::new(West, Bid::init(TrumpGen::NoTrump, 1).unwrap()),
+            DealDistribution::Fair,
+            SideMap::new(suit(Spades), suit(Hearts), suit(Diamonds), suit(Clubs)));
+        let records = collector.play_game(&description).unwrap();
+        assert_eq!(records.len(), 52);
+        let count = |side| records.iter().filter(|record| record.player == side).count();
+        // declarer decides about cards of dummy
+        assert_eq!((count(North), count(East), count(South), count(West)), (13, 0, 13, 26));
+        for record in &records{
+            assert_eq!(record.information_set.len(), collector.tensor_size());
+            let expected = match record.player.axis(){
+                Axis::NorthSouth => 13,
+                Axis::EastWest => 0
+            };
+            assert!(!record.card_values.is_empty());
+            assert!(record.card_values.iter().all(|(_, value)| *value == expected));
+        }
+        // first lead is any spade of North
+        assert_eq!(records[0].player, North);
+        assert_eq!(records[0].card_values.len(), 13);
+    }
+}
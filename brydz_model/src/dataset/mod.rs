@@ -0,0 +1,5 @@
+mod record;
+mod collect;
+
+pub use record::*;
+pub use collect::*;
@@ -0,0 +1,232 @@
+use std::io::{ErrorKind, Read, Write};
+use brydz_core::karty::cards::{Card, STANDARD_DECK};
+use brydz_core::karty::set::{CardSet, CardSetStd};
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::player::side::{Side, SIDES};
+use crate::options::contract::InformationSetRepresentation;
+
+const DATASET_HEADER: &[u8; 8] = b"BRYDZSD\x01";
+const ILLEGAL_CARD: u8 = u8::MAX;
+
+/// Single decision of agent: encoded information set and double dummy values of legal cards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetRecord{
+    /// Agent deciding about card (declarer for cards of dummy).
+    pub player: Side,
+    pub information_set: Vec<f32>,
+    /// Tricks taken by axis of player in the whole deal after playing card, for every legal card.
+    pub card_values: Vec<(Card, u8)>,
+}
+
+impl DatasetRecord{
+    pub fn best_value(&self) -> Option<u8>{
+        self.card_values.iter().map(|(_, value)| *value).max()
+    }
+
+    /// Double dummy optimal cards, that is legal cards of the best value.
+    pub fn optimal_cards(&self) -> CardSetStd{
+        let mut cards = CardSetStd::empty();
+        if let Some(best) = self.best_value(){
+            for (card, value) in &self.card_values{
+                if *value == best{
+                    // card values are built from legal cards, so they have no duplicates
+                    let _ = cards.insert_card(*card);
+                }
+            }
+        }
+        cards
+    }
+}
+
+fn encoding_id(representation: &InformationSetRepresentation) -> u8{
+    match representation{
+        InformationSetRepresentation::Dense => 0,
+        InformationSetRepresentation::Sparse => 1,
+        InformationSetRepresentation::SparseHistoric => 2,
+    }
+}
+
+fn invalid_data(message: String) -> std::io::Error{
+    std::io::Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Header of dataset file.
+#[derive(Debug, Clone)]
+pub struct DatasetHeader{
+    pub representation: InformationSetRepresentation,
+    /// Number of `f32` values in every encoded information set.
+    pub tensor_size: usize,
+}
+
+/// Writes records in compact binary format (all numbers little endian):
+/// - header: `BRYDZSD\x01`, encoding of information set (`u8`: 0 - dense, 1 - sparse,
+///   2 - sparse historic), tensor size (`u32`);
+/// - every record has fixed size: player (`u8`, index of side), information set (tensor size of `f32`),
+///   mask of optimal cards (`u64`, the same as in `CardSetStd`) and value of every card of deck
+///   (52 of `u8` in order of card positions, `0xff` for cards that can not be played).
+pub struct DatasetWriter<W: Write>{
+    writer: W,
+    tensor_size: usize,
+    records: u64,
+}
+
+impl<W: Write> DatasetWriter<W>{
+    pub fn new(mut writer: W, representation: &InformationSetRepresentation, tensor_size: usize) -> std::io::Result<Self>{
+        writer.write_all(DATASET_HEADER)?;
+        writer.write_all(&[encoding_id(representation)])?;
+        writer.write_all(&(tensor_size as u32).to_le_bytes())?;
+        Ok(Self{writer, tensor_size, records: 0})
+    }
+
+    /// Number of records written.
+    pub fn records(&self) -> u64{
+        self.records
+    }
+
+    pub fn write_record(&mut self, record: &DatasetRecord) -> std::io::Result<()>{
+        if record.information_set.len() != self.tensor_size{
+            return Err(std::io::Error::new(ErrorKind::InvalidInput,
+                format!("Information set has {} values, dataset expects {}", record.information_set.len(), self.tensor_size)))
+        }
+        let mut bytes = Vec::with_capacity(1 + 4 * self.tensor_size + 8 + STANDARD_DECK.len());
+        bytes.push(record.player.index());
+        for value in &record.information_set{
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&u64::from(record.optimal_cards()).to_le_bytes());
+        let mut values = [ILLEGAL_CARD; 52];
+        for (card, value) in &record.card_values{
+            values[card.position()] = *value;
+        }
+        bytes.extend_from_slice(&values);
+        self.writer.write_all(&bytes)?;
+        self.records += 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()>{
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W{
+        self.writer
+    }
+}
+
+/// Fills buffer with next record. Returns `false` at the end of data,
+/// record cut in the middle is an error.
+fn read_record_bytes<R: Read>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<bool>{
+    let mut filled = 0;
+    while filled < buffer.len(){
+        match reader.read(&mut buffer[filled..]){
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            Err(e) => return Err(e)
+        }
+    }
+    match filled{
+        0 => Ok(false),
+        n if n == buffer.len() => Ok(true),
+        n => Err(std::io::Error::new(ErrorKind::UnexpectedEof,
+            format!("Truncated record at the end of dataset: {n} of {} bytes", buffer.len())))
+    }
+}
+
+/// Reads dataset written by [`DatasetWriter`].
+/// Fails when the last record is incomplete (for example file was not flushed).
+pub fn read_dataset<R: Read>(mut reader: R) -> std::io::Result<(DatasetHeader, Vec<DatasetRecord>)>{
+    let mut header = [0u8; 13];
+    reader.read_exact(&mut header)?;
+    if &header[..8] != DATASET_HEADER{
+        return Err(invalid_data("Not a brydz dataset file".to_string()))
+    }
+    let representation = match header[8]{
+        0 => InformationSetRepresentation::Dense,
+        1 => InformationSetRepresentation::Sparse,
+        2 => InformationSetRepresentation::SparseHistoric,
+        other => return Err(invalid_data(format!("Unknown information set encoding: {other}")))
+    };
+    let tensor_size = u32::from_le_bytes(header[9..13].try_into().unwrap()) as usize;
+
+    let mut records = Vec::new();
+    let mut buffer = vec![0u8; 1 + 4 * tensor_size + 8 + STANDARD_DECK.len()];
+    while read_record_bytes(&mut reader, &mut buffer)?{
+        let player = *SIDES.iter().find(|side| side.index() == buffer[0])
+            .ok_or_else(|| invalid_data(format!("Illegal player index: {}", buffer[0])))?;
+        let information_set = buffer[1..1 + 4 * tensor_size].chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        let values = &buffer[1 + 4 * tensor_size + 8..];
+        let card_values = STANDARD_DECK.iter()
+            .filter(|card| values[card.position()] != ILLEGAL_CARD)
+            .map(|card| (*card, values[card.position()]))
+            .collect();
+        records.push(DatasetRecord{player, information_set, card_values});
+    }
+    Ok((DatasetHeader{representation, tensor_size}, records))
+}
+
+#[cfg(test)]
+mod tests{
+    use std::io::ErrorKind;
+    use brydz_core::karty::cards::{Card, ACE_SPADES, KING_SPADES, TWO_CLUBS};
+    use brydz_core::karty::set::CardSet;
+    use brydz_core::karty::symbol::CardSymbol;
+    use brydz_core::player::side::Side::{East, North};
+    use crate::dataset::{read_dataset, DatasetRecord, DatasetWriter};
+    use crate::options::contract::InformationSetRepresentation;
+
+    fn records() -> Vec<DatasetRecord>{
+        vec![
+            DatasetRecord{player: North, information_set: vec![0.5, 1.0, -2.0],
+                card_values: vec![(ACE_SPADES, 9), (KING_SPADES, 9), (TWO_CLUBS, 7)]},
+            DatasetRecord{player: East, information_set: vec![0.0, 0.25, 3.0],
+                card_values: vec![(TWO_CLUBS, 4)]},
+        ]
+    }
+
+    fn sorted(mut card_values: Vec<(Card, u8)>) -> Vec<(Card, u8)>{
+        card_values.sort_by_key(|(card, _)| card.position());
+        card_values
+    }
+
+    #[test]
+    fn dataset_round_trip(){
+        let mut writer = DatasetWriter::new(Vec::new(), &InformationSetRepresentation::Dense, 3).unwrap();
+        for record in records(){
+            writer.write_record(&record).unwrap();
+        }
+        assert_eq!(writer.records(), 2);
+        let bytes = writer.into_inner();
+        let (header, read) = read_dataset(&bytes[..]).unwrap();
+        assert!(matches!(header.representation, InformationSetRepresentation::Dense));
+        assert_eq!(header.tensor_size, 3);
+        assert_eq!(read.len(), 2);
+        for (read, written) in read.into_iter().zip(records()){
+            assert_eq!(read.player, written.player);
+            assert_eq!(read.information_set, written.information_set);
+            assert_eq!(sorted(read.card_values.clone()), sorted(written.card_values));
+            assert_eq!(read.optimal_cards(), written.optimal_cards());
+        }
+        assert_eq!(records()[0].optimal_cards().len(), 2);
+    }
+
+    #[test]
+    fn truncated_record_is_error(){
+        let mut writer = DatasetWriter::new(Vec::new(), &InformationSetRepresentation::Sparse, 3).unwrap();
+        for record in records(){
+            writer.write_record(&record).unwrap();
+        }
+        let bytes = writer.into_inner();
+        let error = read_dataset(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn record_of_wrong_size_is_rejected(){
+        let mut writer = DatasetWriter::new(Vec::new(), &InformationSetRepresentation::Sparse, 4).unwrap();
+        assert_eq!(writer.write_record(&records()[0]).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(writer.records(), 0);
+    }
+}
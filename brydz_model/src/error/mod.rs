@@ -6,6 +6,7 @@ use amfiteatr_rl::tch::TchError;
 //use tensorflow::{SaveModelError, Status};
 use brydz_core::error::BridgeCoreError;
 use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_dd::error::DoubleDummyError;
 pub use gen::*;
 pub use simulation::*;
 use amfiteatr_core::error::{AmfiteatrError, ModelError};
@@ -38,6 +39,8 @@ pub enum BrydzModelError {
     IO(String),
     #[error("Locking mutex: {0}")]
     Mutex(String),
+    #[error("Double dummy error: {0}")]
+    DoubleDummy(DoubleDummyError),
 
 }
 
@@ -84,6 +87,12 @@ impl From<tensorflow::Status> for BrydzSimError{
 }
 
  */
+impl From<DoubleDummyError> for BrydzModelError {
+    fn from(value: DoubleDummyError) -> Self {
+        Self::DoubleDummy(value)
+    }
+}
+
 impl From<TchError> for BrydzModelError {
     fn from(value: TchError) -> Self {
         Self::Tch(value)
@@ -2,8 +2,11 @@ use ron::ser::{PrettyConfig, to_string_pretty};
 use brydz_core::bidding::{Bid, Doubling};
 use brydz_core::cards::trump::{Trump, TrumpGen};
 use brydz_core::contract::ContractParameters;
-use brydz_core::deal::{BiasedHandDistribution, ContractGameDescription, DealDistribution, fair_bridge_deal};
+use brydz_core::deal::{BiasedHandDistribution, ContractGameDescription, DealDistribution, fair_bridge_deal_with_rng};
 use brydz_core::player::side::Side;
+use brydz_core::score::tables::{ScoringPreset, ScoringTable};
+use brydz_dd::par::par_of_deal;
+use brydz_dd::solver::{DefaultNodeStore, DefaultOptimiser};
 use karty::set::CardSetStd;
 use karty::random::RandomSymbol;
 use karty::suits::Suit;
@@ -17,7 +20,7 @@ use crate::options::contract_generation::{ChoiceDoubling, ForceDeclarer, Subtrum
 use crate::options::contract_generation::GenContractOptions;
 use crate::options::DealMethod;
 
-fn generate_single_contract(params: &GenContractOptions, rng_source: &mut impl Rng) -> Result<ContractGameDescription, BrydzModelError>{
+pub(crate) fn generate_single_contract(params: &GenContractOptions, rng_source: &mut impl Rng) -> Result<ContractGameDescription, BrydzModelError>{
 
     if params.min_contract > params.max_contract {
         return Err(BrydzModelError::Gen(LowerBoundOverUpper {lower: params.min_contract, upper: params.max_contract }))
@@ -50,19 +53,32 @@ fn generate_single_contract(params: &GenContractOptions, rng_source: &mut impl R
 
 
     let (template, cards) = match params.deal_method{
-        DealMethod::Fair => (DealDistribution::Fair, fair_bridge_deal::<CardSetStd>()),
+        DealMethod::Fair => (DealDistribution::Fair, fair_bridge_deal_with_rng::<CardSetStd, _>(rng_source)),
 
         DealMethod::Biased => {
-            let mut rng = rng();
-            let distribution: BiasedHandDistribution = rng.random();
-            let cards = distribution.sample(&mut rng);
+            let distribution: BiasedHandDistribution = rng_source.random();
+            let cards = distribution.sample(rng_source);
             (DealDistribution::Biased(Box::new(distribution)), cards)
         }
     };
 
-    Ok(ContractGameDescription::new(contract_parameters, template, cards))
+    let description = ContractGameDescription::new(contract_parameters, template, cards)
+        .with_vulnerability(params.vulnerability.choose(rng_source));
+    match params.par{
+        None => Ok(description),
+        Some(scoring) => annotate_par(description, ScoringPreset::from(scoring).table())
+    }
+
 
+}
 
+/// Sets double dummy par score (from North-South perspective) of deal as reference score of board.
+/// Board does not record dealer, declarer of contract is assumed to open the bidding.
+pub fn annotate_par(mut description: ContractGameDescription, scoring: &ScoringTable) -> Result<ContractGameDescription, BrydzModelError>{
+    let par = par_of_deal::<DefaultOptimiser, DefaultNodeStore>(description.cards(), description.vulnerability(),
+                                                                description.parameters().declarer(), scoring)?;
+    description.set_reference_score(Some(par.score()));
+    Ok(description)
 }
 
 pub fn generate_contracts(params: &GenContractOptions) -> Result<Vec<ContractGameDescription>, BrydzModelError>{
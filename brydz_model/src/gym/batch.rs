@@ -0,0 +1,496 @@
+use rand::distr::Distribution;
+use rand::prelude::IndexedRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use amfiteatr_core::agent::{InformationSet, Policy};
+use amfiteatr_core::env::{EpochSummaryGen, GameSummaryGen, SequentialGameState};
+use amfiteatr_core::scheme::Renew;
+use amfiteatr_rl::policy::{LearningNetworkPolicyGeneric, PolicyHelperA2C, PolicyTrainHelperPPO};
+use amfiteatr_rl::tch::{self, Kind, Tensor};
+use amfiteatr_rl::tensor_data::ContextEncodeTensor;
+use amfiteatr_rl::torch_net::NeuralNetActorCritic;
+use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_core::amfiteatr::state::{ContractAction, ContractEnvStateComplete, ContractInfoSetEncoding, ContractInformationSet};
+use brydz_core::bidding::Bid;
+use brydz_core::cards::trump::Trump;
+use brydz_core::contract::ContractParameters;
+use brydz_core::deal::{ContractGameDescription, DealDistribution};
+use brydz_core::karty::cards::{Card, STANDARD_DECK};
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use brydz_core::score::RewardScheme;
+use karty::random::RandomSymbol;
+use crate::generate::annotate_par;
+use crate::model::agent::{initial_information_set, BAgent};
+use crate::model::policy::ContractPolicy;
+use crate::options::contract::{AgentConfig, InformationSetSelection};
+use crate::options::contract_generation::ChoiceVulnerability;
+
+/// Policy selecting actions for many information sets at once.
+pub trait BatchPolicy: Policy<ContractDP, InfoSetType = ContractInformationSet>{
+    /// Actions for information sets (in the same order). When `explore` is off policy should play
+    /// its best actions. Default implementation asks [`select_action`](Policy::select_action) for
+    /// every information set.
+    fn select_actions(&self, info_sets: &[&ContractInformationSet], _explore: bool) -> anyhow::Result<Vec<ContractAction>>{
+        select_each(self, info_sets)
+    }
+}
+
+fn select_each<P: Policy<ContractDP, InfoSetType = ContractInformationSet> + ?Sized>(policy: &P, info_sets: &[&ContractInformationSet]) -> anyhow::Result<Vec<ContractAction>>{
+    info_sets.iter()
+        .map(|info_set| policy.select_action(info_set).map_err(anyhow::Error::from))
+        .collect()
+}
+
+/// Cards ordered by card index, like card actions of network policies.
+fn cards_by_index() -> [Card; 52]{
+    let mut cards = STANDARD_DECK;
+    cards.sort_by_key(|card| card.usize_index());
+    cards
+}
+
+/// Evaluates network once for all information sets and samples cards from actor distributions
+/// (or selects most probable cards when `explore` is off).
+fn network_actions(network: &NeuralNetActorCritic, info_sets: &[&ContractInformationSet],
+    encoding: &ContractInfoSetEncoding, masking: bool, explore: bool) -> anyhow::Result<Vec<ContractAction>>{
+    let cards = cards_by_index();
+    let inputs = info_sets.iter()
+        .map(|info_set| info_set.try_to_tensor(encoding))
+        .collect::<Result<Vec<_>, _>>()?;
+    let input = Tensor::stack(&inputs, 0).to_device(network.device());
+    let mut logits = tch::no_grad(|| (network.net())(&input).actor);
+    if masking{
+        let illegal: Vec<bool> = info_sets.iter()
+            .flat_map(|info_set| cards.iter().map(|card| !info_set.is_action_valid(&ContractAction::PlaceCard(*card))))
+            .collect();
+        let illegal = Tensor::from_slice(&illegal)
+            .view([info_sets.len() as i64, cards.len() as i64])
+            .to_device(logits.device());
+        logits = logits.masked_fill(&illegal, f64::NEG_INFINITY);
+    }
+    let indices: Vec<i64> = match explore{
+        true => logits.softmax(-1, Kind::Float).multinomial(1, true).flatten(0, -1),
+        false => logits.argmax(-1, false)
+    }.try_into()?;
+    indices.into_iter()
+        .map(|i| cards.get(i as usize)
+            .map(|card| ContractAction::PlaceCard(*card))
+            .ok_or_else(|| anyhow::anyhow!("Network selected action {i} out of card range")))
+        .collect()
+}
+
+/// Network policies are evaluated with one batched tensor, using information set encoding of
+/// policy, other policies play information sets one by one.
+impl BatchPolicy for ContractPolicy{
+    fn select_actions(&self, info_sets: &[&ContractInformationSet], explore: bool) -> anyhow::Result<Vec<ContractAction>> {
+        if info_sets.iter().any(|info_set| info_set.is_dummy()){
+            return select_each(self, info_sets)
+        }
+        match self{
+            ContractPolicy::Ppo(policy) => network_actions(policy.network(), info_sets, policy.info_set_encoding(), false, explore),
+            ContractPolicy::MaskedPpo(policy) => network_actions(policy.network(), info_sets, policy.info_set_encoding(), true, explore),
+            ContractPolicy::A2C(policy) => network_actions(policy.network(), info_sets, policy.info_set_encoding(), false, explore),
+            ContractPolicy::MaskedA2C(policy) => network_actions(policy.network(), info_sets, policy.info_set_encoding(), true, explore),
+            ContractPolicy::DoubleDummy(_) | ContractPolicy::Heuristic(_) | ContractPolicy::Pimc(_) | ContractPolicy::Ismcts(_) =>
+                select_each(self, info_sets),
+        }
+    }
+}
+
+/// Where new games of [`BatchContractEnv`] come from.
+#[derive(Debug, Clone)]
+pub enum GameSource{
+    /// Random contract (declarer, trump and level up to 3) on deal sampled from one of distributions
+    /// (fair deal when empty), like learning games of model.
+    Distributions(Vec<DealDistribution>),
+    /// Games of test set, every one is played once after [`reset`](BatchContractEnv::reset).
+    /// When set is used up and its games are finished, next step starts it again.
+    TestSet(Vec<ContractGameDescription>),
+}
+
+impl Default for GameSource{
+    fn default() -> Self{
+        Self::Distributions(Vec::new())
+    }
+}
+
+struct BatchGame{
+    env: ContractEnvStateComplete,
+    views: SideMap<ContractInformationSet>,
+    violator: Option<Side>,
+}
+
+impl BatchGame{
+    fn is_finished(&self) -> bool{
+        self.violator.is_some() || self.env.is_finished()
+    }
+
+    fn apply(&mut self, player: Side, action: ContractAction) -> anyhow::Result<()>{
+        for (side, update) in self.env.forward(player, action)?{
+            self.views[&side].update(update)?;
+        }
+        Ok(())
+    }
+
+    /// Shows hand of dummy when it is on move.
+    fn show_dummy(&mut self) -> anyhow::Result<()>{
+        while let Some(player) = self.env.current_player(){
+            if !self.views[&player].is_dummy(){
+                break;
+            }
+            let action = ContractAction::ShowHand(self.env[player]);
+            self.apply(player, action)?;
+        }
+        Ok(())
+    }
+
+    /// Plays action of player, illegal action finishes game with player as violator.
+    fn play(&mut self, player: Side, action: ContractAction) -> anyhow::Result<()>{
+        if !self.views[&player].is_action_valid(&action){
+            self.violator = Some(player);
+            return Ok(())
+        }
+        self.apply(player, action)?;
+        self.show_dummy()
+    }
+
+    fn summary(&self) -> GameSummaryGen<ContractDP>{
+        let mut summary = GameSummaryGen::<ContractDP>::from(&self.env);
+        summary.set_violating_agent(self.violator);
+        summary
+    }
+}
+
+/// Batch of games played in one thread without communication channels. In every
+/// [`step`](Self::step) each game makes one move and games where seats of the same policy are on
+/// move are decided together with [`BatchPolicy::select_actions`] (one network evaluation per policy).
+/// Finished games are replaced with new ones from [`GameSource`]. Dummy shows hand automatically.
+pub struct BatchContractEnv<P: BatchPolicy = ContractPolicy>{
+    policies: Vec<P>,
+    seats: SideMap<Option<usize>>,
+    information_set_types: SideMap<InformationSetSelection>,
+    games: Vec<BatchGame>,
+    batch_size: usize,
+    source: GameSource,
+    next_test_game: usize,
+    reward_scheme: RewardScheme,
+    vulnerability: ChoiceVulnerability,
+    explore: bool,
+    rng: StdRng,
+}
+
+impl BatchContractEnv<ContractPolicy>{
+    /// Self-play: one policy built from config plays all seats.
+    pub fn from_agent_config(config: &AgentConfig, batch_size: usize, source: GameSource) -> anyhow::Result<Self>{
+        Ok(Self::new(batch_size, source)
+            .with_policy(BAgent::create_policy(config)?, &SIDES, config.information_set_type))
+    }
+
+    /// Every seat is played by own policy built from its config.
+    pub fn from_agent_configs(agents: &SideMap<AgentConfig>, batch_size: usize, source: GameSource) -> anyhow::Result<Self>{
+        let mut batch = Self::new(batch_size, source);
+        for side in SIDES{
+            let config = &agents[&side];
+            batch = batch.with_policy(BAgent::create_policy(config)?, &[side], config.information_set_type);
+        }
+        Ok(batch)
+    }
+}
+
+impl<P: BatchPolicy> BatchContractEnv<P>{
+    /// Every seat needs policy set with [`with_policy`](Self::with_policy) before [`reset`](Self::reset).
+    pub fn new(batch_size: usize, source: GameSource) -> Self{
+        Self{
+            policies: Vec::new(),
+            seats: SideMap::new_with_fn(|_| None),
+            information_set_types: SideMap::new_symmetric(InformationSetSelection::default()),
+            games: Vec::with_capacity(batch_size),
+            batch_size,
+            source,
+            next_test_game: 0,
+            reward_scheme: RewardScheme::default(),
+            vulnerability: ChoiceVulnerability::None,
+            explore: true,
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Policy playing seats, with type of information sets it uses. Seats played by the same
+    /// policy are decided in one batch.
+    pub fn with_policy(mut self, policy: P, seats: &[Side], information_set_type: InformationSetSelection) -> Self{
+        self.policies.push(policy);
+        for side in seats{
+            self.seats[side] = Some(self.policies.len() - 1);
+            self.information_set_types[side] = information_set_type;
+        }
+        self
+    }
+
+    pub fn with_reward_scheme(mut self, reward_scheme: RewardScheme) -> Self{
+        self.reward_scheme = reward_scheme;
+        self
+    }
+
+    /// Vulnerability of games generated from distributions.
+    pub fn with_vulnerability(mut self, vulnerability: ChoiceVulnerability) -> Self{
+        self.vulnerability = vulnerability;
+        self
+    }
+
+    /// Seed of random games and contracts generated from distributions.
+    pub fn with_seed(mut self, seed: u64) -> Self{
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn batch_size(&self) -> usize{
+        self.batch_size
+    }
+
+    pub fn policies(&self) -> impl Iterator<Item = &P>{
+        self.policies.iter()
+    }
+
+    pub fn policies_mut(&mut self) -> impl Iterator<Item = &mut P>{
+        self.policies.iter_mut()
+    }
+
+    /// States of games currently played.
+    pub fn states(&self) -> impl Iterator<Item = &ContractEnvStateComplete>{
+        self.games.iter().map(|game| &game.env)
+    }
+
+    /// Description of next game, `None` when test set is used up.
+    fn next_description(&mut self) -> anyhow::Result<Option<ContractGameDescription>>{
+        match &self.source{
+            GameSource::TestSet(games) => {
+                let description = games.get(self.next_test_game).cloned();
+                self.next_test_game += 1;
+                Ok(description)
+            },
+            GameSource::Distributions(distributions) => {
+                let declarer = Side::random(&mut self.rng);
+                let trump = Trump::random(&mut self.rng);
+                let parameters = ContractParameters::new(declarer, Bid::init(trump, self.rng.random_range(1..=3))?);
+                let distribution = distributions.choose(&mut self.rng).cloned().unwrap_or(DealDistribution::Fair);
+                let cards = distribution.sample(&mut self.rng);
+                let description = ContractGameDescription::new(parameters, distribution, cards)
+                    .with_vulnerability(self.vulnerability.choose(&mut self.rng));
+                match self.reward_scheme.reference_scoring(){
+                    None => Ok(Some(description)),
+                    Some(scoring) => Ok(Some(annotate_par(description, scoring.table())?))
+                }
+            }
+        }
+    }
+
+    fn new_game(&mut self) -> anyhow::Result<Option<BatchGame>>{
+        let description = match self.next_description()?{
+            Some(description) => description,
+            None => return Ok(None)
+        };
+        let mut env = ContractEnvStateComplete::from(&description);
+        env.set_reward_scheme(self.reward_scheme.clone());
+        let mut views = SideMap::new_with_fn(|side| initial_information_set(self.information_set_types[&side], side));
+        for side in SIDES{
+            views[&side].renew_from((&side, &description))?;
+        }
+        let mut game = BatchGame{env, views, violator: None};
+        game.show_dummy()?;
+        Ok(Some(game))
+    }
+
+    /// Starts new batch of games (test set is played again from the first game, batch is smaller
+    /// when test set has less games than batch size).
+    pub fn reset(&mut self) -> anyhow::Result<()>{
+        if let Some(side) = SIDES.into_iter().find(|side| self.seats[side].is_none()){
+            return Err(anyhow::anyhow!("Seat {side:?} has no policy"))
+        }
+        if matches!(&self.source, GameSource::TestSet(games) if games.is_empty()){
+            return Err(anyhow::anyhow!("Test set of batch environment is empty"))
+        }
+        if let GameSource::TestSet(games) = &self.source{
+            if self.reward_scheme.reference_scoring().is_some() && games.iter().any(|game| game.reference_score().is_none()){
+                return Err(anyhow::anyhow!("Reward scheme {:?} requires reference score of every test game", self.reward_scheme))
+            }
+        }
+        self.next_test_game = 0;
+        self.games.clear();
+        for _ in 0..self.batch_size{
+            match self.new_game()?{
+                Some(game) => self.games.push(game),
+                None => break
+            }
+        }
+        Ok(())
+    }
+
+    /// Switches exploration of network policies: with exploration off they play the most probable
+    /// cards instead of sampling.
+    pub fn switch_explore(&mut self, enabled: bool)
+    where P: LearningNetworkPolicyGeneric<ContractDP>{
+        self.explore = enabled;
+        for policy in &mut self.policies{
+            policy.switch_explore(enabled);
+        }
+    }
+
+    /// Makes one move in every game and replaces finished games with new ones (finished games of
+    /// used up test set are dropped). When no game is left, batch is reset first.
+    /// Returns summaries of games finished in this step.
+    pub fn step(&mut self) -> anyhow::Result<Vec<GameSummaryGen<ContractDP>>>{
+        if self.games.is_empty(){
+            self.reset()?;
+        }
+        let mut groups: Vec<Vec<(usize, Side)>> = vec![Vec::new(); self.policies.len()];
+        for (index, game) in self.games.iter().enumerate(){
+            if let Some(player) = game.env.current_player(){
+                let policy = self.seats[&player]
+                    .ok_or_else(|| anyhow::anyhow!("Seat {player:?} has no policy"))?;
+                groups[policy].push((index, player));
+            }
+        }
+        for (group, policy) in groups.iter().zip(self.policies.iter()){
+            if group.is_empty(){
+                continue;
+            }
+            let info_sets: Vec<&ContractInformationSet> = group.iter()
+                .map(|(index, player)| &self.games[*index].views[player])
+                .collect();
+            let actions = policy.select_actions(&info_sets, self.explore)?;
+            for ((index, player), action) in group.iter().zip(actions){
+                self.games[*index].play(*player, action)?;
+            }
+        }
+
+        let mut finished = Vec::new();
+        let mut index = 0;
+        while index < self.games.len(){
+            if !self.games[index].is_finished(){
+                index += 1;
+                continue;
+            }
+            finished.push(self.games[index].summary());
+            match self.new_game()?{
+                Some(game) => {
+                    self.games[index] = game;
+                    index += 1;
+                },
+                None => {
+                    self.games.remove(index);
+                }
+            }
+        }
+        Ok(finished)
+    }
+
+    /// Steps batch until at least given number of games is finished. Games in progress are
+    /// continued in next calls, every game finished in the last step is reported (so there may
+    /// be more summaries than requested).
+    pub fn finish_games(&mut self, number_of_games: usize) -> anyhow::Result<Vec<GameSummaryGen<ContractDP>>>{
+        let mut summaries = Vec::with_capacity(number_of_games);
+        while summaries.len() < number_of_games{
+            summaries.extend(self.step()?);
+        }
+        Ok(summaries)
+    }
+
+    /// Summary of games played with [`finish_games`](Self::finish_games).
+    pub fn play_games(&mut self, number_of_games: usize) -> anyhow::Result<EpochSummaryGen<ContractDP>>{
+        Ok(EpochSummaryGen::new(self.finish_games(number_of_games)?))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use brydz_core::amfiteatr::HeuristicPolicy;
+    use brydz_core::amfiteatr::state::{ContractEnvStateComplete, ContractState};
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::{ContractMechanics, ContractParameters};
+    use brydz_core::deal::{fair_bridge_deal_with_rng, ContractGameDescription, DealDistribution};
+    use brydz_core::karty::set::CardSetStd;
+    use brydz_core::player::side::{SIDES, Side::*};
+    use crate::gym::{BatchContractEnv, GameSource};
+    use crate::model::policy::ContractPolicy;
+    use crate::options::contract::{AgentConfig, InformationSetSelection};
+
+    fn heuristic_batch(batch_size: usize, source: GameSource) -> BatchContractEnv{
+        BatchContractEnv::new(batch_size, source)
+            .with_policy(ContractPolicy::Heuristic(HeuristicPolicy::new()), &SIDES, InformationSetSelection::Simple)
+            .with_seed(5)
+    }
+
+    fn test_set() -> Vec<ContractGameDescription>{
+        let mut rng = StdRng::seed_from_u64(8);
+        [North, East, South].into_iter()
+            .map(|declarer| ContractGameDescription::new(
+                ContractParameters::new(declarer, Bid::init(TrumpGen::NoTrump, 2).unwrap()),
+                DealDistribution::Fair,
+                fair_bridge_deal_with_rng::<CardSetStd, _>(&mut rng)))
+            .collect()
+    }
+
+    #[test]
+    fn step_replaces_finished_games(){
+        let mut batch = heuristic_batch(3, GameSource::default());
+        // first step starts games
+        assert!(batch.step().unwrap().is_empty());
+        assert_eq!(batch.states().count(), 3);
+        for state in batch.states(){
+            assert!((1..=3).contains(&state.contract_data().contract_spec().bid().number()));
+        }
+        // every step places one card in every game
+        for _ in 1..51{
+            assert!(batch.step().unwrap().is_empty());
+        }
+        assert_eq!(batch.step().unwrap().len(), 3);
+        assert_eq!(batch.states().count(), 3);
+        assert!(batch.states().all(|state| state.contract_data().count_completed_tricks() == 0));
+
+        // games finished together are all reported
+        assert_eq!(batch.finish_games(4).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_set_is_played_once_per_reset(){
+        let games = test_set();
+        let mut batch = heuristic_batch(2, GameSource::TestSet(games.clone()));
+        batch.reset().unwrap();
+        let mut finished = 0;
+        while finished < games.len(){
+            finished += batch.step().unwrap().len();
+            assert!(batch.states().count() <= 2);
+        }
+        assert_eq!(finished, 3);
+        assert_eq!(batch.states().count(), 0);
+        // set is started again
+        assert!(batch.step().unwrap().is_empty());
+        let declarers: Vec<_> = batch.states().map(|state| state.contract_data().contract_spec().declarer()).collect();
+        assert_eq!(declarers, vec![North, East]);
+
+        let start = ContractEnvStateComplete::from(&games[0]);
+        assert_eq!(batch.states().next().unwrap().contract_data().contract_spec(), start.contract_data().contract_spec());
+
+        let mut empty = heuristic_batch(2, GameSource::TestSet(Vec::new()));
+        assert!(empty.reset().is_err());
+    }
+
+    #[test]
+    fn masked_network_plays_legal_cards_in_batch(){
+        let mut batch = BatchContractEnv::from_agent_config(&AgentConfig::default(), 4, GameSource::default())
+            .unwrap()
+            .with_seed(1);
+        for explore in [true, false]{
+            batch.switch_explore(explore);
+            batch.reset().unwrap();
+            // illegal card would finish game early
+            for _ in 0..51{
+                assert!(batch.step().unwrap().is_empty());
+            }
+            assert_eq!(batch.step().unwrap().len(), 4);
+        }
+    }
+}
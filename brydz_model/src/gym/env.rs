@@ -0,0 +1,421 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use amfiteatr_core::agent::{InformationSet, Policy};
+use amfiteatr_core::env::{GameStateWithPayoffs, SequentialGameState};
+use amfiteatr_core::scheme::Renew;
+use amfiteatr_rl::tensor_data::{ContextEncodeTensor, TensorEncoding};
+use brydz_core::amfiteatr::spec::ContractDP;
+use brydz_core::amfiteatr::state::{ContractAction, ContractEnvStateComplete, ContractInfoSetEncoding, ContractInformationSet, ContractState};
+use brydz_core::contract::ContractMechanics;
+use brydz_core::deal::ContractGameDescription;
+use brydz_core::karty::cards::{Card, STANDARD_DECK};
+use brydz_core::karty::symbol::CardSymbol;
+use brydz_core::player::axis::Axis;
+use brydz_core::player::side::{Side, SideMap, SIDES};
+use brydz_core::score::RewardScheme;
+use crate::generate::generate_single_contract;
+use crate::model::agent::{initial_information_set, tensor_encoding, BAgent};
+use crate::model::policy::ContractPolicy;
+use crate::options::contract::{AgentConfig, InformationSetRepresentation, InformationSetSelection};
+use crate::options::contract_generation::GenContractOptions;
+
+/// Seats played by user of [`ContractGymEnv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlledSeats{
+    Seat(Side),
+    /// Both seats of axis, declarer plays also cards of dummy.
+    Partnership(Axis),
+}
+
+impl ControlledSeats{
+    pub fn controls(&self, side: Side) -> bool{
+        match self{
+            ControlledSeats::Seat(seat) => *seat == side,
+            ControlledSeats::Partnership(axis) => side.axis() == *axis
+        }
+    }
+
+    pub fn axis(&self) -> Axis{
+        match self{
+            ControlledSeats::Seat(seat) => seat.axis(),
+            ControlledSeats::Partnership(axis) => *axis
+        }
+    }
+}
+
+/// Card played by controlled agent in [`ContractGymEnv::step`], given directly or as index in
+/// action space (index of card in deck).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GymAction{
+    Card(Card),
+    Index(usize),
+}
+
+impl From<Card> for GymAction{
+    fn from(card: Card) -> Self{
+        GymAction::Card(card)
+    }
+}
+
+impl From<usize> for GymAction{
+    fn from(index: usize) -> Self{
+        GymAction::Index(index)
+    }
+}
+
+/// What controlled agent sees before decision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GymObservation{
+    /// Controlled agent that observes (and decides, unless game is finished).
+    pub player: Side,
+    /// Encoded information set of player.
+    pub information_set: Vec<f32>,
+    /// Legal cards, indexed like card actions of policies (by card index in deck).
+    /// All are `false` when game is finished.
+    pub action_mask: Vec<bool>,
+}
+
+/// Additional information returned with every step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GymStepInfo{
+    /// Tricks taken by axis of controlled seats.
+    pub tricks_taken: u32,
+    pub completed_tricks: usize,
+    /// Payoff of controlled axis in current state (final payoff when game is finished).
+    pub payoff: i32,
+}
+
+/// Synchronous single agent environment: user plays controlled seats with [`reset`](Self::reset)
+/// and [`step`](Self::step), other seats are played in the same thread by given policies.
+/// Dummy shows hand automatically. Reward of step is change of payoff of controlled axis
+/// (with environment's reward scheme), so rewards of game sum up to its final payoff.
+pub struct ContractGymEnv<P: Policy<ContractDP, InfoSetType = ContractInformationSet> = ContractPolicy>{
+    controlled: ControlledSeats,
+    policies: SideMap<Option<P>>,
+    views: SideMap<ContractInformationSet>,
+    encoding: ContractInfoSetEncoding,
+    env: ContractEnvStateComplete,
+    reward_scheme: RewardScheme,
+    contract_options: GenContractOptions,
+    observer: Side,
+    payoff: i32,
+}
+
+impl ContractGymEnv<ContractPolicy>{
+    /// Builds policies of not controlled seats from their agent configs.
+    pub fn from_agent_configs(controlled: ControlledSeats, agents: &SideMap<AgentConfig>,
+        observed: InformationSetSelection, representation: &InformationSetRepresentation) -> anyhow::Result<Self>{
+        let mut gym = Self::new(controlled, observed, representation);
+        for side in SIDES{
+            if !controlled.controls(side){
+                gym = gym.with_policy(side, BAgent::create_policy(&agents[&side])?, agents[&side].information_set_type);
+            }
+        }
+        Ok(gym)
+    }
+}
+
+impl<P: Policy<ContractDP, InfoSetType = ContractInformationSet>> ContractGymEnv<P>{
+    /// Controlled seats observe information sets of type `observed`. Every other seat needs policy
+    /// set with [`with_policy`](Self::with_policy) before reset.
+    pub fn new(controlled: ControlledSeats, observed: InformationSetSelection, representation: &InformationSetRepresentation) -> Self{
+        let observer = match controlled{
+            ControlledSeats::Seat(seat) => seat,
+            ControlledSeats::Partnership(Axis::NorthSouth) => Side::North,
+            ControlledSeats::Partnership(Axis::EastWest) => Side::East,
+        };
+        Self{
+            controlled,
+            policies: SideMap::new_with_fn(|_| None),
+            views: SideMap::new_with_fn(|side| initial_information_set(observed, side)),
+            encoding: tensor_encoding(representation),
+            env: ContractEnvStateComplete::default(),
+            reward_scheme: RewardScheme::default(),
+            contract_options: GenContractOptions::default(),
+            observer,
+            payoff: 0,
+        }
+    }
+
+    /// Policy of not controlled seat and type of information set it uses.
+    pub fn with_policy(mut self, side: Side, policy: P, information_set_type: InformationSetSelection) -> Self{
+        self.policies[&side] = Some(policy);
+        self.views[&side] = initial_information_set(information_set_type, side);
+        self
+    }
+
+    pub fn with_reward_scheme(mut self, reward_scheme: RewardScheme) -> Self{
+        self.reward_scheme = reward_scheme;
+        self
+    }
+
+    /// Options of contracts generated in [`reset`](Self::reset).
+    pub fn with_contract_options(mut self, contract_options: GenContractOptions) -> Self{
+        self.contract_options = contract_options;
+        self
+    }
+
+    pub fn controlled(&self) -> ControlledSeats{
+        self.controlled
+    }
+
+    pub fn state(&self) -> &ContractEnvStateComplete{
+        &self.env
+    }
+
+    /// Number of values in encoded information set.
+    pub fn observation_size(&self) -> usize{
+        self.encoding.desired_shape().iter().product::<i64>() as usize
+    }
+
+    /// Number of card actions, length of action mask.
+    pub fn action_space(&self) -> usize{
+        STANDARD_DECK.len()
+    }
+
+    pub fn is_done(&self) -> bool{
+        self.env.is_finished()
+    }
+
+    /// Starts new game generated (with contract options) from seed.
+    pub fn reset(&mut self, seed: u64) -> anyhow::Result<GymObservation>{
+        let description = generate_single_contract(&self.contract_options, &mut StdRng::seed_from_u64(seed))?;
+        self.reset_with(&description)
+    }
+
+    /// Starts game from description. Payoff counted by rewards starts from zero.
+    pub fn reset_with(&mut self, description: &ContractGameDescription) -> anyhow::Result<GymObservation>{
+        if let Some(side) = SIDES.into_iter().find(|side| !self.controlled.controls(*side) && self.policies[side].is_none()){
+            return Err(anyhow::anyhow!("Seat {side:?} is not controlled and has no policy"))
+        }
+        if self.reward_scheme.reference_scoring().is_some() && description.reference_score().is_none(){
+            return Err(anyhow::anyhow!("Reward scheme {:?} requires reference score of board (see contract options' par)", self.reward_scheme))
+        }
+        self.env = ContractEnvStateComplete::from(description);
+        self.env.set_reward_scheme(self.reward_scheme.clone());
+        for side in SIDES{
+            self.views[&side].renew_from((&side, description))?;
+        }
+        self.advance()?;
+        self.payoff = 0;
+        self.observation()
+    }
+
+    /// Plays card of controlled agent on move and then other seats until next decision of controlled
+    /// agent or end of game. Returns observation, reward, whether game is finished and step info.
+    /// Card not allowed by [`action_mask`](Self::action_mask) is an error and does not change game.
+    pub fn step(&mut self, action: impl Into<GymAction>) -> anyhow::Result<(GymObservation, f32, bool, GymStepInfo)>{
+        let player = self.env.current_player()
+            .ok_or_else(|| anyhow::anyhow!("Step in finished game, reset is needed"))?;
+        let card = match action.into(){
+            GymAction::Card(card) => card,
+            GymAction::Index(index) => Card::from_usize_index(index)
+                .map_err(|e| anyhow::anyhow!("Bad index of card action {index}: {e}"))?
+        };
+        if !self.action_mask()[card.usize_index()]{
+            anyhow::bail!("Card {card} is not legal for {player:?}")
+        }
+        self.apply(player, ContractAction::PlaceCard(card))?;
+        self.advance()?;
+        let payoff = self.current_payoff();
+        let reward = (payoff - self.payoff) as f32;
+        self.payoff = payoff;
+        let info = GymStepInfo{
+            tricks_taken: self.env.contract_data().total_tricks_taken_axis(self.controlled.axis()),
+            completed_tricks: self.env.contract_data().count_completed_tricks(),
+            payoff,
+        };
+        Ok((self.observation()?, reward, self.is_done(), info))
+    }
+
+    /// Legal card actions of controlled agent on move (all `false` when game is finished).
+    pub fn action_mask(&self) -> Vec<bool>{
+        let mut mask = vec![false; STANDARD_DECK.len()];
+        if !self.is_done(){
+            let view = &self.views[&self.observer];
+            for card in STANDARD_DECK{
+                mask[card.usize_index()] = view.is_action_valid(&ContractAction::PlaceCard(card));
+            }
+        }
+        mask
+    }
+
+    fn current_payoff(&self) -> i32{
+        self.env.state_payoff_of_player(&self.observer)
+    }
+
+    fn observation(&self) -> anyhow::Result<GymObservation>{
+        Ok(GymObservation{
+            player: self.observer,
+            information_set: self.views[&self.observer].try_to_tensor(&self.encoding)?.try_into()?,
+            action_mask: self.action_mask(),
+        })
+    }
+
+    fn apply(&mut self, player: Side, action: ContractAction) -> anyhow::Result<()>{
+        for (side, update) in self.env.forward(player, action)?{
+            self.views[&side].update(update)?;
+        }
+        Ok(())
+    }
+
+    /// Shows dummy and plays seats of policies until controlled agent has to play card.
+    fn advance(&mut self) -> anyhow::Result<()>{
+        while let Some(player) = self.env.current_player(){
+            if self.views[&player].is_dummy(){
+                let action = ContractAction::ShowHand(self.env[player]);
+                self.apply(player, action)?;
+                continue;
+            }
+            if self.controlled.controls(player){
+                self.observer = player;
+                return Ok(())
+            }
+            let action = match &self.policies[&player]{
+                Some(policy) => policy.select_action(&self.views[&player])?,
+                None => return Err(anyhow::anyhow!("Seat {player:?} is not controlled and has no policy"))
+            };
+            self.apply(player, action)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use amfiteatr_core::env::GameStateWithPayoffs;
+    use brydz_core::amfiteatr::HeuristicPolicy;
+    use brydz_core::amfiteatr::state::ContractState;
+    use brydz_core::bidding::Bid;
+    use brydz_core::cards::trump::TrumpGen;
+    use brydz_core::contract::{ContractMechanics, ContractParameters};
+    use brydz_core::deal::{fair_bridge_deal_with_rng, ContractGameDescription, DealDistribution};
+    use brydz_core::karty::cards::STANDARD_DECK;
+    use brydz_core::karty::set::{CardSet, CardSetStd};
+    use brydz_core::karty::symbol::CardSymbol;
+    use brydz_core::player::axis::Axis;
+    use brydz_core::player::side::Side::{East, North, South, West};
+    use brydz_core::player::side::SIDES;
+    use brydz_core::score::RewardScheme;
+    use brydz_core::score::tables::ScoringPreset;
+    use brydz_dd::play::legal_cards;
+    use crate::gym::{ContractGymEnv, ControlledSeats};
+    use crate::model::policy::ContractPolicy;
+    use crate::options::contract::{InformationSetRepresentation, InformationSetSelection};
+
+    fn heuristic_gym(controlled: ControlledSeats) -> ContractGymEnv{
+        let mut gym = ContractGymEnv::new(controlled, InformationSetSelection::Simple, &InformationSetRepresentation::Sparse);
+        for side in SIDES{
+            if !controlled.controls(side){
+                gym = gym.with_policy(side, ContractPolicy::Heuristic(HeuristicPolicy::new()), InformationSetSelection::Simple);
+            }
+        }
+        gym
+    }
+
+    /// Mask expected from cards of side on move in environment.
+    fn legal_mask(gym: &ContractGymEnv) -> Vec<bool>{
+        let contract = gym.state().contract_data();
+        let hand = gym.state()[contract.current_side()];
+        let mut mask = vec![false; STANDARD_DECK.len()];
+        for card in legal_cards(contract, &hand){
+            mask[card.usize_index()] = true;
+        }
+        mask
+    }
+
+    /// West declares, so North leads and East is dummy.
+    fn west_declares() -> ContractGameDescription{
+        ContractGameDescription::new(
+            ContractParameters::new(West, Bid::init(TrumpGen::NoTrump, 3).unwrap()),
+            DealDistribution::Fair,
+            fair_bridge_deal_with_rng::<CardSetStd, _>(&mut StdRng::seed_from_u64(7)))
+    }
+
+    fn first_legal(mask: &[bool]) -> usize{
+        mask.iter().position(|legal| *legal).unwrap()
+    }
+
+    #[test]
+    fn reset_is_reproducible(){
+        let mut gym = heuristic_gym(ControlledSeats::Seat(North));
+        let observation = gym.reset(11).unwrap();
+        let contract = gym.state().contract_data().clone();
+        let hands = SIDES.map(|side| gym.state()[side]);
+        assert_eq!(gym.reset(11).unwrap(), observation);
+        assert_eq!(gym.state().contract_data(), &contract);
+        assert_eq!(SIDES.map(|side| gym.state()[side]), hands);
+        assert_eq!(observation.information_set.len(), gym.observation_size());
+    }
+
+    #[test]
+    fn mask_allows_legal_cards(){
+        let mut gym = heuristic_gym(ControlledSeats::Seat(North));
+        let mut observation = gym.reset_with(&west_declares()).unwrap();
+        while !gym.is_done(){
+            assert_eq!(observation.player, North);
+            assert_eq!(observation.action_mask, legal_mask(&gym));
+            let illegal = observation.action_mask.iter().position(|legal| !*legal).unwrap();
+            assert!(gym.step(illegal).is_err());
+            assert_eq!(gym.action_mask(), observation.action_mask);
+            observation = gym.step(first_legal(&observation.action_mask)).unwrap().0;
+        }
+        assert!(observation.action_mask.iter().all(|legal| !*legal));
+        assert!(gym.step(0).is_err());
+    }
+
+    #[test]
+    fn declaring_partnership_plays_cards_of_dummy(){
+        let mut gym = heuristic_gym(ControlledSeats::Partnership(Axis::EastWest));
+        let observation = gym.reset_with(&west_declares()).unwrap();
+        // North led, dummy has shown hand and declarer plays its card
+        assert_eq!(gym.state().contract_data().current_side(), East);
+        assert_eq!(observation.player, West);
+        assert_eq!(observation.action_mask, legal_mask(&gym));
+        let card = STANDARD_DECK.into_iter().find(|card| observation.action_mask[card.usize_index()]).unwrap();
+        assert!(gym.state()[East].contains(&card));
+        gym.step(card).unwrap();
+        assert!(!gym.state()[East].contains(&card));
+    }
+
+    #[test]
+    fn rewards_sum_up_to_final_payoff(){
+        let mut gym = heuristic_gym(ControlledSeats::Partnership(Axis::EastWest));
+        let mut observation = gym.reset_with(&west_declares()).unwrap();
+        let mut total = 0.0;
+        loop{
+            let (next, reward, done, info) = gym.step(first_legal(&observation.action_mask)).unwrap();
+            total += reward;
+            observation = next;
+            if done{
+                assert_eq!(info.completed_tricks, 13);
+                assert_eq!(total, info.payoff as f32);
+                assert_eq!(info.payoff, gym.state().state_payoff_of_player(&West));
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn seat_without_policy_is_error(){
+        let mut gym = ContractGymEnv::<ContractPolicy>::new(ControlledSeats::Seat(North),
+            InformationSetSelection::Simple, &InformationSetRepresentation::Sparse);
+        assert!(gym.reset(0).is_err());
+        let mut gym = ContractGymEnv::new(ControlledSeats::Seat(North), InformationSetSelection::Simple, &InformationSetRepresentation::Sparse)
+            .with_policy(South, ContractPolicy::Heuristic(HeuristicPolicy::new()), InformationSetSelection::Simple)
+            .with_policy(West, ContractPolicy::Heuristic(HeuristicPolicy::new()), InformationSetSelection::Simple);
+        assert!(gym.reset(0).is_err());
+        let mut gym = gym.with_policy(East, ContractPolicy::Heuristic(HeuristicPolicy::new()), InformationSetSelection::Simple);
+        assert!(gym.reset(0).is_ok());
+    }
+
+    #[test]
+    fn imps_vs_reference_needs_reference_score(){
+        let mut gym = heuristic_gym(ControlledSeats::Seat(North))
+            .with_reward_scheme(RewardScheme::ImpsVsReference(ScoringPreset::Standard));
+        assert!(gym.reset_with(&west_declares()).is_err());
+        assert!(gym.reset_with(&west_declares().with_reference_score(Some(-400))).is_ok());
+    }
+}
@@ -0,0 +1,5 @@
+mod env;
+mod batch;
+
+pub use env::*;
+pub use batch::*;
@@ -18,3 +18,5 @@ pub mod error;
 pub mod options;
 pub mod generate;
 pub mod model;
+pub mod dataset;
+pub mod gym;
@@ -1,19 +1,25 @@
+use std::time::Duration;
 use log::info;
 use amfiteatr_core::agent::{InformationSet, TracingAgentGen};
 use amfiteatr_core::comm::StdAgentEndpoint;
 use amfiteatr_core::util::TensorboardSupport;
 use amfiteatr_rl::agent::RlSimpleLearningAgent;
 use amfiteatr_rl::policy::{LearnSummary, LearningNetworkPolicy, LearningNetworkPolicyDynamic, LearningNetworkPolicyGeneric, PolicyDiscreteA2C, PolicyDiscretePPO, PolicyMaskingDiscreteA2C, PolicyMaskingDiscretePPO};
-use amfiteatr_rl::tch::nn::{Adam, AdamW, VarStore};
+use amfiteatr_rl::tch::nn::{Adam, AdamW, Optimizer, VarStore};
 use amfiteatr_rl::tensor_data::{ContextEncodeTensor, TensorEncoding};
 use amfiteatr_rl::torch_net::{build_network_operator_ac, A2CNet, NeuralNetActorCritic};
 use brydz_core::amfiteatr::spec::ContractDP;
 use brydz_core::amfiteatr::state::{ActionPlaceCardConvertion1D, ContractActionWayToTensor, ContractAgentInfoSetAllKnowing, ContractAgentInfoSetAssuming, ContractAgentInfoSetSimple, ContractEnvStateComplete, ContractInfoSetConvertDense1, ContractInfoSetConvertSparse, ContractInfoSetConvertSparseHistoric, ContractInfoSetEncoding, ContractInformationSet};
 use brydz_core::deal::{ContractGameDescription, DealDistribution};
-use crate::options::contract::{AgentConfig, AgentPolicyInnerConfig, InformationSetRepresentation, InformationSetSelection};
+use crate::options::contract::{AgentConfig, AgentPolicyInnerConfig, InformationSetRepresentation, InformationSetSelection, IsmctsConfig, IsmctsRolloutSelection, PimcConfig};
 use amfiteatr_rl::tch::nn::OptimizerConfig;
 use brydz_core::player::side::{Side, SideMap};
-use crate::model::policy::ContractPolicy;
+use crate::model::policy::{ContractPolicy, ContractRollout, NetworkRollout};
+use brydz_dd::actions::HeuristicCardOrderer;
+use brydz_dd::ismcts::{HeuristicRollout, IsmctsPolicy, RandomRollout};
+use brydz_dd::policy::{DoubleDummyPolicy, PimcPolicy};
+use brydz_dd::solver::DefaultNodeStore;
+use brydz_core::amfiteatr::HeuristicPolicy;
 use brydz_core::amfiteatr::state::ContractState;
 
 pub trait SimpleContractAgentT:  RlSimpleLearningAgent<ContractDP, DealDistribution, LearnSummary>
@@ -31,51 +37,128 @@ pub struct BAgent{
 
 
 
-impl BAgent{
+pub(crate) fn tensor_encoding(representation: &InformationSetRepresentation) -> ContractInfoSetEncoding{
+    match representation{
+        InformationSetRepresentation::Dense => ContractInfoSetEncoding::Dense1(ContractInfoSetConvertDense1{}),
+        InformationSetRepresentation::Sparse => ContractInfoSetEncoding::Sparse(ContractInfoSetConvertSparse{}),
+        InformationSetRepresentation::SparseHistoric => ContractInfoSetEncoding::SparseHistoric(ContractInfoSetConvertSparseHistoric{}),
+    }
+}
 
+/// Information set of selected type built on default game, to be renewed with game description.
+pub(crate) fn initial_information_set(selection: InformationSetSelection, side: Side) -> ContractInformationSet{
+    let default_contract = ContractEnvStateComplete::default();
+
+    let default_hand = default_contract[side];
+
+    match selection{
+        InformationSetSelection::CompleteKnowledge => ContractInformationSet::AllKnowing(
+            ContractAgentInfoSetAllKnowing::new(side, SideMap {
+                north: default_contract[Side::North],
+                east: default_contract[Side::East],
+                south: default_contract[Side::South],
+                west: default_contract[Side::West],
+            }, default_contract.contract_data().clone())
+        ),
+        InformationSetSelection::DistributionAssume => ContractInformationSet::Assuming(
+            ContractAgentInfoSetAssuming::new_fair(side, default_hand, default_contract.contract_data().clone(), None)
+        ),
+        InformationSetSelection::Simple => ContractInformationSet::Simple(
+            ContractAgentInfoSetSimple::new(side, default_hand, default_contract.contract_data().clone(), None)
+        )
+    }
+}
 
+fn pimc_policy(config: &PimcConfig) -> PimcPolicy<HeuristicCardOrderer, DefaultNodeStore>{
+    let policy = PimcPolicy::new(config.samples, config.vulnerability)
+        .with_objective(config.objective)
+        .with_seed(config.seed);
+    match config.time_limit_ms{
+        Some(ms) => policy.with_time_limit(Duration::from_millis(ms)),
+        None => policy
+    }
+}
 
-    fn create_policy(config: &AgentConfig) -> anyhow::Result<ContractPolicy>{
+/// ISMCTS policy of agent, network rollout policy is masking PPO built from the rest of agent config
+/// (with weights loaded from `var_store_load` if set).
+fn ismcts_policy(config: &AgentConfig, ismcts_config: &IsmctsConfig) -> anyhow::Result<IsmctsPolicy<ContractAgentInfoSetSimple, ContractRollout>>{
+    let rollout = match ismcts_config.rollout{
+        IsmctsRolloutSelection::Random => ContractRollout::Random(RandomRollout{}),
+        IsmctsRolloutSelection::Heuristic => ContractRollout::Heuristic(HeuristicRollout::new(HeuristicPolicy::new())),
+        IsmctsRolloutSelection::Network => {
+            let network_config = AgentConfig{
+                policy: AgentPolicyInnerConfig::default(),
+                ..config.clone()
+            };
+            ContractRollout::Network(Box::new(NetworkRollout::new(
+                BAgent::create_policy(&network_config)?, config.information_set_type)))
+        }
+    };
+    Ok(IsmctsPolicy::new(rollout, ismcts_config.iterations)
+        .with_exploration(ismcts_config.exploration)
+        .with_seed(ismcts_config.seed))
+}
 
-        let tensor_encoding = match config.information_set_conversion{
-            InformationSetRepresentation::Dense => ContractInfoSetEncoding::Dense1(ContractInfoSetConvertDense1{}),
-            InformationSetRepresentation::Sparse => ContractInfoSetEncoding::Sparse(ContractInfoSetConvertSparse{}),
-            InformationSetRepresentation::SparseHistoric => ContractInfoSetEncoding::SparseHistoric(ContractInfoSetConvertSparseHistoric{}),
-        };
-        let network_input_shape = tensor_encoding.desired_shape();
+impl BAgent{
 
-        let vs =  config.policy_data.var_store_load.as_ref()
-            .map_or_else(
-                || VarStore::new(config.policy_data.device),
-                |v| VarStore::new(config.policy_data.device));
 
-        let optimizer = AdamW::default().build(&vs, config.policy_data.adam_learning_rate)?;
 
+    /// Network, its optimiser and encoding of information set for neural network policies.
+    fn network_parts(config: &AgentConfig) -> anyhow::Result<(NeuralNetActorCritic, Optimizer, ContractInfoSetEncoding)>{
+        let tensor_encoding = tensor_encoding(&config.information_set_conversion);
+        let network_input_shape = tensor_encoding.desired_shape();
 
+        let vs = VarStore::new(config.policy_data.device);
+        let optimizer = AdamW::default().build(&vs, config.policy_data.adam_learning_rate)?;
 
         let operator = build_network_operator_ac(config.policy_data.network_layers.clone(),
                                                  network_input_shape.to_vec(), 52);
-        let network = NeuralNetActorCritic::new(vs, operator);
-
-
+        let mut network = NeuralNetActorCritic::new(vs, operator);
+        // variables must exist before they are loaded
+        if let Some(path) = &config.policy_data.var_store_load{
+            network.var_store_mut().load(path)?;
+        }
+        Ok((network, optimizer, tensor_encoding))
+    }
 
+    pub(crate) fn create_policy(config: &AgentConfig) -> anyhow::Result<ContractPolicy>{
         let policy = match config.policy{
             AgentPolicyInnerConfig::MaskingPPO(policy_config) => {
-
-
+                let (network, optimizer, tensor_encoding) = Self::network_parts(config)?;
                 ContractPolicy::MaskedPpo(PolicyMaskingDiscretePPO::new(
                     policy_config, network, optimizer, tensor_encoding, ActionPlaceCardConvertion1D {}))
             }
             AgentPolicyInnerConfig::MaskingA2C(policy_config) => {
+                let (network, optimizer, tensor_encoding) = Self::network_parts(config)?;
                 ContractPolicy::MaskedA2C(PolicyMaskingDiscreteA2C::new(policy_config, network, optimizer, tensor_encoding, ActionPlaceCardConvertion1D {}))
             }
             AgentPolicyInnerConfig::PPO(policy_config) => {
+                let (network, optimizer, tensor_encoding) = Self::network_parts(config)?;
                 ContractPolicy::Ppo(PolicyDiscretePPO::new(
                     policy_config, network, optimizer, tensor_encoding, ActionPlaceCardConvertion1D {}))
             }
             AgentPolicyInnerConfig::A2C(policy_config) => {
+                let (network, optimizer, tensor_encoding) = Self::network_parts(config)?;
                 ContractPolicy::A2C(PolicyDiscreteA2C::new(policy_config, network, optimizer, tensor_encoding, ActionPlaceCardConvertion1D {}))
             }
+            // oracle and search policies do not use neural network
+            AgentPolicyInnerConfig::DoubleDummy => match config.information_set_type{
+                InformationSetSelection::CompleteKnowledge => ContractPolicy::DoubleDummy(DoubleDummyPolicy::new()),
+                other => anyhow::bail!("Double dummy policy requires complete knowledge information set, got {other:?}")
+            },
+            AgentPolicyInnerConfig::Heuristic => match config.information_set_type{
+                InformationSetSelection::Simple => ContractPolicy::Heuristic(HeuristicPolicy::new()),
+                other => anyhow::bail!("Heuristic policy requires simple information set, got {other:?}")
+            },
+            AgentPolicyInnerConfig::Pimc(pimc_config) => match config.information_set_type{
+                InformationSetSelection::Simple => ContractPolicy::Pimc(pimc_policy(&pimc_config)),
+                other => anyhow::bail!("PIMC policy requires simple information set, got {other:?}")
+            },
+            AgentPolicyInnerConfig::Ismcts(ismcts_config) => match config.information_set_type{
+                InformationSetSelection::Simple | InformationSetSelection::DistributionAssume =>
+                    ContractPolicy::Ismcts(ismcts_policy(config, &ismcts_config)?),
+                other => anyhow::bail!("ISMCTS policy requires simple or assuming information set, got {other:?}")
+            },
         };
         Ok(policy)
     }
@@ -86,26 +169,7 @@ impl BAgent{
 
     pub fn build(config: AgentConfig, side: Side, comm: StdAgentEndpoint<ContractDP>) -> anyhow::Result<Self>{
 
-        let default_contract = ContractEnvStateComplete::default();
-
-        let default_hand = default_contract[side];
-
-        let info_set = match config.information_set_type{
-            InformationSetSelection::CompleteKnowledge => ContractInformationSet::AllKnowing(
-                ContractAgentInfoSetAllKnowing::new(side, SideMap {
-                    north: default_contract[Side::North],
-                    east: default_contract[Side::East],
-                    south: default_contract[Side::South],
-                    west: default_contract[Side::West],
-                }, default_contract.contract_data().clone())
-            ),
-            InformationSetSelection::DistributionAssume => ContractInformationSet::Assuming(
-                ContractAgentInfoSetAssuming::new_fair(side, default_hand, default_contract.contract_data().clone(), None)
-            ),
-            InformationSetSelection::Simple => ContractInformationSet::Simple(
-                ContractAgentInfoSetSimple::new(side, default_hand, default_contract.contract_data().clone(), None)
-            )
-        };
+        let info_set = initial_information_set(config.information_set_type, side);
 
         let policy = Self::create_policy(&config)?;
         let reference_policy = Self::create_policy(&config)?;
@@ -141,4 +205,54 @@ impl BAgent{
     pub fn agent(&self) -> &TracingAgentGen<ContractDP, ContractPolicy, StdAgentEndpoint<ContractDP>>{
         &self.agent
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests{
+    use std::path::PathBuf;
+    use crate::model::agent::BAgent;
+    use crate::options::contract::{AgentConfig, AgentPolicyInnerConfig, InformationSetSelection, IsmctsConfig, IsmctsRolloutSelection, PolicyOuterConfig};
+
+    fn weights_path(name: &str) -> PathBuf{
+        std::env::temp_dir().join(format!("brydz_model_{name}_{}.ot", std::process::id()))
+    }
+
+    fn loading_config(path: PathBuf) -> AgentConfig{
+        AgentConfig{
+            policy_data: PolicyOuterConfig{var_store_load: Some(path), ..Default::default()},
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn network_weights_are_loaded(){
+        let (network, _, _) = BAgent::network_parts(&AgentConfig::default()).unwrap();
+        let path = weights_path("weights");
+        network.var_store().save(&path).unwrap();
+        let loaded = BAgent::network_parts(&loading_config(path.clone()));
+        std::fs::remove_file(&path).unwrap();
+        let (loaded, _, _) = loaded.unwrap();
+
+        let saved = network.var_store().variables();
+        let loaded = loaded.var_store().variables();
+        assert_eq!(loaded.len(), saved.len());
+        for (name, tensor) in loaded{
+            assert!(tensor.equal(&saved[&name]), "variable {name} was not loaded");
+        }
+    }
+
+    #[test]
+    fn ismcts_network_rollout_loads_weights(){
+        let config = AgentConfig{
+            policy: AgentPolicyInnerConfig::Ismcts(IsmctsConfig{rollout: IsmctsRolloutSelection::Network, ..Default::default()}),
+            information_set_type: InformationSetSelection::Simple,
+            ..loading_config(weights_path("missing_weights"))
+        };
+        assert!(BAgent::create_policy(&config).is_err());
+        let config = AgentConfig{
+            policy_data: PolicyOuterConfig::default(),
+            ..config
+        };
+        assert!(BAgent::create_policy(&config).is_ok());
+    }
+}
@@ -1,5 +1,5 @@
-mod agent;
+pub(crate) mod agent;
 mod model;
-mod policy;
+pub(crate) mod policy;
 
 pub use model::*;
\ No newline at end of file
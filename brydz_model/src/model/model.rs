@@ -15,7 +15,7 @@ use brydz_core::contract::ContractParameters;
 use brydz_core::deal::{ContractGameDescription, DealDistribution};
 use brydz_core::error::ContractErrorGen;
 use brydz_core::player::side::{Side, SideMap, SIDES};
-use crate::generate::generate_contracts;
+use crate::generate::{annotate_par, generate_contracts};
 use crate::model::agent::BAgent;
 use crate::options::contract::{AgentConfig, ModelConfig, TestSet};
 use crate::options::contract_generation::{ChoiceDoubling, ForceDeclarer, GenContractOptions, Subtrump};
@@ -176,6 +176,10 @@ impl GameModel{
                     let cards  = d.sample(&mut rng);
                     ContractGameDescription::new(parameters, DealDistribution::Fair, cards)
                 }
+            }.with_vulnerability(self.config.vulnerability.choose(&mut rng));
+            let seed = match self.config.reward_scheme.reference_scoring(){
+                None => seed,
+                Some(scoring) => annotate_par(seed, scoring.table())?
             };
             let summary = self.play_one_game(&seed)?;
             summaries.push(summary);
@@ -326,7 +330,9 @@ impl TryFrom<ModelConfig> for GameModel{
         hm_comm.insert(Side::South, comm_env_s);
         hm_comm.insert(Side::West, comm_env_w);
 
-        let env = HashMapEnvironment::new(ContractEnvStateComplete::default(), hm_comm);
+        let env = HashMapEnvironment::new(
+            ContractEnvStateComplete::default().with_reward_scheme(config.reward_scheme.clone()),
+            hm_comm);
 
 
         let thread_pool = rayon::ThreadPoolBuilder::new().build().unwrap();
@@ -343,11 +349,23 @@ impl TryFrom<ModelConfig> for GameModel{
 
                 let contracts_options = GenContractOptions{
                     game_count: n as u64,
+                    vulnerability: config.vulnerability,
                     .. GenContractOptions::default()
                 };
-                generate_contracts(&contracts_options)?
+                let contracts = generate_contracts(&contracts_options)?;
+                match config.reward_scheme.reference_scoring(){
+                    None => contracts,
+                    Some(scoring) => contracts.into_iter()
+                        .map(|c| annotate_par(c, scoring.table()))
+                        .collect::<Result<Vec<_>, _>>()?
+                }
             }
         };
+        if config.reward_scheme.reference_scoring().is_some(){
+            if let Some(i) = test_set_contracts.iter().position(|c| c.reference_score().is_none()){
+                anyhow::bail!("Reward scheme {:?} requires reference score, test contract {i} has none", config.reward_scheme);
+            }
+        }
         let learn_set_biased_game_distributions = match config.game_deal_biases{
             None => None,
             Some(ref bias_path) => {
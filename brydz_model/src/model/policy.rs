@@ -4,14 +4,77 @@ use amfiteatr_core::scheme::Scheme;
 use amfiteatr_rl::error::AmfiteatrRlError;
 use amfiteatr_rl::policy::{LearnSummary, LearningNetworkPolicy, LearningNetworkPolicyGeneric, PolicyDiscreteA2C, PolicyDiscretePPO, PolicyMaskingDiscreteA2C, PolicyMaskingDiscretePPO};
 use amfiteatr_rl::tch::Tensor;
+use brydz_core::amfiteatr::HeuristicPolicy;
 use brydz_core::amfiteatr::spec::ContractDP;
-use brydz_core::amfiteatr::state::{ActionPlaceCardConvertion1D, ContractInfoSetEncoding, ContractInformationSet};
+use brydz_core::amfiteatr::state::{ActionPlaceCardConvertion1D, ContractAction, ContractAgentInfoSetSimple, ContractInfoSetEncoding, ContractInformationSet};
+use brydz_core::contract::Contract;
+use brydz_core::karty::cards::Card;
+use brydz_core::karty::set::CardSetStd;
+use brydz_core::player::side::SideMap;
+use brydz_dd::actions::HeuristicCardOrderer;
+use brydz_dd::ismcts::{rollout_information_set, HeuristicRollout, IsmctsPolicy, RandomRollout, RolloutPolicy};
+use brydz_dd::policy::{DoubleDummyPolicy, PimcPolicy};
+use brydz_dd::solver::{DefaultNodeStore, DefaultOptimiser};
+use rand::rngs::StdRng;
+use crate::options::contract::InformationSetSelection;
 
 pub enum ContractPolicy{
     Ppo(PolicyDiscretePPO<ContractDP, ContractInformationSet, ContractInfoSetEncoding, ActionPlaceCardConvertion1D>),
     MaskedPpo(PolicyMaskingDiscretePPO<ContractDP, ContractInformationSet, ContractInfoSetEncoding, ActionPlaceCardConvertion1D>),
     A2C(PolicyDiscreteA2C<ContractDP, ContractInformationSet, ContractInfoSetEncoding, ActionPlaceCardConvertion1D>),
-    MaskedA2C(PolicyMaskingDiscreteA2C<ContractDP, ContractInformationSet, ContractInfoSetEncoding, ActionPlaceCardConvertion1D>)
+    MaskedA2C(PolicyMaskingDiscreteA2C<ContractDP, ContractInformationSet, ContractInfoSetEncoding, ActionPlaceCardConvertion1D>),
+    /// Double dummy oracle, requires complete knowledge information set and does not learn.
+    DoubleDummy(DoubleDummyPolicy<DefaultOptimiser, DefaultNodeStore>),
+    /// Rule based reference player, requires simple information set and does not learn.
+    Heuristic(HeuristicPolicy),
+    /// Perfect information Monte Carlo player, requires simple information set and does not learn.
+    Pimc(PimcPolicy<HeuristicCardOrderer, DefaultNodeStore>),
+    /// Information set Monte Carlo tree search player, requires simple or assuming information set
+    /// and does not learn (also network used in rollouts).
+    Ismcts(IsmctsPolicy<ContractAgentInfoSetSimple, ContractRollout>),
+}
+
+/// Rollouts played by another agent policy (typically neural network) seeing determinized deal
+/// through information set of selected type.
+pub struct NetworkRollout{
+    policy: ContractPolicy,
+    information_set_type: InformationSetSelection,
+}
+
+impl NetworkRollout{
+    pub fn new(policy: ContractPolicy, information_set_type: InformationSetSelection) -> Self{
+        Self{policy, information_set_type}
+    }
+}
+
+impl RolloutPolicy for NetworkRollout{
+    fn rollout_card(&self, contract: &Contract, hands: &SideMap<CardSetStd>, _rng: &mut StdRng) -> Option<Card> {
+        let info_set = match self.information_set_type{
+            InformationSetSelection::Simple => ContractInformationSet::Simple(rollout_information_set(contract, hands)),
+            InformationSetSelection::DistributionAssume => ContractInformationSet::Assuming(rollout_information_set(contract, hands)),
+            InformationSetSelection::CompleteKnowledge => return None
+        };
+        match self.policy.select_action(&info_set).ok()?{
+            ContractAction::PlaceCard(card) => Some(card),
+            ContractAction::ShowHand(_) => None
+        }
+    }
+}
+
+pub enum ContractRollout{
+    Random(RandomRollout),
+    Heuristic(HeuristicRollout),
+    Network(Box<NetworkRollout>),
+}
+
+impl RolloutPolicy for ContractRollout{
+    fn rollout_card(&self, contract: &Contract, hands: &SideMap<CardSetStd>, rng: &mut StdRng) -> Option<Card> {
+        match self{
+            ContractRollout::Random(rollout) => rollout.rollout_card(contract, hands, rng),
+            ContractRollout::Heuristic(rollout) => rollout.rollout_card(contract, hands, rng),
+            ContractRollout::Network(rollout) => rollout.rollout_card(contract, hands, rng),
+        }
+    }
 }
 
 impl Policy<ContractDP> for ContractPolicy {
@@ -26,6 +89,31 @@ impl Policy<ContractDP> for ContractPolicy {
             ContractPolicy::MaskedPpo(ppos) => ppos.select_action(state),
             ContractPolicy::A2C(a2c) => a2c.select_action(state),
             ContractPolicy::MaskedA2C(a2c) => a2c.select_action(state),
+            ContractPolicy::DoubleDummy(oracle) => match state{
+                ContractInformationSet::AllKnowing(info_set) => oracle.select_action(info_set),
+                _ => Err(AmfiteatrError::NoActionAvailable {
+                    context: "Double dummy policy requires complete knowledge information set".into()
+                })
+            },
+            ContractPolicy::Heuristic(heuristic) => match state{
+                ContractInformationSet::Simple(info_set) => heuristic.select_action(info_set),
+                _ => Err(AmfiteatrError::NoActionAvailable {
+                    context: "Heuristic policy requires simple information set".into()
+                })
+            },
+            ContractPolicy::Pimc(pimc) => match state{
+                ContractInformationSet::Simple(info_set) => pimc.select_action(info_set),
+                _ => Err(AmfiteatrError::NoActionAvailable {
+                    context: "PIMC policy requires simple information set".into()
+                })
+            },
+            ContractPolicy::Ismcts(ismcts) => match state{
+                ContractInformationSet::Simple(info_set) => ismcts.select_action_for(info_set),
+                ContractInformationSet::Assuming(info_set) => ismcts.select_action_for(info_set),
+                ContractInformationSet::AllKnowing(_) => Err(AmfiteatrError::NoActionAvailable {
+                    context: "ISMCTS policy requires simple or assuming information set".into()
+                })
+            }
         }
     }
 }
@@ -39,6 +127,7 @@ impl LearningNetworkPolicyGeneric<ContractDP> for ContractPolicy{
             ContractPolicy::MaskedPpo(policy) => policy.switch_explore(enabled),
             ContractPolicy::A2C(policy) => policy.switch_explore(enabled),
             ContractPolicy::MaskedA2C(policy) => policy.switch_explore(enabled),
+            ContractPolicy::DoubleDummy(_) | ContractPolicy::Heuristic(_) | ContractPolicy::Pimc(_) | ContractPolicy::Ismcts(_) => {},
         }
     }
 
@@ -48,6 +137,7 @@ impl LearningNetworkPolicyGeneric<ContractDP> for ContractPolicy{
             ContractPolicy::MaskedPpo(policy) => policy.train_generic(trajectories, reward_f),
             ContractPolicy::A2C(policy) => policy.train_generic(trajectories, reward_f),
             ContractPolicy::MaskedA2C(policy) => policy.train_generic(trajectories, reward_f),
+            ContractPolicy::DoubleDummy(_) | ContractPolicy::Heuristic(_) | ContractPolicy::Pimc(_) | ContractPolicy::Ismcts(_) => Ok(LearnSummary::default()),
         }
     }
 
@@ -57,6 +147,7 @@ impl LearningNetworkPolicyGeneric<ContractDP> for ContractPolicy{
             ContractPolicy::MaskedPpo(policy) => policy.set_gradient_tracing(enabled),
             ContractPolicy::A2C(policy) => policy.set_gradient_tracing(enabled),
             ContractPolicy::MaskedA2C(policy) => policy.set_gradient_tracing(enabled),
+            ContractPolicy::DoubleDummy(_) | ContractPolicy::Heuristic(_) | ContractPolicy::Pimc(_) | ContractPolicy::Ismcts(_) => {},
         }
     }
 }
\ No newline at end of file
@@ -6,6 +6,10 @@ use amfiteatr_rl::tch;
 use amfiteatr_rl::tch::Device;
 use amfiteatr_rl::torch_net::Layer;
 use brydz_core::player::side::{Side, SideMap};
+use brydz_core::score::{RewardScheme, Vulnerability};
+use brydz_dd::ismcts::DEFAULT_EXPLORATION;
+use brydz_dd::single_dummy::SingleDummyObjective;
+use crate::options::contract_generation::ChoiceVulnerability;
 
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -55,9 +59,94 @@ pub enum AgentPolicyInnerConfig{
     MaskingA2C(ConfigA2C),
     PPO(ConfigPPO),
     A2C(ConfigA2C),
+    /// Plays double dummy optimal cards, usable as oracle opponent or teacher.
+    /// Requires [`InformationSetSelection::CompleteKnowledge`].
+    DoubleDummy,
+    /// Rule based card play (second hand low, third hand high, drawing trumps, etc.).
+    /// Requires [`InformationSetSelection::Simple`].
+    Heuristic,
+    /// Perfect information Monte Carlo play. Requires [`InformationSetSelection::Simple`].
+    Pimc(PimcConfig),
+    /// Information set Monte Carlo tree search. Requires [`InformationSetSelection::Simple`] or
+    /// [`InformationSetSelection::DistributionAssume`].
+    Ismcts(IsmctsConfig),
 
 }
 
+fn default_pimc_samples() -> usize{
+    32
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+pub struct PimcConfig{
+    /// Maximal number of layouts solved per decision.
+    #[serde(default = "default_pimc_samples")]
+    pub samples: usize,
+    /// Time limit of single decision in milliseconds.
+    #[serde(default)]
+    pub time_limit_ms: Option<u64>,
+    #[serde(default)]
+    pub objective: SingleDummyObjective,
+    #[serde(default)]
+    pub vulnerability: Vulnerability,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl Default for PimcConfig{
+    fn default() -> Self {
+        Self{
+            samples: default_pimc_samples(),
+            time_limit_ms: None,
+            objective: SingleDummyObjective::default(),
+            vulnerability: Vulnerability::None,
+            seed: 0,
+        }
+    }
+}
+
+/// Policy finishing deals in ISMCTS rollouts.
+#[derive(Copy, Clone, Serialize, Deserialize, ValueEnum, Default, Debug)]
+pub enum IsmctsRolloutSelection{
+    Random,
+    #[default]
+    Heuristic,
+    /// Masking PPO network built from `policy_data` and information set settings of agent.
+    Network,
+}
+
+fn default_ismcts_iterations() -> usize{
+    1000
+}
+
+fn default_ismcts_exploration() -> f64{
+    DEFAULT_EXPLORATION
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+pub struct IsmctsConfig{
+    /// Number of sampled determinizations (and rollouts) per decision.
+    #[serde(default = "default_ismcts_iterations")]
+    pub iterations: usize,
+    #[serde(default = "default_ismcts_exploration")]
+    pub exploration: f64,
+    #[serde(default)]
+    pub rollout: IsmctsRolloutSelection,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl Default for IsmctsConfig{
+    fn default() -> Self {
+        Self{
+            iterations: default_ismcts_iterations(),
+            exploration: default_ismcts_exploration(),
+            rollout: IsmctsRolloutSelection::default(),
+            seed: 0,
+        }
+    }
+}
+
 impl Default for AgentPolicyInnerConfig{
     fn default() -> Self {
         Self::MaskingPPO(ConfigPPO::default())
@@ -116,6 +205,10 @@ pub struct ModelConfig{
     pub test_set: TestSet,
     pub game_deal_biases: Option<PathBuf>,
     pub force_declarer_when_rand: Option<Side>,
+    #[serde(default)]
+    pub reward_scheme: RewardScheme,
+    #[serde(default)]
+    pub vulnerability: ChoiceVulnerability,
 }
 
 impl std::ops::Index<Side> for ModelConfig{
@@ -1,7 +1,10 @@
 use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use brydz_core::player::side::Side;
+use brydz_core::score::Vulnerability;
+use brydz_core::score::tables::ScoringPreset;
 use crate::error::BrydzModelError;
 use crate::error::GenError::ConvForceDeclarerNoToSide;
 use clap::Args;
@@ -42,6 +45,47 @@ impl TryFrom<&ForceDeclarer> for Side{
 
 
 
+#[derive(Debug, ValueEnum, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum ChoiceVulnerability{
+    #[default]
+    None,
+    NorthSouth,
+    EastWest,
+    Both,
+    /// Vulnerability drawn uniformly for every board.
+    Any
+}
+
+impl ChoiceVulnerability{
+    pub fn choose<R: rand::Rng>(&self, rng: &mut R) -> Vulnerability{
+        match self{
+            ChoiceVulnerability::None => Vulnerability::None,
+            ChoiceVulnerability::NorthSouth => Vulnerability::NorthSouth,
+            ChoiceVulnerability::EastWest => Vulnerability::EastWest,
+            ChoiceVulnerability::Both => Vulnerability::Both,
+            ChoiceVulnerability::Any => Vulnerability::new(rng.random(), rng.random()),
+        }
+    }
+}
+
+/// Scoring rules used to compute par score annotated on generated boards.
+#[derive(Debug, ValueEnum, Clone, Copy)]
+pub enum ParScoring{
+    Standard,
+    Pre2007,
+    Pre1987,
+}
+
+impl From<ParScoring> for ScoringPreset{
+    fn from(value: ParScoring) -> Self {
+        match value{
+            ParScoring::Standard => ScoringPreset::Standard,
+            ParScoring::Pre2007 => ScoringPreset::Pre2007,
+            ParScoring::Pre1987 => ScoringPreset::Pre1987,
+        }
+    }
+}
+
 #[derive(Debug, ValueEnum, Clone,)]
 pub enum Subtrump{
     All,
@@ -80,6 +124,10 @@ pub struct GenContractOptions {
     pub force_declarer: ForceDeclarer,
     #[arg(short = 'd', long = "doubling", help = "Force one side to be declarer", default_value_t = ChoiceDoubling::No, value_enum)]
     pub choice_doubling: ChoiceDoubling,
+    #[arg(short = 'v', long = "vulnerability", help = "Vulnerability of generated boards", default_value_t = ChoiceVulnerability::None, value_enum)]
+    pub vulnerability: ChoiceVulnerability,
+    #[arg(long = "par", help = "Annotate boards with double dummy par score (reference score for IMPs) computed with scoring rules", value_enum)]
+    pub par: Option<ParScoring>,
 
     #[arg(short = 'F', long = "format", help = "Generated biased distributions format", default_value = "ron")]
     pub format: DataFormat,
@@ -100,6 +148,8 @@ impl Default for GenContractOptions{
             trump_limit: Subtrump::All,
             force_declarer: ForceDeclarer::DontForce,
             choice_doubling: ChoiceDoubling::Any,
+            vulnerability: ChoiceVulnerability::None,
+            par: None,
             format: DataFormat::Ron,
         }
     }
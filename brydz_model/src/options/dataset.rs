@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+use clap::Args;
+use crate::options::contract::{InformationSetRepresentation, InformationSetSelection};
+
+#[derive(Debug, Args)]
+pub struct DatasetOptions{
+    #[arg(short = 'i', long = "input", help = "File with game descriptions (RON or YAML), when not given games are generated")]
+    pub input: Option<PathBuf>,
+    #[arg(short = 'g', long = "game-count", help = "Number of games to generate when no input is given", default_value = "100")]
+    pub game_count: u64,
+    #[arg(short = 'c', long = "config", help = "Model configuration with agents playing games, by default every agent plays heuristic cards")]
+    pub config: Option<PathBuf>,
+    #[arg(short = 's', long = "information-set", help = "Type of recorded information set of acting agent", value_enum, default_value_t = InformationSetSelection::Simple)]
+    pub information_set_type: InformationSetSelection,
+    #[arg(short = 'e', long = "encoding", help = "Tensor encoding of recorded information set", value_enum, default_value_t = InformationSetRepresentation::Sparse)]
+    pub information_set_conversion: InformationSetRepresentation,
+    #[arg(short = 'o', long = "output", help = "Path to dataset file")]
+    pub output: PathBuf,
+    #[arg(long = "dd-cache", help = "Double dummy cache file, consulted before solving positions and extended with new results")]
+    pub dd_cache: Option<PathBuf>,
+}
@@ -2,12 +2,14 @@ use clap::{Subcommand, ValueEnum};
 
 use crate::options::bias_generation::BiasDistributionOptions;
 use crate::options::contract_generation::GenContractOptions;
+use crate::options::dataset::DatasetOptions;
 
 pub mod contract_generation;
 pub mod bias_generation;
 pub mod logger;
 
 pub mod contract;
+pub mod dataset;
 
 #[derive(ValueEnum)]
 #[derive(Clone, Debug)]
@@ -81,6 +83,8 @@ impl Display for Subtrump{
 #[derive(Subcommand)]
 pub enum GenerateSubcommand{
     Contract(GenContractOptions),
-    Distribution(BiasDistributionOptions)
+    Distribution(BiasDistributionOptions),
+    /// Plays games and records information sets of acting agents with double dummy values of cards
+    Dataset(DatasetOptions),
 
 }
\ No newline at end of file